@@ -1,6 +1,6 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, FnArg, ImplItemMethod};
+use syn::{parse_macro_input, FnArg, ImplItemMethod, Lit, Meta, MetaNameValue};
 
 use crate::common::{arg_is_mutable_receiver, assert_reference_type};
 
@@ -22,3 +22,35 @@ pub fn verify_hook(item: TokenStream) -> TokenStream {
 
     TokenStream::from(quote! {#method_item})
 }
+
+pub fn verify_migrate(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr = parse_macro_input!(attr as MetaNameValue);
+    parse_migrate_from(&attr);
+
+    verify_hook(item)
+}
+
+// Parse `from = N` of `#[migrate(from = N)]`.
+pub fn parse_migrate_from(name_value: &MetaNameValue) -> u64 {
+    if !name_value.path.is_ident("from") {
+        panic!("The migrate attribute should be `#[migrate(from = N)]`.")
+    }
+
+    match &name_value.lit {
+        Lit::Int(from) => from
+            .base10_parse::<u64>()
+            .expect("The version to migrate from should be an u64."),
+        _ => panic!("The version to migrate from should be an u64."),
+    }
+}
+
+// Parse the whole `#[migrate(from = N)]` attribute of a method.
+pub fn parse_migrate_attr(meta: &Meta) -> u64 {
+    match meta {
+        Meta::List(list) if list.nested.len() == 1 => match &list.nested[0] {
+            syn::NestedMeta::Meta(Meta::NameValue(name_value)) => parse_migrate_from(name_value),
+            _ => panic!("The migrate attribute should be `#[migrate(from = N)]`."),
+        },
+        _ => panic!("The migrate attribute should be `#[migrate(from = N)]`."),
+    }
+}
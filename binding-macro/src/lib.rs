@@ -9,7 +9,7 @@ mod service;
 use proc_macro::TokenStream;
 
 use crate::cycles::gen_cycles_code;
-use crate::hooks::verify_hook;
+use crate::hooks::{verify_hook, verify_migrate};
 use crate::read_write::verify_read_or_write;
 use crate::service::gen_service_code;
 
@@ -168,6 +168,32 @@ pub fn hook_before(_: TokenStream, item: TokenStream) -> TokenStream {
     verify_hook(item)
 }
 
+#[rustfmt::skip]
+/// `#[migrate(from = N)]` marks a method which transforms the stored state of
+/// the service from version `N`. It runs once, before the first block of the
+/// height where the mapping activates a later version. If it panics, its writes
+/// are dropped, the later migrations of the service are skipped and the block
+/// goes on.
+///
+/// Method input params should be `(&mut self, params: &ExecutorParams)`
+///
+/// # Example:
+///
+/// ```rust
+/// struct Service;
+/// #[service]
+/// impl Service {
+///     #[migrate(from = 1)]
+///     fn migrate_from_v1(&mut self, params: &ExecutorParams) {
+///         do_work();
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn migrate(attr: TokenStream, item: TokenStream) -> TokenStream {
+    verify_migrate(attr, item)
+}
+
 #[rustfmt::skip]
 /// Marking a ImplItem for service, it will automatically trait
 /// `protocol::traits::Service`.
@@ -1,6 +1,8 @@
 use proc_macro::TokenStream;
 use quote::quote;
-use syn::{parse_macro_input, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl, Type};
+use syn::{parse_macro_input, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl, ReturnType, Type};
+
+use crate::hooks::parse_migrate_attr;
 
 const READ_ATTRIBUTE: &str = "read";
 const WRITE_ATTRIBUTE: &str = "write";
@@ -9,6 +11,7 @@ const HOOK_BEFORE_ATTRIBUTE: &str = "hook_before";
 const HOOK_AFTER_ATTRIBUTE: &str = "hook_after";
 const TX_HOOK_BEFORE_ATTRIBUTE: &str = "tx_hook_before";
 const TX_HOOK_AFTER_ATTRIBUTE: &str = "tx_hook_after";
+const MIGRATE_ATTRIBUTE: &str = "migrate";
 
 enum ServiceMethod {
     Read(ImplItemMethod),
@@ -18,7 +21,7 @@ enum ServiceMethod {
 struct Hooks {
     before:    Option<Ident>,
     after:     Option<Ident>,
-    tx_before: Option<Ident>,
+    tx_before: Option<ImplItemMethod>,
     tx_after:  Option<Ident>,
 }
 
@@ -64,8 +67,27 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
     };
     let tx_hook_before = &hooks.tx_before;
     let tx_hook_before_body = match tx_hook_before {
-        Some(tx_hook_before) => quote! { self.#tx_hook_before(_ctx) },
-        None => quote! {()},
+        // The tx hook before may reject the tx by returning a `ServiceResponse` with a
+        // non-zero code.
+        Some(tx_hook_before) if has_return_value(tx_hook_before) => {
+            let tx_hook_before = &tx_hook_before.sig.ident;
+            quote! {
+                let res = self.#tx_hook_before(_ctx);
+                if res.is_error() {
+                    ServiceResponse::<String>::from_error(res.code, res.error_message.clone())
+                } else {
+                    ServiceResponse::<String>::from_succeed("".to_owned())
+                }
+            }
+        }
+        Some(tx_hook_before) => {
+            let tx_hook_before = &tx_hook_before.sig.ident;
+            quote! {
+                self.#tx_hook_before(_ctx);
+                ServiceResponse::<String>::from_succeed("".to_owned())
+            }
+        }
+        None => quote! { ServiceResponse::<String>::from_succeed("".to_owned()) },
     };
     let tx_hook_after = &hooks.tx_after;
     let tx_hook_after_body = match tx_hook_after {
@@ -73,6 +95,18 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
         None => quote! {()},
     };
 
+    let (list_migrate_from, list_migrate_ident) = extract_migrations(items);
+    let migrate_body = if list_migrate_from.is_empty() {
+        quote! {()}
+    } else {
+        quote! {
+            match _from {
+                #(#list_migrate_from => self.#list_migrate_ident(_params),)*
+                _ => (),
+            }
+        }
+    };
+
     let list_method_meta: Vec<MethodMeta> = methods.into_iter().map(extract_method_meta).collect();
 
     let (list_read_name, list_read_ident, list_read_payload) =
@@ -99,7 +133,7 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
                 #hook_after_body
             }
 
-            fn tx_hook_before_(&mut self, _ctx: ServiceContext) {
+            fn tx_hook_before_(&mut self, _ctx: ServiceContext) -> ServiceResponse<String> {
                 #tx_hook_before_body
             }
 
@@ -107,6 +141,10 @@ pub fn gen_service_code(_: TokenStream, item: TokenStream) -> TokenStream {
                 #tx_hook_after_body
             }
 
+            fn migrate_(&mut self, _params: &ExecutorParams, _from: u64) {
+                #migrate_body
+            }
+
             fn read_(&self, ctx: protocol::types::ServiceContext) -> ServiceResponse<String> {
                 let service = ctx.get_service_name();
                 let method = ctx.get_service_method();
@@ -335,7 +373,7 @@ fn extract_hooks(items: &[ImplItem]) -> Hooks {
                     }
                 } else if segment.ident == TX_HOOK_BEFORE_ATTRIBUTE {
                     if tx_before_count == 0 {
-                        hooks.tx_before = Some(method.sig.ident.clone());
+                        hooks.tx_before = Some(method.clone());
                         tx_before_count = 1;
                     } else {
                         panic!("The tx before hook can only have one")
@@ -355,6 +393,42 @@ fn extract_hooks(items: &[ImplItem]) -> Hooks {
     hooks
 }
 
+// The `#[migrate(from = N)]` methods, as the versions and the method idents.
+fn extract_migrations(items: &[ImplItem]) -> (Vec<u64>, Vec<Ident>) {
+    let methods: Vec<ImplItemMethod> = find_list_for_item_method(items);
+
+    let mut froms = vec![];
+    let mut idents = vec![];
+
+    for method in methods {
+        for attr in &method.attrs {
+            if !attr.path.is_ident(MIGRATE_ATTRIBUTE) {
+                continue;
+            }
+
+            let meta = attr
+                .parse_meta()
+                .unwrap_or_else(|e| panic!("parse migrate attribute failed: {:?}", e));
+            let from = parse_migrate_attr(&meta);
+            if froms.contains(&from) {
+                panic!("The migration from version {} can only have one", from)
+            }
+
+            froms.push(from);
+            idents.push(method.sig.ident.clone());
+        }
+    }
+
+    (froms, idents)
+}
+
+fn has_return_value(method: &ImplItemMethod) -> bool {
+    match method.sig.output {
+        ReturnType::Default => false,
+        ReturnType::Type(..) => true,
+    }
+}
+
 fn find_list_for_item_method(items: &[ImplItem]) -> Vec<ImplItemMethod> {
     items
         .iter()
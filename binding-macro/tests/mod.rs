@@ -11,8 +11,8 @@ use serde::{Deserialize, Serialize};
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
-    ExecutorParams, Service, ServiceResponse, ServiceSDK, StoreArray, StoreBool, StoreMap,
-    StoreString, StoreUint64,
+    ExecutorParams, Service, ServiceRegistration, ServiceResponse, ServiceSDK, StoreArray,
+    StoreBool, StoreMap, StoreString, StoreUint64,
 };
 use protocol::types::{
     Address, Block, Hash, Receipt, ServiceContext, ServiceContextParams, SignedTransaction,
@@ -392,6 +392,42 @@ fn test_service_none_response() {
     assert_eq!(test_service.hook_after, true);
 }
 
+#[test]
+fn test_service_migrate() {
+    struct Tests<SDK: ServiceSDK> {
+        _sdk:     SDK,
+        migrated: Vec<u64>,
+    }
+
+    #[service]
+    impl<SDK: ServiceSDK> Tests<SDK> {
+        #[migrate(from = 0)]
+        fn migrate_from_v0(&mut self, _params: &ExecutorParams) {
+            self.migrated.push(0);
+        }
+
+        #[migrate(from = 2)]
+        fn migrate_from_v2(&mut self, _params: &ExecutorParams) {
+            self.migrated.push(2);
+        }
+
+        #[write]
+        fn test_write(&mut self, _ctx: ServiceContext) -> ServiceResponse<()> {
+            ServiceResponse::<()>::from_succeed(())
+        }
+    }
+
+    let mut test_service = Tests {
+        _sdk:     MockServiceSDK {},
+        migrated: vec![],
+    };
+
+    for from in 0..4 {
+        test_service.migrate_(&mock_executor_params(), from);
+    }
+    assert_eq!(test_service.migrated, vec![0, 2]);
+}
+
 fn get_context(cycles_limit: u64, service: &str, method: &str, payload: &str) -> ServiceContext {
     let params = ServiceContextParams {
         tx_hash: None,
@@ -402,6 +438,7 @@ fn get_context(cycles_limit: u64, service: &str, method: &str, payload: &str) ->
         caller: Address::from_hash(Hash::from_empty()).unwrap(),
         height: 1,
         timestamp: 0,
+        random_seed: Hash::from_empty(),
         service_name: service.to_owned(),
         service_method: method.to_owned(),
         service_payload: payload.to_owned(),
@@ -418,6 +455,7 @@ fn mock_executor_params() -> ExecutorParams {
         height:       9,
         timestamp:    99,
         cycles_limit: 99999,
+        random_seed:  Hash::default(),
     }
 }
 
@@ -529,4 +567,46 @@ impl ServiceSDK for MockServiceSDK {
     ) -> ServiceResponse<String> {
         unimplemented!()
     }
+
+    fn checkpoint(&mut self) {
+        unimplemented!()
+    }
+
+    fn revert_to_checkpoint(&mut self) {
+        unimplemented!()
+    }
+
+    fn release_checkpoint(&mut self) {
+        unimplemented!()
+    }
+
+    // Register a service instance, only for the governance service
+    fn register_service(
+        &mut self,
+        _ctx: &ServiceContext,
+        _registration: ServiceRegistration,
+    ) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+
+    fn schedule(
+        &mut self,
+        _ctx: &ServiceContext,
+        _height: u64,
+        _service: &str,
+        _method: &str,
+        _payload: &str,
+    ) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+
+    fn emit_event<Data: Serialize>(
+        &self,
+        _ctx: &ServiceContext,
+        _name: &str,
+        _topics: Vec<String>,
+        _data: &Data,
+    ) {
+        unimplemented!()
+    }
 }
@@ -7,15 +7,19 @@ use std::collections::BTreeMap;
 use bytes::Bytes;
 
 use binding_macro::{cycles, genesis, service};
-use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
+use protocol::traits::{ExecutorParams, FeeHook, ServiceResponse, ServiceSDK, StoreMap};
 use protocol::types::{Address, Hash, ServiceContext};
 
 use crate::types::{
-    ApproveEvent, ApprovePayload, Asset, AssetBalance, CreateAssetPayload, GetAllowancePayload,
-    GetAllowanceResponse, GetAssetPayload, GetBalancePayload, GetBalanceResponse,
-    InitGenesisPayload, TransferEvent, TransferFromEvent, TransferFromPayload, TransferPayload,
+    ApproveEvent, ApprovePayload, Asset, AssetBalance, ChargeFeeEvent, ChargeFeePayload,
+    CreateAssetPayload, GetAllowancePayload, GetAllowanceResponse, GetAssetPayload,
+    GetBalancePayload, GetBalanceResponse, InitGenesisPayload, TransferEvent, TransferFromEvent,
+    TransferFromPayload, TransferPayload,
 };
 
+// The asset created at genesis, fees are paid in it.
+const NATIVE_ASSET_KEY: &str = "native_asset";
+
 pub struct AssetService<SDK> {
     sdk:    SDK,
     assets: Box<dyn StoreMap<Hash, Asset>>,
@@ -40,6 +44,8 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
         };
 
         self.assets.insert(asset.id.clone(), asset.clone());
+        self.sdk
+            .set_value(NATIVE_ASSET_KEY.to_owned(), asset.id.clone());
 
         let asset_balance = AssetBalance {
             value:     payload.supply,
@@ -167,13 +173,12 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
         self.sdk
             .set_account_value(&asset.issuer, asset.id.clone(), asset_balance);
 
-        let event_res = serde_json::to_string(&asset);
-
-        if let Err(e) = event_res {
-            return ServiceResponse::<Asset>::from_error(103, format!("{:?}", e));
-        }
-        let event_str = event_res.unwrap();
-        ctx.emit_event(event_str);
+        self.sdk.emit_event(
+            &ctx,
+            "CreateAssetEvent",
+            vec![asset.id.as_hex(), asset.issuer.as_hex()],
+            &asset,
+        );
 
         ServiceResponse::<Asset>::from_succeed(asset)
     }
@@ -200,13 +205,16 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             to,
             value,
         };
-        let event_res = serde_json::to_string(&event);
-
-        if let Err(e) = event_res {
-            return ServiceResponse::<()>::from_error(103, format!("{:?}", e));
-        };
-        let event_str = event_res.unwrap();
-        ctx.emit_event(event_str);
+        self.sdk.emit_event(
+            &ctx,
+            "TransferEvent",
+            vec![
+                event.asset_id.as_hex(),
+                event.from.as_hex(),
+                event.to.as_hex(),
+            ],
+            &event,
+        );
 
         ServiceResponse::<()>::from_succeed(())
     }
@@ -249,13 +257,16 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             grantee: to,
             value,
         };
-        let event_res = serde_json::to_string(&event);
-
-        if let Err(e) = event_res {
-            return ServiceResponse::<()>::from_error(103, format!("{:?}", e));
-        };
-        let event_str = event_res.unwrap();
-        ctx.emit_event(event_str);
+        self.sdk.emit_event(
+            &ctx,
+            "ApproveEvent",
+            vec![
+                event.asset_id.as_hex(),
+                event.grantor.as_hex(),
+                event.grantee.as_hex(),
+            ],
+            &event,
+        );
 
         ServiceResponse::<()>::from_succeed(())
     }
@@ -311,13 +322,79 @@ impl<SDK: ServiceSDK> AssetService<SDK> {
             recipient,
             value,
         };
-        let event_res = serde_json::to_string(&event);
+        self.sdk.emit_event(
+            &ctx,
+            "TransferFromEvent",
+            vec![
+                event.asset_id.as_hex(),
+                event.sender.as_hex(),
+                event.recipient.as_hex(),
+            ],
+            &event,
+        );
+
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    // Fee hook of the executor, burns the fee from the caller's native asset
+    // balance. It consumes no cycles, since it runs after the tx's cycles are
+    // counted. Only the executor may call it, or any service could burn the
+    // balance of its caller.
+    #[write]
+    fn charge_fee(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ChargeFeePayload,
+    ) -> ServiceResponse<()> {
+        if !FeeHook::is_hook_call(&ctx) {
+            return ServiceResponse::<()>::from_error(
+                108,
+                "charge fee is only called by the executor".to_owned(),
+            );
+        }
 
-        if let Err(e) = event_res {
-            return ServiceResponse::<()>::from_error(103, format!("{:?}", e));
+        let caller = ctx.get_caller();
+        let asset_id: Hash = match self.sdk.get_value(&NATIVE_ASSET_KEY.to_owned()) {
+            Some(asset_id) => asset_id,
+            None => {
+                return ServiceResponse::<()>::from_error(107, "native asset not set".to_owned())
+            }
+        };
+
+        let mut asset = match self.assets.get(&asset_id) {
+            Some(asset) => asset,
+            None => {
+                return ServiceResponse::<()>::from_error(101, "asset id not existed".to_owned())
+            }
+        };
+        let mut caller_asset_balance: AssetBalance = self
+            .sdk
+            .get_account_value(&caller, &asset_id)
+            .unwrap_or(AssetBalance {
+                value:     0,
+                allowance: BTreeMap::new(),
+            });
+        if caller_asset_balance.value < payload.fee {
+            return ServiceResponse::<()>::from_error(105, "insufficient balance".to_owned());
+        }
+
+        caller_asset_balance.value -= payload.fee;
+        self.sdk
+            .set_account_value(&caller, asset_id.clone(), caller_asset_balance);
+        asset.supply -= payload.fee;
+        self.assets.insert(asset_id.clone(), asset);
+
+        let event = ChargeFeeEvent {
+            asset_id,
+            payer: caller,
+            fee: payload.fee,
         };
-        let event_str = event_res.unwrap();
-        ctx.emit_event(event_str);
+        self.sdk.emit_event(
+            &ctx,
+            "ChargeFeeEvent",
+            vec![event.asset_id.as_hex(), event.payer.as_hex()],
+            &event,
+        );
 
         ServiceResponse::<()>::from_succeed(())
     }
@@ -7,15 +7,15 @@ use cita_trie::MemoryDB;
 
 use framework::binding::sdk::{DefalutServiceSDK, DefaultChainQuerier};
 use framework::binding::state::{GeneralServiceState, MPTTrie};
-use protocol::traits::{Context, NoopDispatcher, Storage};
+use protocol::traits::{Context, NoopDispatcher, Storage, FEE_HOOK_EXTRA};
 use protocol::types::{
     Address, Block, Hash, Proof, Receipt, ServiceContext, ServiceContextParams, SignedTransaction,
 };
 use protocol::{types::Bytes, ProtocolResult};
 
 use crate::types::{
-    ApprovePayload, CreateAssetPayload, GetAllowancePayload, GetAssetPayload, GetBalancePayload,
-    TransferFromPayload, TransferPayload,
+    ApprovePayload, ChargeFeePayload, CreateAssetPayload, GetAllowancePayload, GetAssetPayload,
+    GetBalancePayload, InitGenesisPayload, TransferFromPayload, TransferPayload,
 };
 use crate::AssetService;
 
@@ -194,6 +194,55 @@ fn test_transfer_from() {
     assert_eq!(balance_res.balance, 24);
 }
 
+#[test]
+fn test_charge_fee() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller.clone());
+
+    let mut service = new_asset_service();
+
+    let asset_id =
+        Hash::from_hex("0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c")
+            .unwrap();
+    let supply = 1024 * 1024;
+    service.init_genesis(InitGenesisPayload {
+        id: asset_id.clone(),
+        name: "test".to_owned(),
+        symbol: "test".to_owned(),
+        supply,
+        issuer: caller.clone(),
+    });
+
+    // Only the executor's fee hook call is accepted
+    let res = service.charge_fee(context.clone(), ChargeFeePayload { fee: 1024 });
+    assert_eq!(res.code, 108);
+
+    let context = mock_context_with_extra(
+        cycles_limit,
+        caller.clone(),
+        Some(Bytes::from(FEE_HOOK_EXTRA)),
+    );
+    let res = service.charge_fee(context.clone(), ChargeFeePayload { fee: 1024 });
+    assert!(!res.is_error());
+
+    let balance_res = service
+        .get_balance(context.clone(), GetBalancePayload {
+            asset_id: asset_id.clone(),
+            user:     caller,
+        })
+        .succeed_data;
+    assert_eq!(balance_res.balance, supply - 1024);
+
+    let asset = service
+        .get_asset(context.clone(), GetAssetPayload { id: asset_id })
+        .succeed_data;
+    assert_eq!(asset.supply, supply - 1024);
+
+    let res = service.charge_fee(context, ChargeFeePayload { fee: supply });
+    assert!(res.is_error());
+}
+
 fn new_asset_service() -> AssetService<
     DefalutServiceSDK<
         GeneralServiceState<MemoryDB>,
@@ -215,6 +264,14 @@ fn new_asset_service() -> AssetService<
 }
 
 fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    mock_context_with_extra(cycles_limit, caller, None)
+}
+
+fn mock_context_with_extra(
+    cycles_limit: u64,
+    caller: Address,
+    extra: Option<Bytes>,
+) -> ServiceContext {
     let params = ServiceContextParams {
         tx_hash: None,
         nonce: None,
@@ -224,10 +281,11 @@ fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
         caller,
         height: 1,
         timestamp: 0,
+        random_seed: Hash::from_empty(),
         service_name: "service_name".to_owned(),
         service_method: "service_method".to_owned(),
         service_payload: "service_payload".to_owned(),
-        extra: None,
+        extra,
         events: Rc::new(RefCell::new(vec![])),
     };
 
@@ -258,6 +316,17 @@ impl Storage for MockStorage {
         unimplemented!()
     }
 
+    async fn commit_block_bundle(
+        &self,
+        _: Context,
+        _: Block,
+        _: Proof,
+        _: Vec<SignedTransaction>,
+        _: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
     async fn get_transaction_by_hash(
         &self,
         _: Context,
@@ -36,6 +36,18 @@ pub struct TransferPayload {
     pub value:    u64,
 }
 
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct ChargeFeePayload {
+    pub fee: u64,
+}
+
+#[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
+pub struct ChargeFeeEvent {
+    pub asset_id: Hash,
+    pub payer:    Address,
+    pub fee:      u64,
+}
+
 #[derive(RlpFixedCodec, Deserialize, Serialize, Clone, Debug)]
 pub struct TransferEvent {
     pub asset_id: Hash,
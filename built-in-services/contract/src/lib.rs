@@ -0,0 +1,239 @@
+pub mod types;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use bytes::Bytes;
+use derive_more::Display;
+
+use protocol::traits::{ServiceResponse, ServiceSDK};
+use protocol::types::{Address, ServiceContext};
+
+use crate::types::CallResponse;
+
+// The parts of the services running user contracts which don't depend on the
+// virtual machine. A contract is stored once by the hash of its code, and
+// deployed as instances, each of which keeps its key/value pairs under its own
+// address.
+//
+// A call returns a code, which is 0 on success. A failed writable call is
+// reverted as a whole through a checkpoint of the executor: its key/value
+// writes, its events and the writes of the services it called through `write`.
+// The host functions charge the bytes they copy, read, write and emit on top
+// of the instructions.
+
+// The cycles of a byte copied between the memory of the contract and the host.
+pub const MEMORY_BYTE_CYCLES: u64 = 1;
+// The cycles of a byte read from or written to the state, or emitted in an
+// event.
+pub const STATE_BYTE_CYCLES: u64 = 100;
+
+pub enum SdkRef<'a, SDK> {
+    Read(&'a SDK),
+    Write(&'a mut SDK),
+}
+
+// Taken before a writable call, see `apply_output`.
+pub struct Checkpoint {
+    events_len:    usize,
+    cycles_refund: u64,
+}
+
+pub fn checkpoint<SDK: ServiceSDK>(sdk: &mut SDK, ctx: &ServiceContext) -> Checkpoint {
+    sdk.checkpoint();
+    Checkpoint {
+        events_len:    ctx.get_events_len(),
+        cycles_refund: ctx.get_cycles_refund(),
+    }
+}
+
+// The result of a contract, its writes are only applied if the code is 0.
+pub struct Output {
+    pub code:   i32,
+    pub output: Vec<u8>,
+    pub writes: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+// The host side of a contract call. The host functions of the machines copy
+// their arguments out of the contract's memory and call it.
+pub struct Host<'a, SDK> {
+    sdk:     SdkRef<'a, SDK>,
+    ctx:     &'a ServiceContext,
+    address: &'a Address,
+    ret:     Vec<u8>,
+    output:  Vec<u8>,
+    writes:  BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl<'a, SDK: ServiceSDK> Host<'a, SDK> {
+    pub fn new(sdk: SdkRef<'a, SDK>, ctx: &'a ServiceContext, address: &'a Address) -> Self {
+        Self {
+            sdk,
+            ctx,
+            address,
+            ret: vec![],
+            output: vec![],
+            writes: BTreeMap::new(),
+        }
+    }
+
+    pub fn ctx(&self) -> &'a ServiceContext {
+        self.ctx
+    }
+
+    pub fn into_output(self, code: i32) -> Output {
+        Output {
+            code,
+            output: self.output,
+            writes: self.writes,
+        }
+    }
+
+    pub fn charge(&self, bytes: usize, byte_cycles: u64) -> Result<(), ContractError> {
+        if !self
+            .ctx
+            .sub_cycles((bytes as u64).saturating_mul(byte_cycles))
+        {
+            return Err(ContractError::OutOfCycles);
+        }
+        Ok(())
+    }
+
+    // The return buffer, which the contract copies out by `ret_copy`.
+    pub fn get_ret(&self) -> &[u8] {
+        &self.ret
+    }
+
+    // Returns the length of the return buffer.
+    pub fn set_ret(&mut self, ret: Vec<u8>) -> usize {
+        self.ret = ret;
+        self.ret.len()
+    }
+
+    pub fn set_output(&mut self, output: Vec<u8>) {
+        self.output = output;
+    }
+
+    // The value is put in the return buffer, its length is returned.
+    pub fn get_value(&mut self, key: Vec<u8>) -> Result<Option<usize>, ContractError> {
+        let value = match self.writes.get(&key) {
+            Some(value) => Some(value.to_owned()),
+            None => {
+                let key = Bytes::from(key.clone());
+                let value: Option<Bytes> = match &self.sdk {
+                    SdkRef::Read(sdk) => sdk.get_account_value(self.address, &key),
+                    SdkRef::Write(sdk) => sdk.get_account_value(self.address, &key),
+                };
+                value.map(|value| value.to_vec())
+            }
+        };
+
+        let value_len = value.as_ref().map(Vec::len).unwrap_or(0);
+        self.charge(key.len() + value_len, STATE_BYTE_CYCLES)?;
+
+        Ok(value.map(|value| self.set_ret(value)))
+    }
+
+    pub fn set_value(&mut self, key: Vec<u8>, value: Vec<u8>) -> Result<(), ContractError> {
+        if let SdkRef::Read(_) = self.sdk {
+            return Err(ContractError::WriteInReadonly);
+        }
+        self.charge(key.len() + value.len(), STATE_BYTE_CYCLES)?;
+        self.writes.insert(key, value);
+        Ok(())
+    }
+
+    // The response or the error message is put in the return buffer, the code
+    // is returned.
+    pub fn call(
+        &mut self,
+        service: &str,
+        method: &str,
+        payload: &str,
+        writable: bool,
+    ) -> Result<u64, ContractError> {
+        let resp: ServiceResponse<String> = match &mut self.sdk {
+            SdkRef::Write(sdk) if writable => sdk.write(self.ctx, None, service, method, payload),
+            SdkRef::Write(sdk) => sdk.read(self.ctx, None, service, method, payload),
+            SdkRef::Read(sdk) if !writable => sdk.read(self.ctx, None, service, method, payload),
+            SdkRef::Read(_) => return Err(ContractError::WriteInReadonly),
+        };
+
+        if resp.is_error() {
+            self.set_ret(resp.error_message.into_bytes());
+        } else {
+            self.set_ret(resp.succeed_data.into_bytes());
+        }
+        Ok(resp.code)
+    }
+
+    pub fn emit_event(&mut self, message: String) -> Result<(), ContractError> {
+        if let SdkRef::Read(_) = self.sdk {
+            return Err(ContractError::WriteInReadonly);
+        }
+        self.charge(message.len(), STATE_BYTE_CYCLES)?;
+        self.ctx.emit_event(message);
+        Ok(())
+    }
+
+    // The hex of the caller's address is put in the return buffer, its length
+    // is returned.
+    pub fn caller(&mut self) -> usize {
+        self.set_ret(self.ctx.get_caller().as_hex().into_bytes())
+    }
+}
+
+// The output of a call which ran to its end with the code 0. Otherwise the
+// response has `failed_code` if the contract returned another code, and
+// `error_code` if it didn't run to its end.
+pub fn check_output<E: fmt::Display>(
+    output: Result<Output, E>,
+    error_code: u64,
+    failed_code: u64,
+) -> Result<Output, ServiceResponse<CallResponse>> {
+    match output {
+        Ok(output) if output.code == 0 => Ok(output),
+        Ok(output) => Err(ServiceResponse::<CallResponse>::from_error(
+            failed_code,
+            String::from_utf8_lossy(&output.output).into_owned(),
+        )),
+        Err(e) => Err(ServiceResponse::<CallResponse>::from_error(
+            error_code,
+            e.to_string(),
+        )),
+    }
+}
+
+// Apply the writes of a succeeded call under the address of the instance, or
+// revert everything the call did since the checkpoint.
+pub fn apply_output<SDK: ServiceSDK>(
+    sdk: &mut SDK,
+    ctx: &ServiceContext,
+    address: &Address,
+    checkpoint: Checkpoint,
+    output: Result<Output, ServiceResponse<CallResponse>>,
+) -> Result<String, ServiceResponse<CallResponse>> {
+    let output = match output {
+        Ok(output) => output,
+        Err(resp) => {
+            sdk.revert_to_checkpoint();
+            ctx.revert_events(checkpoint.events_len);
+            ctx.revert_cycles_refund(checkpoint.cycles_refund);
+            return Err(resp);
+        }
+    };
+
+    for (key, value) in output.writes.into_iter() {
+        sdk.set_account_value(address, Bytes::from(key), Bytes::from(value));
+    }
+    sdk.release_checkpoint();
+    Ok(String::from_utf8_lossy(&output.output).into_owned())
+}
+
+#[derive(Debug, Display)]
+pub enum ContractError {
+    #[display(fmt = "out of cycles")]
+    OutOfCycles,
+    #[display(fmt = "write in a read-only call")]
+    WriteInReadonly,
+}
@@ -0,0 +1,34 @@
+use protocol::types::{Address, Hash, Hex};
+use serde::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct StoreCodePayload {
+    pub code: Hex,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct StoreCodeResponse {
+    pub code_hash: Hash,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DeployPayload {
+    pub code_hash: Hash,
+    pub args:      String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct DeployResponse {
+    pub address: Address,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct CallPayload {
+    pub address: Address,
+    pub args:    String,
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct CallResponse {
+    pub output: String,
+}
@@ -88,6 +88,7 @@ fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
         caller,
         height: 1,
         timestamp: 0,
+        random_seed: Hash::from_empty(),
         service_name: "service_name".to_owned(),
         service_method: "service_method".to_owned(),
         service_payload: "service_payload".to_owned(),
@@ -122,6 +123,17 @@ impl Storage for MockStorage {
         unimplemented!()
     }
 
+    async fn commit_block_bundle(
+        &self,
+        _: Context,
+        _: Block,
+        _: Proof,
+        _: Vec<SignedTransaction>,
+        _: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
     async fn get_transaction_by_hash(
         &self,
         _: Context,
@@ -0,0 +1,183 @@
+mod runtime;
+#[cfg(test)]
+mod tests;
+
+pub use contract::types;
+
+use bytes::Bytes;
+
+use binding_macro::{cycles, service};
+use contract::{apply_output, check_output, checkpoint, SdkRef};
+use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
+use protocol::types::{Address, Hash, ServiceContext};
+
+use crate::runtime::Runtime;
+use crate::types::{
+    CallPayload, CallResponse, DeployPayload, DeployResponse, StoreCodePayload, StoreCodeResponse,
+};
+
+// Instance addresses are derived from the code hash and this counter.
+const INSTANCE_COUNT_KEY: &str = "instance_count";
+
+// `RiscvService` runs contracts compiled to RISC-V on CKB-VM, as the contracts
+// of the `contract` crate. The contract calls the syscalls of `runtime`, and
+// is run with the arguments `[entry, args]`, the entry being `init` on deploy,
+// `call` for the writable `call`, and `query` for the read-only `query`. Its
+// exit code is the code of the call.
+pub struct RiscvService<SDK> {
+    sdk:       SDK,
+    codes:     Box<dyn StoreMap<Hash, Bytes>>,
+    instances: Box<dyn StoreMap<Address, Hash>>,
+}
+
+#[service]
+impl<SDK: ServiceSDK> RiscvService<SDK> {
+    pub fn new(mut sdk: SDK) -> Self {
+        let codes: Box<dyn StoreMap<Hash, Bytes>> = sdk.alloc_or_recover_map("codes");
+        let instances: Box<dyn StoreMap<Address, Hash>> = sdk.alloc_or_recover_map("instances");
+
+        Self {
+            sdk,
+            codes,
+            instances,
+        }
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn store_code(
+        &mut self,
+        ctx: ServiceContext,
+        payload: StoreCodePayload,
+    ) -> ServiceResponse<StoreCodeResponse> {
+        let code = match hex::decode(payload.code.as_string_trim0x()) {
+            Ok(code) => code,
+            Err(_) => {
+                return ServiceResponse::<StoreCodeResponse>::from_error(
+                    130,
+                    "code not valid".to_owned(),
+                )
+            }
+        };
+
+        if let Err(e) = runtime::check_code(&code) {
+            return ServiceResponse::<StoreCodeResponse>::from_error(130, e.to_string());
+        }
+
+        let code_hash = Hash::digest(Bytes::from(code.clone()));
+        if !self.codes.contains(&code_hash) {
+            self.codes.insert(code_hash.clone(), Bytes::from(code));
+        }
+
+        ServiceResponse::<StoreCodeResponse>::from_succeed(StoreCodeResponse { code_hash })
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn deploy(
+        &mut self,
+        ctx: ServiceContext,
+        payload: DeployPayload,
+    ) -> ServiceResponse<DeployResponse> {
+        let code = match self.codes.get(&payload.code_hash) {
+            Some(code) => code,
+            None => {
+                return ServiceResponse::<DeployResponse>::from_error(
+                    131,
+                    "code not existed".to_owned(),
+                )
+            }
+        };
+
+        let count: u64 = self
+            .sdk
+            .get_value(&INSTANCE_COUNT_KEY.to_owned())
+            .unwrap_or(0);
+        let mut seed = payload.code_hash.as_bytes().to_vec();
+        seed.extend_from_slice(&count.to_be_bytes());
+        let address = match Address::from_hash(Hash::digest(Bytes::from(seed))) {
+            Ok(address) => address,
+            Err(e) => return ServiceResponse::<DeployResponse>::from_error(133, e.to_string()),
+        };
+
+        let checkpoint = checkpoint(&mut self.sdk, &ctx);
+        let output = Runtime::new(SdkRef::Write(&mut self.sdk), &ctx, &address).invoke(
+            &code,
+            "init",
+            &payload.args,
+        );
+        if let Err(resp) = apply_output(
+            &mut self.sdk,
+            &ctx,
+            &address,
+            checkpoint,
+            check_output(output, 133, 134),
+        ) {
+            return ServiceResponse::<DeployResponse>::from_error(resp.code, resp.error_message);
+        }
+
+        self.sdk.set_value(INSTANCE_COUNT_KEY.to_owned(), count + 1);
+        self.instances.insert(address.clone(), payload.code_hash);
+
+        ServiceResponse::<DeployResponse>::from_succeed(DeployResponse { address })
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn call(&mut self, ctx: ServiceContext, payload: CallPayload) -> ServiceResponse<CallResponse> {
+        let code = match self.get_instance_code(&payload.address) {
+            Ok(code) => code,
+            Err(resp) => return resp,
+        };
+
+        let checkpoint = checkpoint(&mut self.sdk, &ctx);
+        let output = Runtime::new(SdkRef::Write(&mut self.sdk), &ctx, &payload.address).invoke(
+            &code,
+            "call",
+            &payload.args,
+        );
+
+        match apply_output(
+            &mut self.sdk,
+            &ctx,
+            &payload.address,
+            checkpoint,
+            check_output(output, 133, 134),
+        ) {
+            Ok(output) => ServiceResponse::<CallResponse>::from_succeed(CallResponse { output }),
+            Err(resp) => resp,
+        }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn query(&self, ctx: ServiceContext, payload: CallPayload) -> ServiceResponse<CallResponse> {
+        let code = match self.get_instance_code(&payload.address) {
+            Ok(code) => code,
+            Err(resp) => return resp,
+        };
+
+        let output = Runtime::new(SdkRef::Read(&self.sdk), &ctx, &payload.address).invoke(
+            &code,
+            "query",
+            &payload.args,
+        );
+
+        match check_output(output, 133, 134) {
+            Ok(output) => {
+                let output = String::from_utf8_lossy(&output.output).into_owned();
+                ServiceResponse::<CallResponse>::from_succeed(CallResponse { output })
+            }
+            Err(resp) => resp,
+        }
+    }
+
+    fn get_instance_code(&self, address: &Address) -> Result<Bytes, ServiceResponse<CallResponse>> {
+        self.instances
+            .get(address)
+            .and_then(|code_hash| self.codes.get(&code_hash))
+            .ok_or_else(|| {
+                ServiceResponse::<CallResponse>::from_error(132, "instance not existed".to_owned())
+            })
+    }
+}
@@ -0,0 +1,265 @@
+use ckb_vm::machine::{DefaultCoreMachine, DefaultMachine, DefaultMachineBuilder};
+use ckb_vm::registers::{A0, A1, A2, A3, A4, A5, A7};
+use ckb_vm::{
+    Bytes as VMBytes, Error as VMError, Memory, Register, SparseMemory, SupportMachine, Syscalls,
+    WXorXMemory,
+};
+use derive_more::Display;
+
+use contract::{ContractError, Host, Output, SdkRef, MEMORY_BYTE_CYCLES};
+use protocol::traits::ServiceSDK;
+use protocol::types::{Address, ServiceContext};
+
+// The syscalls of the contracts, the number is in `a7` and the arguments are
+// in `a0` to `a5`, the result is returned in `a0`. Syscalls returning data put
+// it in a return buffer, which is copied out by `RET_COPY`.
+//
+// ret_copy(addr)
+const RET_COPY: u64 = 3000;
+// set_output(addr, len)
+const SET_OUTPUT: u64 = 3001;
+// get_value(key_addr, key_len) -> len, u64::MAX if there is no value
+const GET_VALUE: u64 = 3002;
+// set_value(key_addr, key_len, value_addr, value_len)
+const SET_VALUE: u64 = 3003;
+// read(service_addr, service_len, method_addr, method_len, payload_addr,
+// payload_len) -> code, the response or the error message is returned
+const READ: u64 = 3004;
+// write(service_addr, service_len, method_addr, method_len, payload_addr,
+// payload_len) -> code
+const WRITE: u64 = 3005;
+// emit_event(addr, len)
+const EMIT_EVENT: u64 = 3006;
+// caller() -> len, the hex of the caller's address
+const CALLER: u64 = 3007;
+
+type MachineCore = DefaultCoreMachine<u64, WXorXMemory<u64, SparseMemory<u64>>>;
+
+pub struct Runtime<'a, SDK> {
+    host:    Host<'a, SDK>,
+    // The cycles of the machine which are charged to the context already
+    charged: u64,
+    error:   Option<RiscvError>,
+}
+
+impl<'a, SDK: ServiceSDK> Runtime<'a, SDK> {
+    pub fn new(sdk: SdkRef<'a, SDK>, ctx: &'a ServiceContext, address: &'a Address) -> Self {
+        Self {
+            host:    Host::new(sdk, ctx, address),
+            charged: 0,
+            error:   None,
+        }
+    }
+
+    // Run the contract with the arguments `[entry, args]`, every instruction
+    // costs a cycle of the context. The exit code is the code of the call.
+    pub fn invoke(mut self, code: &[u8], entry: &str, args: &str) -> Result<Output, RiscvError> {
+        let ctx = self.host.ctx();
+        let max_cycles = ctx.get_cycles_limit() - ctx.get_cycles_used();
+        let code = VMBytes::from(code.to_vec());
+        let args = vec![
+            VMBytes::from(entry.as_bytes().to_vec()),
+            VMBytes::from(args.as_bytes().to_vec()),
+        ];
+
+        let result = {
+            let mut machine =
+                new_machine(max_cycles, Box::new(RuntimeSyscalls { runtime: &mut self }));
+            machine
+                .load_program(&code, &args)
+                .map_err(|e| RiscvError::InvalidCode(format!("{:?}", e)))?;
+            let result = machine.run();
+            (result, machine.cycles())
+        };
+
+        let (result, cycles) = result;
+        if !ctx.sub_cycles(cycles - self.charged) {
+            return Err(RiscvError::OutOfCycles);
+        }
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+
+        match result {
+            Ok(code) => Ok(self.host.into_output(i32::from(code))),
+            Err(VMError::InvalidCycles) => {
+                ctx.sub_cycles(std::u64::MAX);
+                Err(RiscvError::OutOfCycles)
+            }
+            Err(e) => Err(RiscvError::Trap(format!("{:?}", e))),
+        }
+    }
+
+    fn call<Mac: SupportMachine>(
+        &mut self,
+        machine: &mut Mac,
+        writable: bool,
+    ) -> Result<(), RiscvError> {
+        let service = self.load_string(machine, A0, A1)?;
+        let method = self.load_string(machine, A2, A3)?;
+        let payload = self.load_string(machine, A4, A5)?;
+
+        let code = self.host.call(&service, &method, &payload, writable)?;
+        machine.set_register(A0, Mac::REG::from_u64(code));
+        Ok(())
+    }
+
+    fn ecall<Mac: SupportMachine>(&mut self, machine: &mut Mac) -> Result<bool, RiscvError> {
+        let number = machine.registers()[A7].to_u64();
+        match number {
+            RET_COPY => {
+                let addr = machine.registers()[A0].to_u64();
+                let ret = self.host.get_ret();
+                self.host.charge(ret.len(), MEMORY_BYTE_CYCLES)?;
+                machine
+                    .memory_mut()
+                    .store_bytes(addr, ret)
+                    .map_err(|e| RiscvError::Memory(format!("{:?}", e)))?;
+            }
+            SET_OUTPUT => {
+                let output = self.load_bytes(machine, A0, A1)?;
+                self.host.set_output(output);
+            }
+            GET_VALUE => {
+                let key = self.load_bytes(machine, A0, A1)?;
+                match self.host.get_value(key)? {
+                    Some(len) => set_ret_len(machine, len),
+                    None => machine.set_register(A0, Mac::REG::from_u64(std::u64::MAX)),
+                }
+            }
+            SET_VALUE => {
+                let key = self.load_bytes(machine, A0, A1)?;
+                let value = self.load_bytes(machine, A2, A3)?;
+                self.host.set_value(key, value)?;
+            }
+            READ => self.call(machine, false)?,
+            WRITE => self.call(machine, true)?,
+            EMIT_EVENT => {
+                let message = self.load_string(machine, A0, A1)?;
+                self.host.emit_event(message)?;
+            }
+            CALLER => {
+                let len = self.host.caller();
+                set_ret_len(machine, len);
+            }
+            _ => return Ok(false),
+        }
+
+        Ok(true)
+    }
+
+    // The bytes are charged before they are copied.
+    fn load_bytes<Mac: SupportMachine>(
+        &self,
+        machine: &mut Mac,
+        addr: usize,
+        len: usize,
+    ) -> Result<Vec<u8>, RiscvError> {
+        let addr = machine.registers()[addr].to_u64();
+        let len = machine.registers()[len].to_u64();
+        self.host.charge(len as usize, MEMORY_BYTE_CYCLES)?;
+
+        let mut bytes = vec![];
+        for i in 0..len {
+            let byte = machine
+                .memory_mut()
+                .load8(&Mac::REG::from_u64(addr.wrapping_add(i)))
+                .map_err(|e| RiscvError::Memory(format!("{:?}", e)))?;
+            bytes.push(byte.to_u8());
+        }
+
+        Ok(bytes)
+    }
+
+    fn load_string<Mac: SupportMachine>(
+        &self,
+        machine: &mut Mac,
+        addr: usize,
+        len: usize,
+    ) -> Result<String, RiscvError> {
+        String::from_utf8(self.load_bytes(machine, addr, len)?).map_err(|_| RiscvError::InvalidUtf8)
+    }
+}
+
+// The syscalls of a runtime, the error of a syscall is kept in the runtime.
+struct RuntimeSyscalls<'r, 'a, SDK> {
+    runtime: &'r mut Runtime<'a, SDK>,
+}
+
+impl<'r, 'a, SDK: ServiceSDK, Mac: SupportMachine> Syscalls<Mac> for RuntimeSyscalls<'r, 'a, SDK> {
+    fn initialize(&mut self, _machine: &mut Mac) -> Result<(), VMError> {
+        Ok(())
+    }
+
+    fn ecall(&mut self, machine: &mut Mac) -> Result<bool, VMError> {
+        let number = machine.registers()[A7].to_u64();
+
+        // Nested calls use the cycles of the context too, the ones used by
+        // the machine so far are charged first.
+        let cycles = machine.cycles();
+        if !self
+            .runtime
+            .host
+            .ctx()
+            .sub_cycles(cycles - self.runtime.charged)
+        {
+            return Err(VMError::InvalidCycles);
+        }
+        self.runtime.charged = cycles;
+
+        self.runtime.ecall(machine).map_err(|e| {
+            self.runtime.error = Some(e);
+            VMError::InvalidEcall(number)
+        })
+    }
+}
+
+fn set_ret_len<Mac: SupportMachine>(machine: &mut Mac, len: usize) {
+    machine.set_register(A0, Mac::REG::from_u64(len as u64));
+}
+
+fn new_machine<'a>(
+    max_cycles: u64,
+    syscalls: Box<dyn Syscalls<MachineCore> + 'a>,
+) -> DefaultMachine<'a, MachineCore> {
+    let core = MachineCore::new_with_max_cycles(max_cycles);
+    DefaultMachineBuilder::new(core)
+        .instruction_cycle_func(Box::new(|_| 1))
+        .syscall(syscalls)
+        .build()
+}
+
+// Check that the code can be loaded as a RISC-V program.
+pub fn check_code(code: &[u8]) -> Result<(), RiscvError> {
+    let mut machine = DefaultMachineBuilder::new(MachineCore::new_with_max_cycles(0)).build();
+    machine
+        .load_program(&VMBytes::from(code.to_vec()), &[])
+        .map(|_| ())
+        .map_err(|e| RiscvError::InvalidCode(format!("{:?}", e)))
+}
+
+#[derive(Debug, Display)]
+pub enum RiscvError {
+    #[display(fmt = "invalid code: {}", _0)]
+    InvalidCode(String),
+    #[display(fmt = "trap: {}", _0)]
+    Trap(String),
+
+    #[display(fmt = "memory access failed: {}", _0)]
+    Memory(String),
+    #[display(fmt = "invalid utf8")]
+    InvalidUtf8,
+    #[display(fmt = "out of cycles")]
+    OutOfCycles,
+    #[display(fmt = "write in a read-only call")]
+    WriteInReadonly,
+}
+
+impl From<ContractError> for RiscvError {
+    fn from(error: ContractError) -> Self {
+        match error {
+            ContractError::OutOfCycles => RiscvError::OutOfCycles,
+            ContractError::WriteInReadonly => RiscvError::WriteInReadonly,
+        }
+    }
+}
@@ -0,0 +1,369 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use cita_trie::MemoryDB;
+
+use async_trait::async_trait;
+use framework::binding::sdk::{DefalutServiceSDK, DefaultChainQuerier};
+use framework::binding::state::{GeneralServiceState, MPTTrie};
+use protocol::traits::{Context, NoopDispatcher, ServiceSDK, Storage};
+use protocol::types::{
+    Address, Block, Hash, Hex, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction,
+};
+use protocol::{types::Bytes, ProtocolResult};
+
+use crate::types::{CallPayload, DeployPayload, StoreCodePayload};
+use crate::RiscvService;
+
+const SP: u32 = 2;
+const T0: u32 = 5;
+const T1: u32 = 6;
+const S0: u32 = 8;
+const A0: u32 = 10;
+const A1: u32 = 11;
+const A2: u32 = 12;
+const A3: u32 = 13;
+const A7: u32 = 17;
+
+#[test]
+fn test_riscv_instance() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller);
+
+    let mut service = new_riscv_service();
+
+    let res = service.store_code(context.clone(), StoreCodePayload {
+        code: Hex::from_string("0x1234".to_owned()).unwrap(),
+    });
+    assert_eq!(res.code, 130);
+
+    let code_hash = store_code(&mut service, &context, store_program(0));
+    let address = service
+        .deploy(context.clone(), DeployPayload {
+            code_hash,
+            args: "".to_owned(),
+        })
+        .succeed_data
+        .address;
+
+    // Every instruction costs a cycle on top of the method's
+    let cycles_used = context.get_cycles_used();
+    let res = service.query(context.clone(), CallPayload {
+        address,
+        args: "".to_owned(),
+    });
+    assert_eq!(res.succeed_data.output, "v");
+    assert!(context.get_cycles_used() - cycles_used > 100_00);
+
+    // The instance isn't deployed if its init fails
+    let code_hash = store_code(&mut service, &context, store_program(1));
+    let res = service.deploy(context.clone(), DeployPayload {
+        code_hash,
+        args: "".to_owned(),
+    });
+    assert_eq!(res.code, 134);
+}
+
+#[test]
+fn test_riscv_cycles() {
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let store_context = mock_context(1024 * 1024 * 1024, caller.clone());
+
+    let mut service = new_riscv_service();
+    // jal zero, 0
+    let code_hash = store_code(&mut service, &store_context, elf(&[0x0000_006f], &[]));
+
+    let context = mock_context(210_00 + 1000, caller.clone());
+    let res = service.deploy(context.clone(), DeployPayload {
+        code_hash,
+        args: "".to_owned(),
+    });
+    assert_eq!(res.code, 133);
+    assert!(context.is_out_of_cycles());
+
+    // The bytes of a syscall are charged before they're copied out of the
+    // memory, set_output(0, 1GiB) runs out of cycles.
+    let mut code = vec![addi(A0, 0, 0)];
+    code.extend(li(A1, 0x4000_0000));
+    code.extend(li(A7, 3001));
+    code.extend(vec![ecall(), addi(A0, 0, 0), addi(A7, 0, 93), ecall()]);
+    let code_hash = store_code(&mut service, &store_context, elf(&code, &[]));
+
+    let context = mock_context(210_00 + 1_000_000, caller);
+    let res = service.deploy(context.clone(), DeployPayload {
+        code_hash,
+        args: "".to_owned(),
+    });
+    assert_eq!(res.code, 133);
+    assert!(context.is_out_of_cycles());
+}
+
+fn store_code<SDK: ServiceSDK>(
+    service: &mut RiscvService<SDK>,
+    context: &ServiceContext,
+    code: Vec<u8>,
+) -> Hash {
+    let res = service.store_code(context.clone(), StoreCodePayload {
+        code: Hex::from_string("0x".to_owned() + &hex::encode(code)).unwrap(),
+    });
+    assert_eq!(res.code, 0);
+    res.succeed_data.code_hash
+}
+
+// `init` and `call` set "k" to "v" and exit with `exit_code`, `query` outputs
+// the value of "k".
+fn store_program(exit_code: i32) -> Vec<u8> {
+    // The data follows the 36 instructions
+    let data = 36;
+    let mut code = vec![
+        ld(T0, SP, 8),
+        lbu(T0, T0, 0),
+        addi(T1, 0, i32::from(b'q')),
+        beq(T0, T1, (16 - 3) * 4),
+        auipc(A0, 0),
+        addi(A0, A0, (data - 4) * 4),
+        addi(A1, 0, 1),
+        auipc(A2, 0),
+        addi(A2, A2, (data - 7) * 4 + 1),
+        addi(A3, 0, 1),
+    ];
+    code.extend(li(A7, 3003));
+    code.extend(vec![
+        ecall(),
+        addi(A0, 0, exit_code),
+        addi(A7, 0, 93),
+        ecall(),
+    ]);
+
+    // query
+    code.extend(vec![
+        auipc(A0, 0),
+        addi(A0, A0, (data - 16) * 4),
+        addi(A1, 0, 1),
+    ]);
+    code.extend(li(A7, 3002));
+    code.extend(vec![
+        ecall(),
+        addi(S0, A0, 0),
+        addi(SP, SP, -16),
+        addi(A0, SP, 0),
+    ]);
+    code.extend(li(A7, 3000));
+    code.extend(vec![ecall(), addi(A0, SP, 0), addi(A1, S0, 0)]);
+    code.extend(li(A7, 3001));
+    code.extend(vec![ecall(), addi(A0, 0, 0), addi(A7, 0, 93), ecall()]);
+    assert_eq!(code.len(), data as usize);
+
+    elf(&code, b"kv")
+}
+
+fn i_type(imm: i32, rs1: u32, funct3: u32, rd: u32, opcode: u32) -> u32 {
+    ((imm as u32 & 0xfff) << 20) | (rs1 << 15) | (funct3 << 12) | (rd << 7) | opcode
+}
+
+fn addi(rd: u32, rs1: u32, imm: i32) -> u32 {
+    i_type(imm, rs1, 0, rd, 0x13)
+}
+
+fn ld(rd: u32, rs1: u32, imm: i32) -> u32 {
+    i_type(imm, rs1, 3, rd, 0x03)
+}
+
+fn lbu(rd: u32, rs1: u32, imm: i32) -> u32 {
+    i_type(imm, rs1, 4, rd, 0x03)
+}
+
+fn auipc(rd: u32, imm: i32) -> u32 {
+    ((imm as u32 & 0xfffff) << 12) | (rd << 7) | 0x17
+}
+
+fn li(rd: u32, imm: i32) -> Vec<u32> {
+    let hi = (imm + 0x800) >> 12;
+    let lo = imm - (hi << 12);
+    vec![
+        ((hi as u32 & 0xfffff) << 12) | (rd << 7) | 0x37,
+        addi(rd, rd, lo),
+    ]
+}
+
+fn beq(rs1: u32, rs2: u32, offset: i32) -> u32 {
+    let imm = offset as u32;
+    (((imm >> 12) & 1) << 31)
+        | (((imm >> 5) & 0x3f) << 25)
+        | (rs2 << 20)
+        | (rs1 << 15)
+        | (((imm >> 1) & 0xf) << 8)
+        | (((imm >> 11) & 1) << 7)
+        | 0x63
+}
+
+fn ecall() -> u32 {
+    0x73
+}
+
+// A RISC-V ELF64 executable of a single segment, the code starts right after
+// the headers.
+fn elf(code: &[u32], data: &[u8]) -> Vec<u8> {
+    let vaddr: u64 = 0x10000;
+    let headers_len: u64 = 64 + 56;
+    let len = headers_len + code.len() as u64 * 4 + data.len() as u64;
+
+    let mut elf = vec![0x7f, b'E', b'L', b'F', 2, 1, 1, 0];
+    elf.extend_from_slice(&[0; 8]);
+    elf.extend_from_slice(&2u16.to_le_bytes()); // executable
+    elf.extend_from_slice(&243u16.to_le_bytes()); // RISC-V
+    elf.extend_from_slice(&1u32.to_le_bytes());
+    elf.extend_from_slice(&(vaddr + headers_len).to_le_bytes()); // entry
+    elf.extend_from_slice(&64u64.to_le_bytes()); // program headers
+    elf.extend_from_slice(&0u64.to_le_bytes()); // section headers
+    elf.extend_from_slice(&0u32.to_le_bytes());
+    elf.extend_from_slice(&64u16.to_le_bytes());
+    elf.extend_from_slice(&56u16.to_le_bytes());
+    elf.extend_from_slice(&1u16.to_le_bytes());
+    elf.extend_from_slice(&64u16.to_le_bytes());
+    elf.extend_from_slice(&0u16.to_le_bytes());
+    elf.extend_from_slice(&0u16.to_le_bytes());
+
+    elf.extend_from_slice(&1u32.to_le_bytes()); // loadable
+    elf.extend_from_slice(&5u32.to_le_bytes()); // readable and executable
+    elf.extend_from_slice(&0u64.to_le_bytes());
+    elf.extend_from_slice(&vaddr.to_le_bytes());
+    elf.extend_from_slice(&vaddr.to_le_bytes());
+    elf.extend_from_slice(&len.to_le_bytes());
+    elf.extend_from_slice(&len.to_le_bytes());
+    elf.extend_from_slice(&0x1000u64.to_le_bytes());
+
+    for instruction in code {
+        elf.extend_from_slice(&instruction.to_le_bytes());
+    }
+    elf.extend_from_slice(data);
+    elf
+}
+
+fn new_riscv_service() -> RiscvService<
+    DefalutServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        NoopDispatcher,
+    >,
+> {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    let sdk = DefalutServiceSDK::new(
+        Rc::new(RefCell::new(state)),
+        Rc::new(chain_db),
+        NoopDispatcher {},
+    );
+
+    RiscvService::new(sdk)
+}
+
+fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    let params = ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit,
+        cycles_price: 1,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller,
+        height: 1,
+        timestamp: 0,
+        random_seed: Hash::from_empty(),
+        service_name: "service_name".to_owned(),
+        service_method: "service_method".to_owned(),
+        service_payload: "service_payload".to_owned(),
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    };
+
+    ServiceContext::new(params)
+}
+
+struct MockStorage;
+
+#[async_trait]
+impl Storage for MockStorage {
+    async fn insert_transactions(
+        &self,
+        _: Context,
+        _: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_block(&self, _: Context, _: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_receipts(&self, _: Context, _: Vec<Receipt>) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn update_latest_proof(&self, _: Context, _: Proof) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn commit_block_bundle(
+        &self,
+        _: Context,
+        _: Block,
+        _: Proof,
+        _: Vec<SignedTransaction>,
+        _: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        _: Context,
+        _: Hash,
+    ) -> ProtocolResult<SignedTransaction> {
+        unimplemented!()
+    }
+
+    async fn get_transactions(
+        &self,
+        _: Context,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block(&self, _: Context) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block_by_height(&self, _: Context, _: u64) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block_by_hash(&self, _: Context, _: Hash) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_receipt(&self, _: Context, _: Hash) -> ProtocolResult<Receipt> {
+        unimplemented!()
+    }
+
+    async fn get_receipts(&self, _: Context, _: Vec<Hash>) -> ProtocolResult<Vec<Receipt>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_proof(&self, _: Context) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn update_overlord_wal(&self, _: Context, _info: Bytes) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn load_overlord_wal(&self, _: Context) -> ProtocolResult<Bytes> {
+        unimplemented!()
+    }
+}
@@ -2,9 +2,9 @@ use bytes::Bytes;
 use hasher::{Hasher, HasherKeccak};
 
 use binding_macro::{cycles, service};
-use common_crypto::{Crypto, Secp256k1};
+use common_crypto::{Crypto, Ed25519, Secp256k1, Sm2};
 use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK};
-use protocol::types::{Hash, ServiceContext};
+use protocol::types::{Hash, ServiceContext, SignatureScheme};
 
 use crate::types::{KeccakPayload, KeccakResponse, SigVerifyPayload, SigVerifyResponse};
 
@@ -66,15 +66,20 @@ impl<SDK: ServiceSDK> UtilService<SDK> {
         };
 
         let data_hash = payload.hash.as_bytes();
+        let (data_sig, data_pk) = (data_sig.unwrap(), data_pk.unwrap());
 
-        let response = SigVerifyResponse {
-            is_ok: Secp256k1::verify_signature(
-                data_hash.as_ref(),
-                data_sig.unwrap().as_slice(),
-                data_pk.unwrap().as_slice(),
-            )
-            .is_ok(),
+        let is_ok = match payload.scheme {
+            SignatureScheme::Secp256k1 => {
+                Secp256k1::verify_signature(data_hash.as_ref(), &data_sig, &data_pk).is_ok()
+            }
+            SignatureScheme::Ed25519 => {
+                Ed25519::verify_signature(data_hash.as_ref(), &data_sig, &data_pk).is_ok()
+            }
+            SignatureScheme::Sm2 => {
+                Sm2::verify_signature(data_hash.as_ref(), &data_sig, &data_pk).is_ok()
+            }
         };
+        let response = SigVerifyResponse { is_ok };
 
         ServiceResponse::<SigVerifyResponse>::from_succeed(response)
     }
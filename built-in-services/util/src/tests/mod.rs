@@ -7,14 +7,14 @@ use rand::rngs::OsRng;
 
 use async_trait::async_trait;
 use common_crypto::{
-    Crypto, PrivateKey, PublicKey, Secp256k1, Secp256k1PrivateKey, Signature, ToPublicKey,
+    Crypto, PrivateKey, PublicKey, Secp256k1, Secp256k1PrivateKey, Signature, Sm2, ToPublicKey,
 };
 use framework::binding::sdk::{DefalutServiceSDK, DefaultChainQuerier};
 use framework::binding::state::{GeneralServiceState, MPTTrie};
 use protocol::traits::{Context, NoopDispatcher, Storage};
 use protocol::types::{
     Address, Block, Hash, Hex, Proof, Receipt, ServiceContext, ServiceContextParams,
-    SignedTransaction,
+    SignatureScheme, SignedTransaction,
 };
 use protocol::{types::Bytes, ProtocolResult};
 
@@ -75,12 +75,47 @@ fn test_verify() {
             hash,
             sig: sig_data,
             pub_key: pub_key_data,
+            scheme: SignatureScheme::Secp256k1,
         })
         .succeed_data;
 
     assert_eq!(res.is_ok, true)
 }
 
+#[test]
+fn test_verify_sm2() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+
+    let service = new_util_service();
+
+    let (priv_key, pub_key) = Sm2::generate_keypair();
+    let hash = Hash::from_hex("0x56570de287d73cd1cb6092bb8fdee6173974955fdef345ae579ee9f475ea7432")
+        .unwrap();
+    let sig = Sm2::sign_message(&hash.as_bytes(), &priv_key).unwrap();
+    let payload = SigVerifyPayload {
+        hash,
+        sig: Hex::from_string("0x".to_owned() + &hex::encode(sig)).unwrap(),
+        pub_key: Hex::from_string("0x".to_owned() + &hex::encode(pub_key)).unwrap(),
+        scheme: SignatureScheme::Sm2,
+    };
+
+    let res = service
+        .verify(mock_context(cycles_limit, caller.clone()), payload.clone())
+        .succeed_data;
+    assert_eq!(res.is_ok, true);
+
+    // It isn't a valid secp256k1 signature
+    let payload = SigVerifyPayload {
+        scheme: SignatureScheme::Secp256k1,
+        ..payload
+    };
+    let res = service
+        .verify(mock_context(cycles_limit, caller), payload)
+        .succeed_data;
+    assert_eq!(res.is_ok, false);
+}
+
 fn new_util_service() -> UtilService<
     DefalutServiceSDK<
         GeneralServiceState<MemoryDB>,
@@ -111,6 +146,7 @@ fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
         caller,
         height: 1,
         timestamp: 0,
+        random_seed: Hash::from_empty(),
         service_name: "service_name".to_owned(),
         service_method: "service_method".to_owned(),
         service_payload: "service_payload".to_owned(),
@@ -145,6 +181,17 @@ impl Storage for MockStorage {
         unimplemented!()
     }
 
+    async fn commit_block_bundle(
+        &self,
+        _: Context,
+        _: Block,
+        _: Proof,
+        _: Vec<SignedTransaction>,
+        _: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
     async fn get_transaction_by_hash(
         &self,
         _: Context,
@@ -1,4 +1,4 @@
-use protocol::types::{Hash, Hex};
+use protocol::types::{Hash, Hex, SignatureScheme};
 use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -16,6 +16,8 @@ pub struct SigVerifyPayload {
     pub hash:    Hash,
     pub sig:     Hex,
     pub pub_key: Hex,
+    #[serde(default)]
+    pub scheme:  SignatureScheme,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug, Default)]
@@ -0,0 +1,204 @@
+mod runtime;
+#[cfg(test)]
+mod tests;
+
+pub use contract::types;
+
+use bytes::Bytes;
+
+use binding_macro::{cycles, service};
+use contract::{apply_output, check_output, checkpoint, SdkRef};
+use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK, StoreMap};
+use protocol::types::{Address, Hash, ServiceContext};
+
+use crate::runtime::Runtime;
+use crate::types::{
+    CallPayload, CallResponse, DeployPayload, DeployResponse, StoreCodePayload, StoreCodeResponse,
+};
+
+// Instance addresses are derived from the code hash and this counter.
+const INSTANCE_COUNT_KEY: &str = "instance_count";
+
+// The cycles of a byte of stored code, which pay for its instrumentation and
+// its storage.
+pub const CODE_BYTE_CYCLES: u64 = 200;
+
+// `WasmService` runs user-supplied Wasm modules as the contracts of the
+// `contract` crate. The module calls the host functions of `runtime` and
+// exports:
+//
+// - `init`, optional, called on deploy
+// - `call`, called by the writable `call`
+// - `query`, called by the read-only `query`
+//
+// They return the `i32` code of the call.
+pub struct WasmService<SDK> {
+    sdk:       SDK,
+    codes:     Box<dyn StoreMap<Hash, Bytes>>,
+    instances: Box<dyn StoreMap<Address, Hash>>,
+}
+
+#[service]
+impl<SDK: ServiceSDK> WasmService<SDK> {
+    pub fn new(mut sdk: SDK) -> Self {
+        let codes: Box<dyn StoreMap<Hash, Bytes>> = sdk.alloc_or_recover_map("codes");
+        let instances: Box<dyn StoreMap<Address, Hash>> = sdk.alloc_or_recover_map("instances");
+
+        Self {
+            sdk,
+            codes,
+            instances,
+        }
+    }
+
+    // The code is kept instrumented with the gas metering, under the hash of
+    // the code as it's stored. It's charged by its size.
+    #[cycles(210_00)]
+    #[write]
+    fn store_code(
+        &mut self,
+        ctx: ServiceContext,
+        payload: StoreCodePayload,
+    ) -> ServiceResponse<StoreCodeResponse> {
+        let code = match hex::decode(payload.code.as_string_trim0x()) {
+            Ok(code) => code,
+            Err(_) => {
+                return ServiceResponse::<StoreCodeResponse>::from_error(
+                    120,
+                    "code not valid".to_owned(),
+                )
+            }
+        };
+
+        if !ctx.sub_cycles((code.len() as u64).saturating_mul(CODE_BYTE_CYCLES)) {
+            return ServiceResponse::<StoreCodeResponse>::from_error(3, "out of cycles".to_owned());
+        }
+
+        let code_hash = Hash::digest(Bytes::from(code.clone()));
+        if !self.codes.contains(&code_hash) {
+            match runtime::prepare_code(&code) {
+                Ok(prepared) => self.codes.insert(code_hash.clone(), Bytes::from(prepared)),
+                Err(e) => {
+                    return ServiceResponse::<StoreCodeResponse>::from_error(120, e.to_string())
+                }
+            }
+        }
+
+        ServiceResponse::<StoreCodeResponse>::from_succeed(StoreCodeResponse { code_hash })
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn deploy(
+        &mut self,
+        ctx: ServiceContext,
+        payload: DeployPayload,
+    ) -> ServiceResponse<DeployResponse> {
+        let code = match self.codes.get(&payload.code_hash) {
+            Some(code) => code,
+            None => {
+                return ServiceResponse::<DeployResponse>::from_error(
+                    121,
+                    "code not existed".to_owned(),
+                )
+            }
+        };
+
+        let count: u64 = self
+            .sdk
+            .get_value(&INSTANCE_COUNT_KEY.to_owned())
+            .unwrap_or(0);
+        let mut seed = payload.code_hash.as_bytes().to_vec();
+        seed.extend_from_slice(&count.to_be_bytes());
+        let address = match Address::from_hash(Hash::digest(Bytes::from(seed))) {
+            Ok(address) => address,
+            Err(e) => return ServiceResponse::<DeployResponse>::from_error(123, e.to_string()),
+        };
+
+        let checkpoint = checkpoint(&mut self.sdk, &ctx);
+        let output = Runtime::new(
+            SdkRef::Write(&mut self.sdk),
+            &ctx,
+            &address,
+            payload.args.as_bytes(),
+        )
+        .invoke(&code, "init", true);
+        if let Err(resp) = apply_output(
+            &mut self.sdk,
+            &ctx,
+            &address,
+            checkpoint,
+            check_output(output, 123, 124),
+        ) {
+            return ServiceResponse::<DeployResponse>::from_error(resp.code, resp.error_message);
+        }
+
+        self.sdk.set_value(INSTANCE_COUNT_KEY.to_owned(), count + 1);
+        self.instances.insert(address.clone(), payload.code_hash);
+
+        ServiceResponse::<DeployResponse>::from_succeed(DeployResponse { address })
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn call(&mut self, ctx: ServiceContext, payload: CallPayload) -> ServiceResponse<CallResponse> {
+        let code = match self.get_instance_code(&payload.address) {
+            Ok(code) => code,
+            Err(resp) => return resp,
+        };
+
+        let checkpoint = checkpoint(&mut self.sdk, &ctx);
+        let output = Runtime::new(
+            SdkRef::Write(&mut self.sdk),
+            &ctx,
+            &payload.address,
+            payload.args.as_bytes(),
+        )
+        .invoke(&code, "call", false);
+
+        match apply_output(
+            &mut self.sdk,
+            &ctx,
+            &payload.address,
+            checkpoint,
+            check_output(output, 123, 124),
+        ) {
+            Ok(output) => ServiceResponse::<CallResponse>::from_succeed(CallResponse { output }),
+            Err(resp) => resp,
+        }
+    }
+
+    #[cycles(100_00)]
+    #[read]
+    fn query(&self, ctx: ServiceContext, payload: CallPayload) -> ServiceResponse<CallResponse> {
+        let code = match self.get_instance_code(&payload.address) {
+            Ok(code) => code,
+            Err(resp) => return resp,
+        };
+
+        let output = Runtime::new(
+            SdkRef::Read(&self.sdk),
+            &ctx,
+            &payload.address,
+            payload.args.as_bytes(),
+        )
+        .invoke(&code, "query", false);
+
+        match check_output(output, 123, 124) {
+            Ok(output) => {
+                let output = String::from_utf8_lossy(&output.output).into_owned();
+                ServiceResponse::<CallResponse>::from_succeed(CallResponse { output })
+            }
+            Err(resp) => resp,
+        }
+    }
+
+    fn get_instance_code(&self, address: &Address) -> Result<Bytes, ServiceResponse<CallResponse>> {
+        self.instances
+            .get(address)
+            .and_then(|code_hash| self.codes.get(&code_hash))
+            .ok_or_else(|| {
+                ServiceResponse::<CallResponse>::from_error(122, "instance not existed".to_owned())
+            })
+    }
+}
@@ -0,0 +1,305 @@
+use std::cmp;
+
+use derive_more::Display;
+use parity_wasm::elements;
+use pwasm_utils::rules;
+use wasmi::{
+    Error as WasmiError, Externals, FuncInstance, FuncRef, HostError, ImportsBuilder, MemoryRef,
+    Module, ModuleImportResolver, ModuleInstance, RuntimeArgs, RuntimeValue, Signature, Trap,
+    ValueType,
+};
+
+use contract::{ContractError, Host, Output, SdkRef, MEMORY_BYTE_CYCLES};
+use protocol::traits::ServiceSDK;
+use protocol::types::{Address, ServiceContext};
+
+// The memory of a module has at most these pages of 64KiB.
+pub const MAX_MEMORY_PAGES: u32 = 16;
+// The stack height limit injected into a module, in values on the stack.
+pub const MAX_STACK_HEIGHT: u32 = 64 * 1024;
+
+// The host functions imported from the module `env`, all the arguments are
+// `i32` pointers or lengths into the exported memory. Functions returning data
+// put it in a return buffer, which is copied out by `ret_copy`.
+const GAS: usize = 0;
+// get_args() -> len
+const GET_ARGS: usize = 1;
+// ret_copy(ptr)
+const RET_COPY: usize = 2;
+// set_output(ptr, len)
+const SET_OUTPUT: usize = 3;
+// get_value(key_ptr, key_len) -> len, -1 if there is no value
+const GET_VALUE: usize = 4;
+// set_value(key_ptr, key_len, value_ptr, value_len)
+const SET_VALUE: usize = 5;
+// read(service_ptr, service_len, method_ptr, method_len, payload_ptr,
+// payload_len) -> code, the response or the error message is returned
+const READ: usize = 6;
+// write(service_ptr, service_len, method_ptr, method_len, payload_ptr,
+// payload_len) -> code
+const WRITE: usize = 7;
+// emit_event(ptr, len)
+const EMIT_EVENT: usize = 8;
+// caller() -> len, the hex of the caller's address
+const CALLER: usize = 9;
+
+pub struct Runtime<'a, SDK> {
+    host:   Host<'a, SDK>,
+    args:   &'a [u8],
+    memory: Option<MemoryRef>,
+}
+
+impl<'a, SDK: ServiceSDK> Runtime<'a, SDK> {
+    pub fn new(
+        sdk: SdkRef<'a, SDK>,
+        ctx: &'a ServiceContext,
+        address: &'a Address,
+        args: &'a [u8],
+    ) -> Self {
+        Self {
+            host: Host::new(sdk, ctx, address),
+            args,
+            memory: None,
+        }
+    }
+
+    // Run the export `fn() -> i32` of the prepared code. A missing export is
+    // a success if it's optional.
+    pub fn invoke(
+        mut self,
+        code: &[u8],
+        export: &str,
+        optional: bool,
+    ) -> Result<Output, WasmError> {
+        let module =
+            Module::from_buffer(code).map_err(|e| WasmError::InvalidCode(e.to_string()))?;
+        let imports = ImportsBuilder::new().with_resolver("env", &HostImports);
+        let not_started = ModuleInstance::new(&module, &imports)
+            .map_err(|e| WasmError::InvalidCode(e.to_string()))?;
+
+        self.memory = not_started
+            .not_started_instance()
+            .export_by_name("memory")
+            .and_then(|e| e.as_memory().cloned());
+        let instance = not_started
+            .run_start(&mut self)
+            .map_err(|e| WasmError::Trap(e.to_string()))?;
+
+        if instance.export_by_name(export).is_none() {
+            if optional {
+                return Ok(self.host.into_output(0));
+            }
+            return Err(WasmError::NotFoundExport(export.to_owned()));
+        }
+
+        match instance.invoke_export(export, &[], &mut self) {
+            Ok(Some(RuntimeValue::I32(code))) => Ok(self.host.into_output(code)),
+            Ok(_) => Err(WasmError::InvalidCode(format!(
+                "{} must return i32",
+                export
+            ))),
+            Err(e) => Err(WasmError::Trap(e.to_string())),
+        }
+    }
+
+    fn get_memory(&self, ptr: u32, len: u32) -> Result<Vec<u8>, Trap> {
+        self.host
+            .charge(len as usize, MEMORY_BYTE_CYCLES)
+            .map_err(WasmError::from)?;
+        let memory = self.memory.as_ref().ok_or(WasmError::NoMemory)?;
+        memory
+            .get(ptr, len as usize)
+            .map_err(|e| WasmError::Memory(e.to_string()).into())
+    }
+
+    fn get_string(&self, ptr: u32, len: u32) -> Result<String, Trap> {
+        String::from_utf8(self.get_memory(ptr, len)?).map_err(|_| WasmError::InvalidUtf8.into())
+    }
+
+    fn set_memory(&self, ptr: u32, data: &[u8]) -> Result<(), Trap> {
+        self.host
+            .charge(data.len(), MEMORY_BYTE_CYCLES)
+            .map_err(WasmError::from)?;
+        let memory = self.memory.as_ref().ok_or(WasmError::NoMemory)?;
+        memory
+            .set(ptr, data)
+            .map_err(|e| WasmError::Memory(e.to_string()).into())
+    }
+
+    fn call(&mut self, args: &RuntimeArgs, writable: bool) -> Result<Option<RuntimeValue>, Trap> {
+        let service = self.get_string(args.nth_checked(0)?, args.nth_checked(1)?)?;
+        let method = self.get_string(args.nth_checked(2)?, args.nth_checked(3)?)?;
+        let payload = self.get_string(args.nth_checked(4)?, args.nth_checked(5)?)?;
+
+        let code = self
+            .host
+            .call(&service, &method, &payload, writable)
+            .map_err(WasmError::from)?;
+        Ok(Some(RuntimeValue::I32(code as i32)))
+    }
+}
+
+impl<'a, SDK: ServiceSDK> Externals for Runtime<'a, SDK> {
+    fn invoke_index(
+        &mut self,
+        index: usize,
+        args: RuntimeArgs,
+    ) -> Result<Option<RuntimeValue>, Trap> {
+        match index {
+            GAS => {
+                let gas: u32 = args.nth_checked(0)?;
+                if !self.host.ctx().sub_cycles(u64::from(gas)) {
+                    return Err(WasmError::OutOfCycles.into());
+                }
+                Ok(None)
+            }
+            GET_ARGS => Ok(ret_len(self.host.set_ret(self.args.to_vec()))),
+            RET_COPY => {
+                self.set_memory(args.nth_checked(0)?, self.host.get_ret())?;
+                Ok(None)
+            }
+            SET_OUTPUT => {
+                let output = self.get_memory(args.nth_checked(0)?, args.nth_checked(1)?)?;
+                self.host.set_output(output);
+                Ok(None)
+            }
+            GET_VALUE => {
+                let key = self.get_memory(args.nth_checked(0)?, args.nth_checked(1)?)?;
+                match self.host.get_value(key).map_err(WasmError::from)? {
+                    Some(len) => Ok(ret_len(len)),
+                    None => Ok(Some(RuntimeValue::I32(-1))),
+                }
+            }
+            SET_VALUE => {
+                let key = self.get_memory(args.nth_checked(0)?, args.nth_checked(1)?)?;
+                let value = self.get_memory(args.nth_checked(2)?, args.nth_checked(3)?)?;
+                self.host.set_value(key, value).map_err(WasmError::from)?;
+                Ok(None)
+            }
+            READ => self.call(&args, false),
+            WRITE => self.call(&args, true),
+            EMIT_EVENT => {
+                let message = self.get_string(args.nth_checked(0)?, args.nth_checked(1)?)?;
+                self.host.emit_event(message).map_err(WasmError::from)?;
+                Ok(None)
+            }
+            CALLER => Ok(ret_len(self.host.caller())),
+            _ => Err(WasmError::NotFoundHostFunction(index.to_string()).into()),
+        }
+    }
+}
+
+fn ret_len(len: usize) -> Option<RuntimeValue> {
+    Some(RuntimeValue::I32(len as i32))
+}
+
+struct HostImports;
+
+impl ModuleImportResolver for HostImports {
+    fn resolve_func(&self, field_name: &str, signature: &Signature) -> Result<FuncRef, WasmiError> {
+        let (index, params, ret) = match field_name {
+            "gas" => (GAS, 1, false),
+            "get_args" => (GET_ARGS, 0, true),
+            "ret_copy" => (RET_COPY, 1, false),
+            "set_output" => (SET_OUTPUT, 2, false),
+            "get_value" => (GET_VALUE, 2, true),
+            "set_value" => (SET_VALUE, 4, false),
+            "read" => (READ, 6, true),
+            "write" => (WRITE, 6, true),
+            "emit_event" => (EMIT_EVENT, 2, false),
+            "caller" => (CALLER, 0, true),
+            _ => {
+                return Err(WasmiError::Instantiation(
+                    WasmError::NotFoundHostFunction(field_name.to_owned()).to_string(),
+                ))
+            }
+        };
+
+        let expect = Signature::new(
+            vec![ValueType::I32; params],
+            if ret { Some(ValueType::I32) } else { None },
+        );
+        if signature != &expect {
+            return Err(WasmiError::Instantiation(format!(
+                "host function {} has a wrong signature",
+                field_name
+            )));
+        }
+
+        Ok(FuncInstance::alloc_host(expect, index))
+    }
+}
+
+// Check the code and instrument it with the gas metering and the stack height
+// limit, the floating point instructions are denied since their results may
+// differ among nodes. The memory is capped at `MAX_MEMORY_PAGES`.
+pub fn prepare_code(code: &[u8]) -> Result<Vec<u8>, WasmError> {
+    let mut module: elements::Module =
+        parity_wasm::deserialize_buffer(code).map_err(|e| WasmError::InvalidCode(e.to_string()))?;
+
+    if let Some(section) = module.memory_section_mut() {
+        for memory in section.entries_mut().iter_mut() {
+            let initial = memory.limits().initial();
+            if initial > MAX_MEMORY_PAGES {
+                return Err(WasmError::InvalidCode(format!(
+                    "memory exceeds {} pages",
+                    MAX_MEMORY_PAGES
+                )));
+            }
+            let maximum = memory
+                .limits()
+                .maximum()
+                .map_or(MAX_MEMORY_PAGES, |maximum| {
+                    cmp::min(maximum, MAX_MEMORY_PAGES)
+                });
+            *memory = elements::MemoryType::new(initial, Some(maximum));
+        }
+    }
+
+    let module = pwasm_utils::inject_gas_counter(module, &rules::Set::default())
+        .map_err(|_| WasmError::InvalidCode("inject gas counter failed".to_owned()))?;
+    let module = pwasm_utils::stack_height::inject_limiter(module, MAX_STACK_HEIGHT)
+        .map_err(|_| WasmError::InvalidCode("inject stack height limit failed".to_owned()))?;
+
+    let wasmi_module = Module::from_parity_wasm_module(module.clone())
+        .map_err(|e| WasmError::InvalidCode(e.to_string()))?;
+    wasmi_module
+        .deny_floating_point()
+        .map_err(|e| WasmError::InvalidCode(e.to_string()))?;
+
+    parity_wasm::serialize(module).map_err(|e| WasmError::InvalidCode(e.to_string()))
+}
+
+#[derive(Debug, Display)]
+pub enum WasmError {
+    #[display(fmt = "invalid code: {}", _0)]
+    InvalidCode(String),
+    #[display(fmt = "export {} was not found", _0)]
+    NotFoundExport(String),
+    #[display(fmt = "host function {} was not found", _0)]
+    NotFoundHostFunction(String),
+    #[display(fmt = "trap: {}", _0)]
+    Trap(String),
+
+    #[display(fmt = "memory is not exported")]
+    NoMemory,
+    #[display(fmt = "memory access failed: {}", _0)]
+    Memory(String),
+    #[display(fmt = "invalid utf8")]
+    InvalidUtf8,
+    #[display(fmt = "out of cycles")]
+    OutOfCycles,
+    #[display(fmt = "write in a read-only call")]
+    WriteInReadonly,
+}
+
+impl HostError for WasmError {}
+
+impl From<ContractError> for WasmError {
+    fn from(error: ContractError) -> Self {
+        match error {
+            ContractError::OutOfCycles => WasmError::OutOfCycles,
+            ContractError::WriteInReadonly => WasmError::WriteInReadonly,
+        }
+    }
+}
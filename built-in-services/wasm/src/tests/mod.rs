@@ -0,0 +1,328 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use cita_trie::MemoryDB;
+
+use async_trait::async_trait;
+use framework::binding::sdk::{DefalutServiceSDK, DefaultChainQuerier};
+use framework::binding::state::{GeneralServiceState, MPTTrie};
+use protocol::traits::{Context, NoopDispatcher, Storage};
+use protocol::types::{
+    Address, Block, Hash, Hex, Proof, Receipt, ServiceContext, ServiceContextParams,
+    SignedTransaction,
+};
+use protocol::{types::Bytes, ProtocolResult};
+
+use crate::types::{CallPayload, DeployPayload, StoreCodePayload};
+use crate::{WasmService, CODE_BYTE_CYCLES};
+
+// `init` and `call` store the args under "key", `call` fails if the args are 4
+// bytes long. `query` returns the stored value.
+const TEST_WAT: &str = r#"
+(module
+  (import "env" "get_args" (func $get_args (result i32)))
+  (import "env" "ret_copy" (func $ret_copy (param i32)))
+  (import "env" "set_output" (func $set_output (param i32 i32)))
+  (import "env" "get_value" (func $get_value (param i32 i32) (result i32)))
+  (import "env" "set_value" (func $set_value (param i32 i32 i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "key")
+
+  (func $store (result i32)
+    (local $len i32)
+    (local.set $len (call $get_args))
+    (call $ret_copy (i32.const 16))
+    (call $set_value (i32.const 0) (i32.const 3) (i32.const 16) (local.get $len))
+    (call $set_output (i32.const 16) (local.get $len))
+    (local.get $len))
+
+  (func (export "init") (result i32)
+    (drop (call $store))
+    (i32.const 0))
+
+  (func (export "call") (result i32)
+    (i32.eq (call $store) (i32.const 4)))
+
+  (func (export "query") (result i32)
+    (local $len i32)
+    (local.set $len (call $get_value (i32.const 0) (i32.const 3)))
+    (call $ret_copy (i32.const 16))
+    (call $set_output (i32.const 16) (local.get $len))
+    (i32.const 0)))
+"#;
+
+#[test]
+fn test_wasm_instance() {
+    let cycles_limit = 1024 * 1024 * 1024; // 1073741824
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(cycles_limit, caller);
+
+    let mut service = new_wasm_service();
+
+    let code = wat::parse_str(TEST_WAT).unwrap();
+    let code_hash = service
+        .store_code(context.clone(), StoreCodePayload {
+            code: Hex::from_string("0x".to_owned() + &hex::encode(code)).unwrap(),
+        })
+        .succeed_data
+        .code_hash;
+
+    let address = service
+        .deploy(context.clone(), DeployPayload {
+            code_hash: code_hash.clone(),
+            args:      "foo".to_owned(),
+        })
+        .succeed_data
+        .address;
+    let query = |service: &WasmService<_>, address: &Address| {
+        service
+            .query(context.clone(), CallPayload {
+                address: address.clone(),
+                args:    "".to_owned(),
+            })
+            .succeed_data
+            .output
+    };
+    assert_eq!(query(&service, &address), "foo");
+
+    // Instances of the same code keep their own values
+    let other = service
+        .deploy(context.clone(), DeployPayload {
+            code_hash,
+            args: "bar".to_owned(),
+        })
+        .succeed_data
+        .address;
+    assert_ne!(other, address);
+    assert_eq!(query(&service, &other), "bar");
+
+    let res = service.call(context.clone(), CallPayload {
+        address: address.clone(),
+        args:    "baz".to_owned(),
+    });
+    assert_eq!(res.succeed_data.output, "baz");
+    assert_eq!(query(&service, &address), "baz");
+
+    // The writes of a failed call are dropped
+    let res = service.call(context.clone(), CallPayload {
+        address: address.clone(),
+        args:    "fail".to_owned(),
+    });
+    assert_eq!(res.code, 124);
+    assert_eq!(res.error_message, "fail");
+    assert_eq!(query(&service, &address), "baz");
+}
+
+#[test]
+fn test_wasm_cycles() {
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let context = mock_context(1024 * 1024 * 1024, caller.clone());
+
+    let mut service = new_wasm_service();
+
+    let res = service.store_code(context.clone(), StoreCodePayload {
+        code: Hex::from_string("0x1234".to_owned()).unwrap(),
+    });
+    assert_eq!(res.code, 120);
+
+    let code = wat::parse_str(TEST_WAT).unwrap();
+    let code_hash = service
+        .store_code(context.clone(), StoreCodePayload {
+            code: Hex::from_string("0x".to_owned() + &hex::encode(code)).unwrap(),
+        })
+        .succeed_data
+        .code_hash;
+    let address = service
+        .deploy(context, DeployPayload {
+            code_hash,
+            args: "foo".to_owned(),
+        })
+        .succeed_data
+        .address;
+
+    // The instructions are charged on top of the cycles of the method
+    let context = mock_context(210_00 + 1, caller);
+    let res = service.call(context.clone(), CallPayload {
+        address,
+        args: "bar".to_owned(),
+    });
+    assert_eq!(res.code, 123);
+    assert!(context.is_out_of_cycles());
+}
+
+#[test]
+fn test_wasm_limits() {
+    let caller = Address::from_hex("0x755cdba6ae4f479f7164792b318b2a06c759833b").unwrap();
+    let mut service = new_wasm_service();
+    let store_code = |service: &mut WasmService<_>, context: &ServiceContext, wat: &str| {
+        let code = wat::parse_str(wat).unwrap();
+        service.store_code(context.clone(), StoreCodePayload {
+            code: Hex::from_string("0x".to_owned() + &hex::encode(code)).unwrap(),
+        })
+    };
+
+    // The code is charged by its size
+    let context = mock_context(1024 * 1024 * 1024, caller.clone());
+    let res = store_code(&mut service, &context, TEST_WAT);
+    assert_eq!(res.code, 0);
+    let code_len = wat::parse_str(TEST_WAT).unwrap().len() as u64;
+    assert_eq!(
+        context.get_cycles_used(),
+        210_00 + code_len * CODE_BYTE_CYCLES
+    );
+
+    let context = mock_context(1024 * 1024 * 1024, caller);
+    let res = store_code(
+        &mut service,
+        &context,
+        r#"(module (memory (export "memory") 17))"#,
+    );
+    assert_eq!(res.code, 120);
+
+    // A recursion is trapped by the stack height limit
+    let code_hash = store_code(
+        &mut service,
+        &context,
+        r#"(module (func $call (export "call") (result i32) (call $call)))"#,
+    )
+    .succeed_data
+    .code_hash;
+    let address = service
+        .deploy(context.clone(), DeployPayload {
+            code_hash,
+            args: "".to_owned(),
+        })
+        .succeed_data
+        .address;
+    let res = service.call(context, CallPayload {
+        address,
+        args: "".to_owned(),
+    });
+    assert_eq!(res.code, 123);
+}
+
+fn new_wasm_service() -> WasmService<
+    DefalutServiceSDK<
+        GeneralServiceState<MemoryDB>,
+        DefaultChainQuerier<MockStorage>,
+        NoopDispatcher,
+    >,
+> {
+    let chain_db = DefaultChainQuerier::new(Arc::new(MockStorage {}));
+    let trie = MPTTrie::new(Arc::new(MemoryDB::new(false)));
+    let state = GeneralServiceState::new(trie);
+
+    let sdk = DefalutServiceSDK::new(
+        Rc::new(RefCell::new(state)),
+        Rc::new(chain_db),
+        NoopDispatcher {},
+    );
+
+    WasmService::new(sdk)
+}
+
+fn mock_context(cycles_limit: u64, caller: Address) -> ServiceContext {
+    let params = ServiceContextParams {
+        tx_hash: None,
+        nonce: None,
+        cycles_limit,
+        cycles_price: 1,
+        cycles_used: Rc::new(RefCell::new(0)),
+        caller,
+        height: 1,
+        timestamp: 0,
+        random_seed: Hash::from_empty(),
+        service_name: "service_name".to_owned(),
+        service_method: "service_method".to_owned(),
+        service_payload: "service_payload".to_owned(),
+        extra: None,
+        events: Rc::new(RefCell::new(vec![])),
+    };
+
+    ServiceContext::new(params)
+}
+
+struct MockStorage;
+
+#[async_trait]
+impl Storage for MockStorage {
+    async fn insert_transactions(
+        &self,
+        _: Context,
+        _: Vec<SignedTransaction>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_block(&self, _: Context, _: Block) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn insert_receipts(&self, _: Context, _: Vec<Receipt>) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn update_latest_proof(&self, _: Context, _: Proof) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn commit_block_bundle(
+        &self,
+        _: Context,
+        _: Block,
+        _: Proof,
+        _: Vec<SignedTransaction>,
+        _: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn get_transaction_by_hash(
+        &self,
+        _: Context,
+        _: Hash,
+    ) -> ProtocolResult<SignedTransaction> {
+        unimplemented!()
+    }
+
+    async fn get_transactions(
+        &self,
+        _: Context,
+        _: Vec<Hash>,
+    ) -> ProtocolResult<Vec<SignedTransaction>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_block(&self, _: Context) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block_by_height(&self, _: Context, _: u64) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_block_by_hash(&self, _: Context, _: Hash) -> ProtocolResult<Block> {
+        unimplemented!()
+    }
+
+    async fn get_receipt(&self, _: Context, _: Hash) -> ProtocolResult<Receipt> {
+        unimplemented!()
+    }
+
+    async fn get_receipts(&self, _: Context, _: Vec<Hash>) -> ProtocolResult<Vec<Receipt>> {
+        unimplemented!()
+    }
+
+    async fn get_latest_proof(&self, _: Context) -> ProtocolResult<Proof> {
+        unimplemented!()
+    }
+
+    async fn update_overlord_wal(&self, _: Context, _info: Bytes) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
+    async fn load_overlord_wal(&self, _: Context) -> ProtocolResult<Bytes> {
+        unimplemented!()
+    }
+}
@@ -1,9 +1,14 @@
+mod sm2;
+
 pub use ophelia::HashValue;
 pub use ophelia::{
     BlsSignatureVerify, Crypto, Error, PrivateKey, PublicKey, Signature, ToBlsPublicKey,
     ToPublicKey,
 };
 pub use ophelia_bls_amcl::{BlsCommonReference, BlsPrivateKey, BlsPublicKey, BlsSignature};
+pub use ophelia_ed25519::{Ed25519, Ed25519PrivateKey, Ed25519PublicKey, Ed25519Signature};
 pub use ophelia_secp256k1::{
     Secp256k1, Secp256k1PrivateKey, Secp256k1PublicKey, Secp256k1Signature,
 };
+
+pub use sm2::{Sm2, Sm2Error};
@@ -0,0 +1,78 @@
+use std::error::Error;
+use std::fmt;
+
+use libsm::sm2::signature::{SigCtx, Signature as Sm2Signature};
+
+// SM2 signatures over SM3 digests with the default user id. The private key is
+// the 32 bytes scalar, the public key is compressed and the signature is DER
+// encoded.
+pub struct Sm2;
+
+impl Sm2 {
+    // Returns the private key and the public key.
+    pub fn generate_keypair() -> (Vec<u8>, Vec<u8>) {
+        let ctx = SigCtx::new();
+        let (pk, sk) = ctx.new_keypair();
+
+        (ctx.serialize_seckey(&sk), ctx.serialize_pubkey(&pk, true))
+    }
+
+    pub fn pub_key(private_key: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+        let ctx = SigCtx::new();
+        let sk = ctx
+            .load_seckey(private_key)
+            .map_err(|_| Sm2Error::InvalidPrivateKey)?;
+
+        Ok(ctx.serialize_pubkey(&ctx.pk_from_sk(&sk), true))
+    }
+
+    pub fn sign_message(msg: &[u8], private_key: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+        let ctx = SigCtx::new();
+        let sk = ctx
+            .load_seckey(private_key)
+            .map_err(|_| Sm2Error::InvalidPrivateKey)?;
+        let pk = ctx.pk_from_sk(&sk);
+
+        Ok(ctx.sign(msg, &sk, &pk).der_encode())
+    }
+
+    pub fn verify_signature(
+        msg: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<(), Sm2Error> {
+        let ctx = SigCtx::new();
+        let pk = ctx
+            .load_pubkey(public_key)
+            .map_err(|_| Sm2Error::InvalidPublicKey)?;
+        let sig = Sm2Signature::der_decode(signature).map_err(|_| Sm2Error::InvalidSignature)?;
+
+        if ctx.verify(msg, &pk, &sig) {
+            Ok(())
+        } else {
+            Err(Sm2Error::VerifyFailed)
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Sm2Error {
+    InvalidPrivateKey,
+    InvalidPublicKey,
+    InvalidSignature,
+    VerifyFailed,
+}
+
+impl fmt::Display for Sm2Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let msg = match self {
+            Sm2Error::InvalidPrivateKey => "invalid sm2 private key",
+            Sm2Error::InvalidPublicKey => "invalid sm2 public key",
+            Sm2Error::InvalidSignature => "invalid sm2 signature",
+            Sm2Error::VerifyFailed => "sm2 signature verification failed",
+        };
+        write!(f, "{}", msg)
+    }
+}
+
+impl Error for Sm2Error {}
@@ -7,7 +7,8 @@ use derive_more::Display;
 use common_apm::muta_apm;
 use protocol::traits::ExecutorFactory;
 use protocol::traits::{
-    APIAdapter, Context, ExecutorParams, MemPool, ServiceMapping, ServiceResponse, Storage,
+    APIAdapter, Context, ExecutorOptions, ExecutorParams, MemPool, ServiceMapping, ServiceResponse,
+    Storage,
 };
 use protocol::types::{Address, Block, Hash, Receipt, SignedTransaction, TransactionRequest};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
@@ -134,6 +135,7 @@ impl<
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
             Arc::clone(&self.service_mapping),
+            ExecutorOptions::default(),
         )?;
 
         let params = ExecutorParams {
@@ -141,6 +143,7 @@ impl<
             height,
             timestamp: block.header.timestamp,
             cycles_limit,
+            random_seed: block.header.random_seed(),
         };
         executor.read(&params, &caller, cycles_price, &TransactionRequest {
             service_name,
@@ -277,6 +277,8 @@ impl Mutation {
             tx_hash:   tx_hash.clone(),
             signature: signature.to_bytes(),
             pubkey:    pubkey.to_bytes(),
+            scheme:    protocol::types::SignatureScheme::Secp256k1,
+            kind:      protocol::types::PubkeyKind::Single,
         };
         state_ctx
             .adapter
@@ -46,6 +46,11 @@ pub struct BlockHeader {
     pub cycles_used:       Vec<Uint64>,
     #[graphql(description = "The address descirbed who packed the block")]
     pub proposer:          Address,
+    #[graphql(
+        description = "The BLS signature of the proposer on the random seed of the previous block, \
+                       its hash is the random seed of the block"
+    )]
+    pub seed_signature:    Bytes,
     pub proof:             Proof,
     #[graphql(description = "The version of validator is designed for cross chain")]
     pub validator_version: Uint64,
@@ -96,6 +101,7 @@ impl From<protocol::types::BlockHeader> for BlockHeader {
                 .map(Uint64::from)
                 .collect(),
             proposer:          Address::from(block_header.proposer),
+            seed_signature:    Bytes::from(block_header.seed_signature),
             proof:             Proof::from(block_header.proof),
             validator_version: Uint64::from(block_header.validator_version),
             validators:        block_header
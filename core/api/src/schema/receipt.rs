@@ -2,17 +2,29 @@ use crate::schema::{Hash, MerkleRoot, ServiceResponse, Uint64};
 
 #[derive(juniper::GraphQLObject, Clone)]
 pub struct Receipt {
-    pub state_root:  MerkleRoot,
-    pub height:      Uint64,
-    pub tx_hash:     Hash,
-    pub cycles_used: Uint64,
-    pub events:      Vec<Event>,
-    pub response:    ReceiptResponse,
+    pub state_root:       MerkleRoot,
+    pub height:           Uint64,
+    pub tx_hash:          Hash,
+    pub cycles_used:      Uint64,
+    pub cycles_refunded:  Uint64,
+    pub cycles_breakdown: Vec<CyclesRecord>,
+    pub events:           Vec<Event>,
+    pub response:         ReceiptResponse,
+}
+
+#[derive(juniper::GraphQLObject, Clone)]
+pub struct CyclesRecord {
+    pub service:    String,
+    pub method:     String,
+    pub call_depth: Uint64,
+    pub cycles:     Uint64,
 }
 
 #[derive(juniper::GraphQLObject, Clone)]
 pub struct Event {
     pub service: String,
+    pub name:    String,
+    pub topics:  Vec<String>,
     pub data:    String,
 }
 
@@ -26,12 +38,29 @@ pub struct ReceiptResponse {
 impl From<protocol::types::Receipt> for Receipt {
     fn from(receipt: protocol::types::Receipt) -> Self {
         Self {
-            state_root:  MerkleRoot::from(receipt.state_root),
-            height:      Uint64::from(receipt.height),
-            tx_hash:     Hash::from(receipt.tx_hash),
-            cycles_used: Uint64::from(receipt.cycles_used),
-            events:      receipt.events.into_iter().map(Event::from).collect(),
-            response:    ReceiptResponse::from(receipt.response),
+            state_root:       MerkleRoot::from(receipt.state_root),
+            height:           Uint64::from(receipt.height),
+            tx_hash:          Hash::from(receipt.tx_hash),
+            cycles_used:      Uint64::from(receipt.cycles_used),
+            cycles_refunded:  Uint64::from(receipt.cycles_refunded),
+            cycles_breakdown: receipt
+                .cycles_breakdown
+                .into_iter()
+                .map(CyclesRecord::from)
+                .collect(),
+            events:           receipt.events.into_iter().map(Event::from).collect(),
+            response:         ReceiptResponse::from(receipt.response),
+        }
+    }
+}
+
+impl From<protocol::types::CyclesRecord> for CyclesRecord {
+    fn from(record: protocol::types::CyclesRecord) -> Self {
+        Self {
+            service:    record.service,
+            method:     record.method,
+            call_depth: Uint64::from(record.call_depth),
+            cycles:     Uint64::from(record.cycles),
         }
     }
 }
@@ -40,6 +69,8 @@ impl From<protocol::types::Event> for Event {
     fn from(event: protocol::types::Event) -> Self {
         Self {
             service: event.service,
+            name:    event.name,
+            topics:  event.topics,
             data:    event.data,
         }
     }
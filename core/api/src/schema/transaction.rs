@@ -15,6 +15,8 @@ pub struct SignedTransaction {
     pub tx_hash:      Hash,
     pub pubkey:       Bytes,
     pub signature:    Bytes,
+    pub scheme:       String,
+    pub kind:         String,
 }
 
 impl From<protocol::types::SignedTransaction> for SignedTransaction {
@@ -31,6 +33,8 @@ impl From<protocol::types::SignedTransaction> for SignedTransaction {
             tx_hash:      Hash::from(stx.tx_hash),
             pubkey:       Bytes::from(stx.pubkey),
             signature:    Bytes::from(stx.signature),
+            scheme:       stx.scheme.name().to_owned(),
+            kind:         stx.kind.name().to_owned(),
         }
     }
 }
@@ -77,6 +81,16 @@ pub struct InputTransactionEncryption {
     pub pubkey:    Bytes,
     #[graphql(description = "The signature of the transaction")]
     pub signature: Bytes,
+    #[graphql(
+        description = "The signature scheme of the public key, one of secp256k1, ed25519 \
+                       and sm2, secp256k1 if not passed"
+    )]
+    pub scheme:    Option<String>,
+    #[graphql(
+        description = "The kind of the public key, one of single, multisig and verifier, \
+                       single if not passed"
+    )]
+    pub kind:      Option<String>,
 }
 
 pub fn to_signed_transaction(
@@ -86,12 +100,22 @@ pub fn to_signed_transaction(
     let pubkey: &[u8] = &hex::decode(encryption.pubkey.as_hex()?).map_err(SchemaError::from)?;
     let signature: &[u8] =
         &hex::decode(encryption.signature.as_hex()?).map_err(SchemaError::from)?;
+    let scheme = match encryption.scheme {
+        Some(scheme) => protocol::types::SignatureScheme::from_name(&scheme)?,
+        None => protocol::types::SignatureScheme::Secp256k1,
+    };
+    let kind = match encryption.kind {
+        Some(kind) => protocol::types::PubkeyKind::from_name(&kind)?,
+        None => protocol::types::PubkeyKind::Single,
+    };
 
     Ok(protocol::types::SignedTransaction {
-        raw:       to_transaction(raw)?,
-        tx_hash:   protocol::types::Hash::from_hex(&encryption.tx_hash.as_hex())?,
-        pubkey:    bytes::BytesMut::from(pubkey).freeze(),
+        raw: to_transaction(raw)?,
+        tx_hash: protocol::types::Hash::from_hex(&encryption.tx_hash.as_hex())?,
+        pubkey: bytes::BytesMut::from(pubkey).freeze(),
         signature: bytes::BytesMut::from(signature).freeze(),
+        scheme,
+        kind,
     })
 }
 
@@ -12,12 +12,11 @@ use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::mpsc::{channel, Receiver, Sender};
 
 use common_apm::muta_apm;
-use common_merkle::Merkle;
 
 use protocol::traits::{
-    CommonConsensusAdapter, ConsensusAdapter, Context, ExecutorFactory, ExecutorParams,
-    ExecutorResp, Gossip, MemPool, MessageTarget, MixedTxHashes, PeerTrust, Priority, Rpc,
-    ServiceMapping, Storage, SynchronizationAdapter, TrustFeedback,
+    CommonConsensusAdapter, ConsensusAdapter, Context, ExecutorFactory, ExecutorOptions,
+    ExecutorParams, ExecutorResp, Gossip, MemPool, MessageTarget, MixedTxHashes, PeerTrust,
+    Priority, Rpc, ServiceMapping, Storage, SynchronizationAdapter, TrustFeedback,
 };
 use protocol::types::{
     Address, Block, Bytes, Hash, MerkleRoot, Metadata, Proof, Receipt, SignedTransaction,
@@ -33,7 +32,7 @@ use crate::message::{
     BROADCAST_HEIGHT, RPC_SYNC_PULL_BLOCK, RPC_SYNC_PULL_PROOF, RPC_SYNC_PULL_TXS,
 };
 use crate::status::{ExecutedInfo, StatusAgent};
-use crate::util::{ExecuteInfo, OverlordCrypto};
+use crate::util::{verify_seed_signature, ExecuteInfo, OverlordCrypto};
 use crate::BlockHeaderField::{PreviousBlockHash, ProofHash, Proposer};
 use crate::BlockProofField::{BitMap, HashMismatch, HeightMismatch, Signature, WeightNotFound};
 use crate::{BlockHeaderField, BlockProofField, ConsensusError};
@@ -55,9 +54,10 @@ pub struct OverlordConsensusAdapter<
     service_mapping:  Arc<Mapping>,
     overlord_handler: RwLock<Option<OverlordHandler<FixedPill>>>,
 
-    exec_queue:  Sender<ExecuteInfo>,
-    exec_demons: Option<ExecDemons<S, DB, EF, Mapping>>,
-    crypto:      Arc<OverlordCrypto>,
+    exec_queue:   Sender<ExecuteInfo>,
+    exec_demons:  Option<ExecDemons<S, DB, EF, Mapping>>,
+    exec_options: ExecutorOptions,
+    crypto:       Arc<OverlordCrypto>,
 }
 
 #[async_trait]
@@ -132,6 +132,7 @@ where
         signed_txs: Vec<SignedTransaction>,
         cycles_limit: u64,
         timestamp: u64,
+        random_seed: Hash,
     ) -> ProtocolResult<()> {
         let exec_info = ExecuteInfo {
             ctx,
@@ -144,6 +145,7 @@ where
             coinbase,
             cycles_limit,
             timestamp,
+            random_seed,
         };
 
         let mut tx = self.exec_queue.clone();
@@ -280,6 +282,7 @@ where
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
             Arc::clone(&self.service_mapping),
+            self.exec_options,
         )?;
 
         let resp = executor.exec(params, txs)?;
@@ -423,6 +426,25 @@ where
         self.storage.insert_receipts(ctx, receipts).await
     }
 
+    /// Save a block together with its proof, signed transactions and receipts
+    /// in one atomic write.
+    #[muta_apm::derive::tracing_span(
+        kind = "consensus.adapter",
+        logs = "{'txs_len': 'signed_txs.len()', 'receipts_len': 'receipts.len()'}"
+    )]
+    async fn save_block_bundle(
+        &self,
+        ctx: Context,
+        block: Block,
+        proof: Proof,
+        signed_txs: Vec<SignedTransaction>,
+        receipts: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        self.storage
+            .commit_block_bundle(ctx, block, proof, signed_txs, receipts)
+            .await
+    }
+
     /// Flush the given transactions in the mempool.
     #[muta_apm::derive::tracing_span(
         kind = "consensus.adapter",
@@ -472,12 +494,14 @@ where
         state_root: MerkleRoot,
         height: u64,
         timestamp: u64,
+        random_seed: Hash,
     ) -> ProtocolResult<Metadata> {
         let executor = EF::from_root(
             state_root.clone(),
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
             Arc::clone(&self.service_mapping),
+            ExecutorOptions::default(),
         )?;
 
         let caller = Address::from_hex("0x0000000000000000000000000000000000000000")?;
@@ -487,6 +511,7 @@ where
             height,
             timestamp,
             cycles_limit: u64::max_value(),
+            random_seed,
         };
         let exec_resp = executor.read(&params, &caller, 1, &TransactionRequest {
             service_name: "metadata".to_string(),
@@ -552,6 +577,7 @@ where
             previous_block.header.state_root.clone(),
             previous_block.header.height,
             previous_block.header.timestamp,
+            previous_block.header.random_seed(),
         )?;
 
         let authority_map = previous_metadata
@@ -580,6 +606,9 @@ where
             return Err(ConsensusError::VerifyBlockHeader(block.header.height, Proposer).into());
         }
 
+        // check the random seed, the proposer signs the seed of the previous block
+        verify_seed_signature(&self.crypto, &block.header, &previous_block.header)?;
+
         // check validators
         for validator in block.header.validators.iter() {
             if !authority_map.contains_key(&validator.address.as_bytes()) {
@@ -664,6 +693,7 @@ where
             previous_block.header.state_root.clone(),
             previous_block.header.height,
             previous_block.header.timestamp,
+            previous_block.header.random_seed(),
         )?;
 
         let mut authority_list = metadata
@@ -800,6 +830,7 @@ where
         service_mapping: Arc<Mapping>,
         status_agent: StatusAgent,
         crypto: Arc<OverlordCrypto>,
+        exec_options: ExecutorOptions,
     ) -> ProtocolResult<Self> {
         let (exec_queue, rx) = channel(OVERLORD_GAP);
         let exec_demons = Some(ExecDemons::new(
@@ -808,6 +839,7 @@ where
             Arc::clone(&service_mapping),
             rx,
             status_agent,
+            exec_options,
         ));
 
         let adapter = OverlordConsensusAdapter {
@@ -819,6 +851,7 @@ where
             overlord_handler: RwLock::new(None),
             exec_queue,
             exec_demons,
+            exec_options,
             crypto,
         };
 
@@ -841,9 +874,10 @@ pub struct ExecDemons<S, DB, EF, Mapping> {
     trie_db:         Arc<DB>,
     service_mapping: Arc<Mapping>,
 
-    pin_ef: PhantomData<EF>,
-    queue:  Receiver<ExecuteInfo>,
-    status: StatusAgent,
+    pin_ef:  PhantomData<EF>,
+    queue:   Receiver<ExecuteInfo>,
+    status:  StatusAgent,
+    options: ExecutorOptions,
 }
 
 impl<S, DB, EF, Mapping> ExecDemons<S, DB, EF, Mapping>
@@ -859,6 +893,7 @@ where
         service_mapping: Arc<Mapping>,
         rx: Receiver<ExecuteInfo>,
         status_agent: StatusAgent,
+        options: ExecutorOptions,
     ) -> Self {
         ExecDemons {
             storage,
@@ -867,6 +902,7 @@ where
             queue: rx,
             pin_ef: PhantomData,
             status: status_agent,
+            options,
         }
     }
 
@@ -906,12 +942,14 @@ where
             Arc::clone(&self.trie_db),
             Arc::clone(&self.storage),
             Arc::clone(&self.service_mapping),
+            self.options,
         )?;
         let exec_params = ExecutorParams {
             state_root: state_root.clone(),
             height,
             timestamp: info.timestamp,
             cycles_limit: info.cycles_limit,
+            random_seed: info.random_seed.clone(),
         };
         let resp = executor.exec(&exec_params, &txs)?;
         log::info!(
@@ -921,18 +959,18 @@ where
         );
 
         let now = Instant::now();
-        self.save_receipts(info.ctx.clone(), resp.receipts.clone())
-            .await?;
+        let receipts = resp.all_receipts().cloned().collect::<Vec<_>>();
+        self.save_receipts(info.ctx.clone(), receipts).await?;
         log::info!(
             "[consensus-adapter]: save receipts cost {:?} receipts len {:?}",
             now.elapsed(),
             resp.receipts.len(),
         );
-        self.status.update_by_executed(gen_executed_info(
+        self.status.update_by_executed(ExecutedInfo::new(
             info.ctx.clone(),
-            resp.clone(),
             height,
             order_root,
+            resp.clone(),
         ));
 
         Ok(())
@@ -946,32 +984,3 @@ where
         self.storage.insert_receipts(ctx, receipts).await
     }
 }
-
-fn gen_executed_info(
-    ctx: Context,
-    exec_resp: ExecutorResp,
-    height: u64,
-    order_root: MerkleRoot,
-) -> ExecutedInfo {
-    let cycles = exec_resp.all_cycles_used;
-
-    let receipt = Merkle::from_hashes(
-        exec_resp
-            .receipts
-            .iter()
-            .map(|r| Hash::digest(r.to_owned().encode_fixed().unwrap()))
-            .collect::<Vec<_>>(),
-    )
-    .get_root_hash()
-    .unwrap_or_else(Hash::from_empty);
-
-    ExecutedInfo {
-        ctx,
-        exec_height: height,
-        cycles_used: cycles,
-        receipt_root: receipt,
-        confirm_root: order_root,
-        state_root: exec_resp.state_root.clone(),
-        logs_bloom: exec_resp.logs_bloom,
-    }
-}
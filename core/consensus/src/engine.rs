@@ -11,7 +11,7 @@ use log::error;
 use moodyblues_sdk::trace;
 use overlord::error::ConsensusError as OverlordError;
 use overlord::types::{Commit, Node, OverlordMsg, Status};
-use overlord::{Consensus as Engine, DurationConfig, Wal};
+use overlord::{Consensus as Engine, Crypto, DurationConfig, Wal};
 use parking_lot::RwLock;
 use rlp::Encodable;
 use serde_json::json;
@@ -36,7 +36,7 @@ use crate::message::{
 use crate::status::StatusAgent;
 use crate::util::{check_list_roots, OverlordCrypto};
 use crate::wal::SignedTxsWAL;
-use crate::ConsensusError;
+use crate::{BlockHeaderField, ConsensusError};
 
 const RETRY_COMMIT_INTERVAL: u64 = 1000;
 
@@ -78,7 +78,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill> for ConsensusEngine<
         let (ordered_tx_hashes, propose_hashes) = self
             .adapter
             .get_txs_from_mempool(
-                ctx,
+                ctx.clone(),
                 next_height,
                 current_consensus_status.cycles_limit,
                 current_consensus_status.tx_num_limit,
@@ -97,6 +97,14 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill> for ConsensusEngine<
 
         let state_root = current_consensus_status.get_latest_state_root();
 
+        let previous_block = self
+            .adapter
+            .get_block_by_height(ctx, next_height - 1)
+            .await?;
+        let seed_signature = self
+            .crypto
+            .sign(previous_block.header.random_seed().as_bytes())?;
+
         let header = BlockHeader {
             chain_id: self.node_info.chain_id.clone(),
             pre_hash: current_consensus_status.current_hash,
@@ -110,6 +118,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill> for ConsensusEngine<
             receipt_root: current_consensus_status.list_receipt_root.clone(),
             cycles_used: current_consensus_status.list_cycles_used,
             proposer: self.node_info.self_address.clone(),
+            seed_signature,
             proof: current_consensus_status.current_proof.clone(),
             validator_version: 0u64,
             validators: current_consensus_status.validators.clone(),
@@ -290,7 +299,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill> for ConsensusEngine<
         let signature = commit.proof.signature.signature.clone();
         let bitmap = commit.proof.signature.address_bitmap.clone();
 
-        // Storage save the latest proof.
+        // The latest proof is saved along with the block.
         let proof = Proof {
             height: commit.proof.height,
             round: commit.proof.round,
@@ -299,8 +308,6 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill> for ConsensusEngine<
             bitmap,
         };
 
-        self.adapter.save_proof(ctx.clone(), proof.clone()).await?;
-
         // Get full transactions from mempool. If is error, try get from wal.
         let ordered_tx_hashes = pill.block.ordered_tx_hashes.clone();
         let signed_txs = match self
@@ -323,6 +330,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill> for ConsensusEngine<
                     pill.block.header.timestamp,
                     Hash::digest(pill.block.encode_fixed()?),
                     signed_txs.clone(),
+                    pill.block.header.random_seed(),
                 )
                 .await
                 .is_ok()
@@ -340,6 +348,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill> for ConsensusEngine<
             pill.block.header.state_root.clone(),
             pill.block.header.height,
             pill.block.header.timestamp,
+            pill.block.header.random_seed(),
         )?;
         log::info!(
             "[consensus]: validator of height {} is {:?}",
@@ -473,6 +482,7 @@ impl<Adapter: ConsensusAdapter + 'static> Engine<FixedPill> for ConsensusEngine<
             old_block.header.state_root.clone(),
             old_block.header.timestamp,
             old_block.header.height,
+            old_block.header.random_seed(),
         )?;
         let mut old_validators = old_metadata
             .verifier_list
@@ -543,6 +553,7 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
         timestamp: u64,
         block_hash: Hash,
         txs: Vec<SignedTransaction>,
+        random_seed: Hash,
     ) -> ProtocolResult<()> {
         let status = self.status_agent.to_inner();
 
@@ -558,6 +569,7 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
                 txs,
                 status.cycles_limit,
                 timestamp,
+                random_seed,
             )
             .await
     }
@@ -648,6 +660,30 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
             return Err(ConsensusError::InvalidStatusVec.into());
         }
 
+        // check cycles limit
+        if block
+            .cycles_used
+            .iter()
+            .any(|cycles| *cycles > status.cycles_limit)
+        {
+            trace::error(
+                "check_block_cycles_limit_exceeded".to_string(),
+                Some(json!({
+                    "block_cycles_used": block.cycles_used,
+                    "cycles_limit": status.cycles_limit,
+                })),
+            );
+            error!(
+                "cycles limit {:?}, block cycles used {:?}",
+                status.cycles_limit, block.cycles_used
+            );
+            return Err(ConsensusError::VerifyBlockHeader(
+                block.height,
+                BlockHeaderField::CyclesUsed,
+            )
+            .into());
+        }
+
         // check logs bloom
         if !check_list_roots(&status.list_logs_bloom, &block.logs_bloom) {
             trace::error(
@@ -678,10 +714,9 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
 
     /// After get the signed transactions:
     /// 1. Execute the signed transactions.
-    /// 2. Save the signed transactions.
-    /// 3. Save the latest proof.
-    /// 4. Save the new block.
-    /// 5. Save the receipt.
+    /// 2. Save the signed transactions, the latest proof and the new block in
+    ///    one atomic write.
+    /// 3. Save the receipt.
     pub async fn update_status(
         &self,
         metadata: Metadata,
@@ -689,12 +724,11 @@ impl<Adapter: ConsensusAdapter + 'static> ConsensusEngine<Adapter> {
         proof: Proof,
         txs: Vec<SignedTransaction>,
     ) -> ProtocolResult<()> {
-        // Save signed transactions
-        self.adapter.save_signed_txs(Context::new(), txs).await?;
-
-        // Save the block.
+        // Save the block bundle. Receipts are saved by the executor once the
+        // block is executed. The storage reports a committed block without
+        // receipts at startup, and the node executes it again.
         self.adapter
-            .save_block(Context::new(), block.clone())
+            .save_block_bundle(Context::new(), block.clone(), proof.clone(), txs, vec![])
             .await?;
 
         // update timeout_gap of mempool
@@ -181,8 +181,8 @@ mod test {
     use rand::random;
 
     use protocol::types::{
-        Address, Block, BlockHeader, Hash, Proof, RawTransaction, SignedTransaction,
-        TransactionRequest,
+        Address, Block, BlockHeader, Hash, Proof, PubkeyKind, RawTransaction, SignatureScheme,
+        SignedTransaction, TransactionRequest,
     };
     use protocol::Bytes;
 
@@ -204,6 +204,7 @@ mod test {
             receipt_root: Vec::new(),
             cycles_used: vec![999_999],
             proposer: Address::from_hex(addr_str).unwrap(),
+            seed_signature: Bytes::new(),
             proof: mock_proof(block_hash),
             validator_version: 1,
             validators: Vec::new(),
@@ -256,6 +257,8 @@ mod test {
             tx_hash,
             pubkey: Bytes::from(gen_random_bytes(32)),
             signature: Bytes::from(gen_random_bytes(64)),
+            scheme: SignatureScheme::Secp256k1,
+            kind: PubkeyKind::Single,
         }
     }
 
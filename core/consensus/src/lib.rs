@@ -139,11 +139,17 @@ pub enum BlockHeaderField {
     #[display(fmt = "The proposer is not in the committee")]
     Proposer,
 
+    #[display(fmt = "The seed signature isn't signed by the proposer on the previous seed")]
+    SeedSignature,
+
     #[display(fmt = "There is at least one validator not in the committee")]
     Validator,
 
     #[display(fmt = "There is at least one validator's weight mismatch")]
     Weight,
+
+    #[display(fmt = "The cycles used exceeds the cycles limit")]
+    CyclesUsed,
 }
 
 #[derive(Debug, Display)]
@@ -231,8 +231,7 @@ impl ExecutedInfo {
         let cycles = resp.all_cycles_used;
 
         let receipt = Merkle::from_hashes(
-            resp.receipts
-                .iter()
+            resp.all_receipts()
                 .map(|r| Hash::digest(r.to_owned().encode_fixed().unwrap()))
                 .collect::<Vec<_>>(),
         )
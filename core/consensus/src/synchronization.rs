@@ -319,6 +319,7 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
             block.header.state_root.clone(),
             block.header.height,
             block.header.timestamp,
+            block.header.random_seed(),
         )?;
 
         self.crypto
@@ -339,10 +340,13 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
 
         status_agent.update_by_committed(metadata, block.clone(), block_hash, proof);
 
+        // The receipts of the scheduled calls are saved along, they have no
+        // transactions.
+        let receipts = executor_resp.all_receipts().cloned().collect();
         self.save_chain_data(
             ctx.clone(),
             rich_block.txs.clone(),
-            executor_resp.receipts.clone(),
+            receipts,
             rich_block.block.clone(),
         )
         .await?;
@@ -397,13 +401,10 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
         receipts: Vec<Receipt>,
         block: Block,
     ) -> ProtocolResult<()> {
-        self.adapter.save_signed_txs(ctx.clone(), txs).await?;
-        self.adapter.save_receipts(ctx.clone(), receipts).await?;
+        let proof = block.header.proof.clone();
         self.adapter
-            .save_proof(ctx.clone(), block.header.proof.clone())
-            .await?;
-        self.adapter.save_block(ctx.clone(), block).await?;
-        Ok(())
+            .save_block_bundle(ctx.clone(), block, proof, txs, receipts)
+            .await
     }
 
     #[muta_apm::derive::tracing_span(kind = "consensus.sync")]
@@ -421,6 +422,7 @@ impl<Adapter: SynchronizationAdapter> OverlordSynchronization<Adapter> {
             height: rich_block.block.header.height,
             timestamp: rich_block.block.header.timestamp,
             cycles_limit,
+            random_seed: rich_block.block.header.random_seed(),
         };
         let resp = self
             .adapter
@@ -18,14 +18,15 @@ use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{CommonConsensusAdapter, Synchronization, SynchronizationAdapter};
 use protocol::traits::{Context, ExecutorParams, ExecutorResp, ServiceResponse, TrustFeedback};
 use protocol::types::{
-    Address, Block, BlockHeader, Bytes, Hash, Hex, MerkleRoot, Metadata, Proof, RawTransaction,
-    Receipt, ReceiptResponse, SignedTransaction, TransactionRequest, Validator, ValidatorExtend,
+    Address, Block, BlockHeader, Bytes, Hash, Hex, MerkleRoot, Metadata, Proof, PubkeyKind,
+    RawTransaction, Receipt, ReceiptResponse, SignatureScheme, SignedTransaction,
+    TransactionRequest, Validator, ValidatorExtend,
 };
 use protocol::ProtocolResult;
 
 use crate::status::{CurrentConsensusStatus, StatusAgent};
 use crate::synchronization::{OverlordSynchronization, RichBlock};
-use crate::util::OverlordCrypto;
+use crate::util::{verify_seed_signature, OverlordCrypto};
 use crate::BlockHeaderField::{PreviousBlockHash, ProofHash, Proposer};
 use crate::BlockProofField::{BitMap, HashMismatch, HeightMismatch, WeightNotFound};
 use crate::{BlockHeaderField, BlockProofField, ConsensusError};
@@ -247,6 +248,20 @@ impl CommonConsensusAdapter for MockCommonConsensusAdapter {
         Ok(())
     }
 
+    async fn save_block_bundle(
+        &self,
+        ctx: Context,
+        block: Block,
+        proof: Proof,
+        signed_txs: Vec<SignedTransaction>,
+        receipts: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        self.save_signed_txs(ctx.clone(), signed_txs).await?;
+        self.save_receipts(ctx.clone(), receipts).await?;
+        self.save_proof(ctx.clone(), proof).await?;
+        self.save_block(ctx, block).await
+    }
+
     /// Flush the given transactions in the mempool.
     async fn flush_mempool(&self, _: Context, _: &[Hash]) -> ProtocolResult<()> {
         Ok(())
@@ -288,6 +303,7 @@ impl CommonConsensusAdapter for MockCommonConsensusAdapter {
         _state_root: MerkleRoot,
         _height: u64,
         _timestamp: u64,
+        _random_seed: Hash,
     ) -> ProtocolResult<Metadata> {
         Ok(Metadata {
             chain_id:        Hash::from_empty(),
@@ -351,6 +367,7 @@ impl CommonConsensusAdapter for MockCommonConsensusAdapter {
             previous_block.header.state_root.clone(),
             previous_block.header.height,
             previous_block.header.timestamp,
+            previous_block.header.random_seed(),
         )?;
 
         let authority_map = previous_metadata
@@ -379,6 +396,9 @@ impl CommonConsensusAdapter for MockCommonConsensusAdapter {
             return Err(ConsensusError::VerifyBlockHeader(block.header.height, Proposer).into());
         }
 
+        // check the random seed, the proposer signs the seed of the previous block
+        verify_seed_signature(&self.crypto, &block.header, &previous_block.header)?;
+
         // check validators
         for validator in block.header.validators.iter() {
             if !authority_map.contains_key(&validator.address.as_bytes()) {
@@ -455,6 +475,7 @@ impl CommonConsensusAdapter for MockCommonConsensusAdapter {
             previous_block.header.state_root.clone(),
             previous_block.header.height,
             previous_block.header.timestamp,
+            previous_block.header.random_seed(),
         )?;
 
         let mut authority_list = metadata
@@ -657,6 +678,10 @@ fn mock_chained_rich_block(len: u64, gap: u64, key_tool: &KeyTool) -> (Vec<RichB
             receipt_root: vec![],
             cycles_used: vec![],
             proposer: Address::from_hex("0x82c67c421d208fb7015d2da79550212a50f2e773").unwrap(),
+            seed_signature: key_tool
+                .overlord_crypto
+                .sign(last_header.random_seed().as_bytes())
+                .unwrap(),
             proof: last_proof,
             validator_version: 0,
             validators: vec![Validator {
@@ -730,6 +755,7 @@ fn mock_genesis_rich_block() -> RichBlock {
         receipt_root:      vec![],
         cycles_used:       vec![],
         proposer:          Address::from_hex("0x82c67c421d208fb7015d2da79550212a50f2e773").unwrap(),
+        seed_signature:    Bytes::new(),
         proof:             Proof {
             height:     0,
             round:      0,
@@ -762,6 +788,8 @@ fn get_receipt(tx: &SignedTransaction, height: u64) -> Receipt {
         height,
         tx_hash: tx.tx_hash.clone(),
         cycles_used: tx.raw.cycles_limit,
+        cycles_refunded: 0,
+        cycles_breakdown: vec![],
         events: vec![],
         response: ReceiptResponse {
             service_name: "sync".to_owned(),
@@ -814,6 +842,8 @@ fn mock_tx_list(num: usize, height: u64) -> Vec<SignedTransaction> {
             tx_hash,
             pubkey: test_pubkey.to_bytes(),
             signature: signature.to_bytes(),
+            scheme: SignatureScheme::Secp256k1,
+            kind: PubkeyKind::Single,
         };
 
         txs.push(signed_tx)
@@ -929,6 +959,7 @@ fn exec_txs(height: u64, txs: &[SignedTransaction]) -> (ExecutorResp, MerkleRoot
     (
         ExecutorResp {
             receipts,
+            scheduled_receipts: vec![],
             all_cycles_used,
             logs_bloom: Default::default(),
             state_root: MerkleRoot::from_empty(),
@@ -5,14 +5,14 @@ use std::error::Error;
 use overlord::Crypto;
 use parking_lot::RwLock;
 
-use crate::ConsensusError;
+use crate::{BlockHeaderField, ConsensusError};
 use common_crypto::{
     BlsCommonReference, BlsPrivateKey, BlsPublicKey, BlsSignature, BlsSignatureVerify, HashValue,
     PrivateKey, Signature,
 };
 use protocol::traits::Context;
-use protocol::types::{Address, Hash, MerkleRoot, SignedTransaction};
-use protocol::{Bytes, ProtocolError};
+use protocol::types::{Address, BlockHeader, Hash, MerkleRoot, SignedTransaction};
+use protocol::{Bytes, ProtocolError, ProtocolResult};
 
 pub struct OverlordCrypto {
     private_key: BlsPrivateKey,
@@ -151,6 +151,7 @@ pub struct ExecuteInfo {
     pub coinbase:     Address,
     pub timestamp:    u64,
     pub cycles_limit: u64,
+    pub random_seed:  Hash,
 }
 
 pub fn check_list_roots<T: Eq>(cache_roots: &[T], block_roots: &[T]) -> bool {
@@ -161,8 +162,38 @@ pub fn check_list_roots<T: Eq>(cache_roots: &[T], block_roots: &[T]) -> bool {
             .all(|(c_root, e_root)| c_root == e_root)
 }
 
+// The proposer signs the random seed of the previous block, see
+// `BlockHeader::random_seed`. A block goes without a seed signature only if the
+// previous one did too, that is the chain hasn't switched to them yet.
+pub fn verify_seed_signature(
+    crypto: &OverlordCrypto,
+    header: &BlockHeader,
+    previous_header: &BlockHeader,
+) -> ProtocolResult<()> {
+    if header.seed_signature.is_empty() && previous_header.seed_signature.is_empty() {
+        return Ok(());
+    }
+
+    crypto
+        .verify_signature(
+            header.seed_signature.clone(),
+            previous_header.random_seed().as_bytes(),
+            header.proposer.as_bytes(),
+        )
+        .map_err(|e| {
+            log::error!(
+                "[consensus] verify_seed_signature, seed_signature: {:?}, error: {}",
+                header.seed_signature,
+                e
+            );
+            ConsensusError::VerifyBlockHeader(header.height, BlockHeaderField::SeedSignature).into()
+        })
+}
+
 #[cfg(test)]
 mod test {
+    use protocol::types::Proof;
+
     use super::*;
 
     #[test]
@@ -201,6 +232,72 @@ mod test {
         assert!(res.is_ok());
     }
 
+    #[test]
+    fn test_verify_seed_signature() {
+        let private_key = BlsPrivateKey::try_from(hex::decode("000000000000000000000000000000001abd6ffdb44427d9e1fcb6f84e7fe7d98f2b5b205b30a94992ec24d94bb0c970").unwrap().as_ref()).unwrap();
+        let public_key = BlsPublicKey::try_from(hex::decode("041054fe9a65be0891094ed37fb3655e3ffb12353bc0a1b4f8673b52ad65d1ca481780cf7e988eb8dcdc05d8352f03605b0d11afb2525b3f1b55ec694509248bcfead39cbb292725d710e2a509c77ed051d1d49e15e429cf6d12b9be7c02179612").unwrap().as_ref()).unwrap();
+        let proposer = Address::from_hex("0x82c67c421d208fb7015d2da79550212a50f2e773").unwrap();
+        let mut addr_pubkey = HashMap::new();
+        addr_pubkey.insert(proposer.as_bytes(), public_key);
+        let crypto = OverlordCrypto::new(private_key, addr_pubkey, "muta".into());
+
+        // The chain hasn't switched to seed signatures yet
+        let previous_header = mock_header(1, proposer.clone());
+        let mut header = mock_header(2, proposer.clone());
+        assert!(verify_seed_signature(&crypto, &header, &previous_header).is_ok());
+
+        header.seed_signature = crypto
+            .sign(previous_header.random_seed().as_bytes())
+            .unwrap();
+        assert!(verify_seed_signature(&crypto, &header, &previous_header).is_ok());
+
+        // Signed on another seed
+        let mut wrong_header = header.clone();
+        wrong_header.seed_signature = crypto
+            .sign(Hash::digest(Bytes::from("wrong seed")).as_bytes())
+            .unwrap();
+        assert!(verify_seed_signature(&crypto, &wrong_header, &previous_header).is_err());
+
+        // Signed by a key which isn't the proposer's one
+        let mut wrong_header = header.clone();
+        wrong_header.proposer =
+            Address::from_hex("0x0000000000000000000000000000000000000000").unwrap();
+        assert!(verify_seed_signature(&crypto, &wrong_header, &previous_header).is_err());
+
+        // The seed signature can't be dropped once the chain has switched
+        let mut next_header = mock_header(3, proposer);
+        assert!(verify_seed_signature(&crypto, &next_header, &header).is_err());
+        next_header.seed_signature = crypto.sign(header.random_seed().as_bytes()).unwrap();
+        assert!(verify_seed_signature(&crypto, &next_header, &header).is_ok());
+    }
+
+    fn mock_header(height: u64, proposer: Address) -> BlockHeader {
+        BlockHeader {
+            chain_id: Hash::from_empty(),
+            height,
+            exec_height: height - 1,
+            pre_hash: Hash::from_empty(),
+            timestamp: 0,
+            logs_bloom: vec![],
+            order_root: Hash::from_empty(),
+            confirm_root: vec![],
+            state_root: Hash::from_empty(),
+            receipt_root: vec![],
+            cycles_used: vec![],
+            proposer,
+            proof: Proof {
+                height:     height - 1,
+                round:      0,
+                block_hash: Hash::from_empty(),
+                signature:  Bytes::new(),
+                bitmap:     Bytes::new(),
+            },
+            validator_version: 0,
+            validators: vec![],
+            seed_signature: Bytes::new(),
+        }
+    }
+
     #[test]
     fn test_zip_roots() {
         let roots_1 = vec![1, 2, 3, 4, 5];
@@ -135,6 +135,8 @@ mod test {
             tx_hash:   mock_hash(),
             pubkey:    Default::default(),
             signature: Default::default(),
+            scheme:    Default::default(),
+            kind:      Default::default(),
         }
     }
 
@@ -23,11 +23,14 @@ use futures::{
 use futures_timer::Delay;
 use log::{debug, error};
 
-use common_crypto::Crypto;
+use common_crypto::{Crypto, Ed25519, Sm2};
 use protocol::{
     fixed_codec::FixedCodec,
-    traits::{Context, Gossip, MemPoolAdapter, PeerTrust, Priority, Rpc, Storage, TrustFeedback},
-    types::{Hash, SignedTransaction},
+    traits::{
+        Context, Gossip, MemPoolAdapter, NonceQuerier, PeerTrust, Priority, Rpc, SignatureVerifier,
+        Storage, TrustFeedback,
+    },
+    types::{Hash, MultiSigWitness, SignatureScheme, SignedTransaction},
     ProtocolError, ProtocolErrorKind, ProtocolResult,
 };
 
@@ -38,6 +41,7 @@ use crate::MemPoolError;
 
 pub const DEFAULT_BROADCAST_TXS_SIZE: usize = 200;
 pub const DEFAULT_BROADCAST_TXS_INTERVAL: u64 = 200; // milliseconds
+pub const ACCOUNT_NONCE_WINDOW: u64 = 64;
 
 struct IntervalTxsBroadcaster;
 
@@ -143,6 +147,10 @@ pub struct DefaultMemPoolAdapter<C, N, S> {
     stx_tx: UnboundedSender<SignedTransaction>,
     err_rx: Mutex<UnboundedReceiver<ProtocolError>>,
 
+    // Only in the account nonce mode
+    nonce_querier:      Option<Arc<dyn NonceQuerier>>,
+    signature_verifier: Option<Arc<dyn SignatureVerifier>>,
+
     pin_c: PhantomData<C>,
 }
 
@@ -186,9 +194,25 @@ where
             stx_tx,
             err_rx: Mutex::new(err_rx),
 
+            nonce_querier: None,
+            signature_verifier: None,
+
             pin_c: PhantomData,
         }
     }
+
+    pub fn with_nonce_querier(mut self, nonce_querier: Arc<dyn NonceQuerier>) -> Self {
+        self.nonce_querier = Some(nonce_querier);
+        self
+    }
+
+    pub fn with_signature_verifier(
+        mut self,
+        signature_verifier: Arc<dyn SignatureVerifier>,
+    ) -> Self {
+        self.signature_verifier = Some(signature_verifier);
+        self
+    }
 }
 
 #[async_trait]
@@ -237,6 +261,15 @@ where
     #[muta_apm::derive::tracing_span(kind = "mempool.adapter")]
     async fn check_signature(&self, ctx: Context, tx: SignedTransaction) -> ProtocolResult<()> {
         let network = self.network.clone();
+        // The signature verified by a service is checked after the hash. It
+        // depends on the state, so it's only checked for new txs, on a best
+        // effort basis: the executor verifies it again against the state the
+        // tx is executed on. The txs of a proposal or a synced block may have
+        // been verified against a state this node hasn't reached yet.
+        let verifier_pubkey = tx.verifier_pubkey().unwrap_or(None);
+        let by_service = verifier_pubkey.is_some();
+        let stx = tx.clone();
+        let verify_ctx = ctx.clone();
 
         let blocking_res = tokio::task::spawn_blocking(move || {
             // Verify transaction hash
@@ -262,11 +295,7 @@ where
                 return Err(wrong_hash.into());
             }
 
-            let hash = tx.tx_hash.as_bytes();
-            let pub_key = tx.pubkey.as_ref();
-            let sig = tx.signature.as_ref();
-
-            C::verify_signature(hash.as_ref(), sig, pub_key).map_err(|_| {
+            if !by_service && !verify_tx_signature::<C>(&tx) {
                 if ctx.is_network_origin_txs() {
                     network.report(
                         ctx,
@@ -277,25 +306,62 @@ where
                     );
                 }
 
-                MemPoolError::CheckSig {
+                return Err(MemPoolError::CheckSig {
                     tx_hash: tx.tx_hash,
                 }
-                .into()
-            })
+                .into());
+            }
+
+            Ok(())
         })
         .await;
 
         match blocking_res {
-            Ok(res) => res,
+            Ok(res) => res?,
             Err(_) => {
                 log::error!("[mempool] check_signature failed");
-                Err(AdapterError::Internal.into())
+                return Err(AdapterError::Internal.into());
             }
         }
+
+        // The witness of an order tx is verified when it's executed, which
+        // takes the block's cycles if it's invalid.
+        if verify_ctx.is_order_txs() {
+            return Ok(());
+        }
+
+        if let Some(pubkey) = verifier_pubkey {
+            let verified = match self.signature_verifier.as_ref() {
+                Some(verifier) => {
+                    verifier
+                        .verify_signature(verify_ctx.clone(), stx.clone(), pubkey)
+                        .await?
+                }
+                None => false,
+            };
+
+            if !verified {
+                if verify_ctx.is_network_origin_txs() {
+                    self.network.report(
+                        verify_ctx,
+                        TrustFeedback::Worse(format!(
+                            "Mempool wrong signature of tx {:?}",
+                            stx.tx_hash
+                        )),
+                    );
+                }
+
+                return Err(MemPoolError::CheckSig {
+                    tx_hash: stx.tx_hash,
+                }
+                .into());
+            }
+        }
+
+        Ok(())
     }
 
     // TODO: Verify Fee?
-    // TODO: Verify Nonce?
     // TODO: Cycle limit?
     #[muta_apm::derive::tracing_span(kind = "mempool.adapter")]
     async fn check_transaction(&self, ctx: Context, stx: SignedTransaction) -> ProtocolResult<()> {
@@ -393,6 +459,40 @@ where
             return Err(timeout.into());
         }
 
+        // Verify account nonce, a window of nonces from the next one of the
+        // sender is admitted, so that a sender can have several txs pending.
+        // They are packaged in the order of their nonces. Like the signature
+        // verified by a service, it's only checked for new txs, the executor
+        // checks it again.
+        if let Some(nonce_querier) = self.nonce_querier.as_ref() {
+            if ctx.is_order_txs() {
+                return Ok(());
+            }
+
+            let nonce = match stx.raw.account_nonce() {
+                Some(nonce) => nonce,
+                None => {
+                    if ctx.is_network_origin_txs() {
+                        self.network.report(
+                            ctx.clone(),
+                            TrustFeedback::Bad(format!(
+                                "Mempool invalid nonce of tx {:?}",
+                                stx.tx_hash
+                            )),
+                        );
+                    }
+                    return Err(MemPoolError::InvalidNonce {
+                        tx_hash: stx.tx_hash,
+                    }
+                    .into());
+                }
+            };
+
+            let sender = stx.sender()?;
+            let expect = nonce_querier.get_account_nonce(ctx, sender).await?;
+            check_account_nonce(&stx.tx_hash, nonce, expect)?;
+        }
+
         Ok(())
     }
 
@@ -434,6 +534,72 @@ where
     }
 }
 
+pub fn check_account_nonce(tx_hash: &Hash, nonce: u64, expect: u64) -> ProtocolResult<()> {
+    if nonce < expect || nonce - expect >= ACCOUNT_NONCE_WINDOW {
+        return Err(MemPoolError::UnexpectedNonce {
+            tx_hash: tx_hash.clone(),
+            nonce,
+            expect,
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+// A multisig tx needs the valid signatures of at least the threshold of its
+// keys, and no invalid one.
+pub fn verify_tx_signature<C: Crypto>(tx: &SignedTransaction) -> bool {
+    let hash = tx.tx_hash.as_bytes();
+
+    let multisig = match tx.multisig_pubkey() {
+        Ok(Some(multisig)) => multisig,
+        Ok(None) => {
+            return verify_scheme_signature::<C>(
+                tx.scheme,
+                hash.as_ref(),
+                tx.signature.as_ref(),
+                tx.pubkey.as_ref(),
+            )
+        }
+        Err(_) => return false,
+    };
+    let witness = match MultiSigWitness::decode_fixed(tx.signature.clone()) {
+        Ok(witness) => witness,
+        Err(_) => return false,
+    };
+    if !multisig.is_valid() || witness.signatures.len() != multisig.pubkeys.len() {
+        return false;
+    }
+
+    let mut signed = 0;
+    for (pubkey, sig) in multisig.pubkeys.iter().zip(witness.signatures.iter()) {
+        if sig.is_empty() {
+            continue;
+        }
+        if !verify_scheme_signature::<C>(tx.scheme, hash.as_ref(), sig.as_ref(), pubkey.as_ref()) {
+            return false;
+        }
+        signed += 1;
+    }
+
+    signed >= usize::from(multisig.threshold)
+}
+
+// The secp256k1 signatures are verified by `C`.
+fn verify_scheme_signature<C: Crypto>(
+    scheme: SignatureScheme,
+    msg: &[u8],
+    signature: &[u8],
+    pubkey: &[u8],
+) -> bool {
+    match scheme {
+        SignatureScheme::Secp256k1 => C::verify_signature(msg, signature, pubkey).is_ok(),
+        SignatureScheme::Ed25519 => Ed25519::verify_signature(msg, signature, pubkey).is_ok(),
+        SignatureScheme::Sm2 => Sm2::verify_signature(msg, signature, pubkey).is_ok(),
+    }
+}
+
 #[derive(Debug, Display)]
 pub enum AdapterError {
     #[display(fmt = "adapter: interval broadcaster drop")]
@@ -2,11 +2,16 @@ use protocol::traits::Context;
 
 const TXS_ORIGINAL_KEY: &str = "txs_original";
 const NETWORK_TXS: usize = 1;
+const ORDER_TXS_KEY: &str = "order_txs";
 
 pub(crate) trait TxContext {
     fn mark_network_origin_new_txs(&self) -> Self;
 
     fn is_network_origin_txs(&self) -> bool;
+
+    fn mark_order_txs(&self) -> Self;
+
+    fn is_order_txs(&self) -> bool;
 }
 
 impl TxContext for Context {
@@ -17,4 +22,12 @@ impl TxContext for Context {
     fn is_network_origin_txs(&self) -> bool {
         self.get::<usize>(TXS_ORIGINAL_KEY) == Some(&NETWORK_TXS)
     }
+
+    fn mark_order_txs(&self) -> Self {
+        self.with_value::<bool>(ORDER_TXS_KEY, true)
+    }
+
+    fn is_order_txs(&self) -> bool {
+        self.get::<bool>(ORDER_TXS_KEY) == Some(&true)
+    }
 }
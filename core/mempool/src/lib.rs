@@ -13,7 +13,10 @@ pub use adapter::message::{
     RPC_RESP_PULL_TXS, RPC_RESP_PULL_TXS_SYNC,
 };
 pub use adapter::DefaultMemPoolAdapter;
-pub use adapter::{DEFAULT_BROADCAST_TXS_INTERVAL, DEFAULT_BROADCAST_TXS_SIZE};
+pub use adapter::{
+    check_account_nonce, verify_tx_signature, ACCOUNT_NONCE_WINDOW, DEFAULT_BROADCAST_TXS_INTERVAL,
+    DEFAULT_BROADCAST_TXS_SIZE,
+};
 
 use std::error::Error;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -298,7 +301,8 @@ where
                 .into());
             }
 
-            self.verify_tx_in_parallel(ctx.clone(), txs.clone()).await?;
+            self.verify_tx_in_parallel(ctx.mark_order_txs(), txs.clone())
+                .await?;
             for signed_tx in txs.into_iter() {
                 self.callback_cache
                     .insert(signed_tx.tx_hash.clone(), signed_tx)
@@ -317,7 +321,8 @@ where
         ctx: Context,
         order_txs: Vec<SignedTransaction>,
     ) -> ProtocolResult<()> {
-        self.verify_tx_in_parallel(ctx, order_txs).await
+        self.verify_tx_in_parallel(ctx.mark_order_txs(), order_txs)
+            .await
     }
 
     #[muta_apm::derive::tracing_span(
@@ -416,6 +421,21 @@ pub enum MemPoolError {
     #[display(fmt = "Tx: {:?} invalid timeout", tx_hash)]
     InvalidTimeout { tx_hash: Hash },
 
+    #[display(fmt = "Tx: {:?} nonce is not an account nonce", tx_hash)]
+    InvalidNonce { tx_hash: Hash },
+
+    #[display(
+        fmt = "Tx: {:?} wrong nonce {}, expect from {}",
+        tx_hash,
+        nonce,
+        expect
+    )]
+    UnexpectedNonce {
+        tx_hash: Hash,
+        nonce:   u64,
+        expect:  u64,
+    },
+
     #[display(fmt = "Batch transaction validation failed")]
     VerifyBatchTransactions,
 }
@@ -2,7 +2,11 @@ use std::sync::Arc;
 
 use test::Bencher;
 
-use protocol::types::Hash;
+use common_crypto::{Ed25519, Ed25519PrivateKey, Sm2};
+use protocol::fixed_codec::FixedCodec;
+use protocol::types::{account_nonce_to_hash, Hash, MultiSigPubkey, MultiSigWitness, PubkeyKind};
+
+use crate::ACCOUNT_NONCE_WINDOW;
 
 use super::*;
 
@@ -114,6 +118,53 @@ async fn test_package_order_consistent_with_insert_order() {
     assert!(check_order_consistant(&mixed_tx_hashes, reserve_txs));
 }
 
+#[tokio::test]
+async fn test_package_account_nonces_in_order() {
+    let mempool = Arc::new(default_mempool());
+    let priv_key = Secp256k1PrivateKey::generate(&mut OsRng);
+    let pub_key = priv_key.pub_key();
+    let nonce_tx = |nonce| {
+        mock_nonce_signed_tx(
+            &priv_key,
+            &pub_key,
+            TIMEOUT,
+            account_nonce_to_hash(nonce),
+            true,
+        )
+    };
+
+    // Several consecutive nonces of one sender, inserted out of order, around
+    // a tx of another sender.
+    let nonce_txs = (0..5).map(nonce_tx).collect::<Vec<_>>();
+    let other_tx = default_mock_txs(1).remove(0);
+    let insert_txs = vec![
+        nonce_txs[3].clone(),
+        nonce_txs[1].clone(),
+        other_tx.clone(),
+        nonce_txs[4].clone(),
+        nonce_txs[0].clone(),
+        nonce_txs[2].clone(),
+    ];
+    for tx in insert_txs.into_iter() {
+        mempool.insert(Context::new(), tx).await.unwrap();
+    }
+
+    // A nonce beyond the window isn't admitted.
+    let far_tx = nonce_tx(ACCOUNT_NONCE_WINDOW);
+    assert!(mempool.insert(Context::new(), far_tx).await.is_err());
+
+    let mixed_tx_hashes = exec_package(Arc::clone(&mempool), CYCLE_LIMIT, TX_NUM_LIMIT).await;
+    let expect = vec![
+        nonce_txs[0].tx_hash.clone(),
+        nonce_txs[1].tx_hash.clone(),
+        other_tx.tx_hash.clone(),
+        nonce_txs[2].tx_hash.clone(),
+        nonce_txs[3].tx_hash.clone(),
+        nonce_txs[4].tx_hash.clone(),
+    ];
+    assert_eq!(mixed_tx_hashes.order_tx_hashes, expect);
+}
+
 #[tokio::test]
 async fn test_flush() {
     let mempool = Arc::new(default_mempool());
@@ -191,6 +242,71 @@ async fn test_sync_propose_txs() {
     assert_eq!(mempool.get_tx_cache().len().await, 50);
 }
 
+#[test]
+fn test_multisig_signature() {
+    let priv_keys = (0..3)
+        .map(|_| Secp256k1PrivateKey::generate(&mut OsRng))
+        .collect::<Vec<_>>();
+    let multisig = MultiSigPubkey {
+        threshold: 2,
+        pubkeys:   priv_keys
+            .iter()
+            .map(|priv_key| priv_key.pub_key().to_bytes())
+            .collect(),
+    };
+
+    let mut tx = mock_signed_tx(&priv_keys[0], &priv_keys[0].pub_key(), TIMEOUT, true);
+    let sign = |priv_key: &Secp256k1PrivateKey| {
+        Secp256k1::sign_message(&tx.tx_hash.as_bytes(), &priv_key.to_bytes())
+            .unwrap()
+            .to_bytes()
+    };
+    let witness = |signatures: Vec<Bytes>| MultiSigWitness { signatures }.encode_fixed().unwrap();
+    let signatures = vec![sign(&priv_keys[0]), Bytes::new(), sign(&priv_keys[2])];
+    let under_threshold = vec![sign(&priv_keys[0]), Bytes::new(), Bytes::new()];
+    let wrong_key = vec![
+        sign(&priv_keys[0]),
+        sign(&priv_keys[0]),
+        sign(&priv_keys[2]),
+    ];
+
+    tx.kind = PubkeyKind::MultiSig;
+    tx.pubkey = multisig.encode_fixed().unwrap();
+    tx.signature = witness(signatures);
+    assert!(verify_tx_signature::<Secp256k1>(&tx));
+
+    tx.signature = witness(under_threshold);
+    assert!(!verify_tx_signature::<Secp256k1>(&tx));
+
+    tx.signature = witness(wrong_key);
+    assert!(!verify_tx_signature::<Secp256k1>(&tx));
+}
+
+#[test]
+fn test_signature_schemes() {
+    let priv_key = Secp256k1PrivateKey::generate(&mut OsRng);
+    let mut tx = mock_signed_tx(&priv_key, &priv_key.pub_key(), TIMEOUT, true);
+    assert!(verify_tx_signature::<Secp256k1>(&tx));
+
+    // The signature is verified in the tagged scheme only
+    tx.scheme = SignatureScheme::Sm2;
+    assert!(!verify_tx_signature::<Secp256k1>(&tx));
+
+    let ed25519_key = Ed25519PrivateKey::generate(&mut OsRng);
+    tx.scheme = SignatureScheme::Ed25519;
+    tx.pubkey = ed25519_key.pub_key().to_bytes();
+    tx.signature = Ed25519::sign_message(&tx.tx_hash.as_bytes(), &ed25519_key.to_bytes())
+        .unwrap()
+        .to_bytes();
+    assert!(verify_tx_signature::<Secp256k1>(&tx));
+
+    let (sm2_priv_key, sm2_pub_key) = Sm2::generate_keypair();
+    tx.scheme = SignatureScheme::Sm2;
+    tx.pubkey = Bytes::from(sm2_pub_key);
+    tx.signature = Bytes::from(Sm2::sign_message(&tx.tx_hash.as_bytes(), &sm2_priv_key).unwrap());
+    assert!(verify_tx_signature::<Secp256k1>(&tx));
+}
+
 #[bench]
 fn bench_insert(b: &mut Bencher) {
     let mut runtime = tokio::runtime::Runtime::new().unwrap();
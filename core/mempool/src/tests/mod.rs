@@ -17,10 +17,12 @@ use common_crypto::{
 };
 use protocol::codec::ProtocolCodec;
 use protocol::traits::{Context, MemPool, MemPoolAdapter, MixedTxHashes};
-use protocol::types::{Hash, RawTransaction, SignedTransaction, TransactionRequest};
+use protocol::types::{
+    Hash, PubkeyKind, RawTransaction, SignatureScheme, SignedTransaction, TransactionRequest,
+};
 use protocol::{Bytes, ProtocolResult};
 
-use crate::{HashMemPool, MemPoolError};
+use crate::{check_account_nonce, verify_tx_signature, HashMemPool, MemPoolError};
 
 const CYCLE_LIMIT: u64 = 1_000_000;
 const TX_NUM_LIMIT: u64 = 10_000;
@@ -69,8 +71,12 @@ impl MemPoolAdapter for HashMemPoolAdapter {
         check_sig(&tx)
     }
 
-    async fn check_transaction(&self, _ctx: Context, _tx: SignedTransaction) -> ProtocolResult<()> {
-        Ok(())
+    // Every sender's next account nonce is 0.
+    async fn check_transaction(&self, _ctx: Context, tx: SignedTransaction) -> ProtocolResult<()> {
+        match tx.raw.account_nonce() {
+            Some(nonce) => check_account_nonce(&tx.tx_hash, nonce, 0),
+            None => Ok(()),
+        }
     }
 
     async fn check_storage_exist(&self, _ctx: Context, _tx_hash: Hash) -> ProtocolResult<()> {
@@ -131,7 +137,7 @@ async fn check_hash(tx: SignedTransaction) -> ProtocolResult<()> {
 }
 
 fn check_sig(tx: &SignedTransaction) -> ProtocolResult<()> {
-    if Secp256k1::verify_signature(&tx.tx_hash.as_bytes(), &tx.signature, &tx.pubkey).is_err() {
+    if !verify_tx_signature::<Secp256k1>(tx) {
         return Err(MemPoolError::CheckSig {
             tx_hash: tx.tx_hash.clone(),
         }
@@ -241,7 +247,16 @@ fn mock_signed_tx(
     valid: bool,
 ) -> SignedTransaction {
     let nonce = Hash::digest(Bytes::from(get_random_bytes(10)));
+    mock_nonce_signed_tx(priv_key, pub_key, timeout, nonce, valid)
+}
 
+fn mock_nonce_signed_tx(
+    priv_key: &Secp256k1PrivateKey,
+    pub_key: &Secp256k1PublicKey,
+    timeout: u64,
+    nonce: Hash,
+    valid: bool,
+) -> SignedTransaction {
     let request = TransactionRequest {
         service_name: "test".to_owned(),
         method:       "test".to_owned(),
@@ -270,6 +285,8 @@ fn mock_signed_tx(
         tx_hash,
         pubkey: pub_key.to_bytes(),
         signature: signature.to_bytes(),
+        scheme: SignatureScheme::Secp256k1,
+        kind: PubkeyKind::Single,
     }
 }
 
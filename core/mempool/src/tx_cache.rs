@@ -1,15 +1,43 @@
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 
 use crossbeam_queue::ArrayQueue;
 
 use protocol::traits::MixedTxHashes;
-use protocol::types::{Hash, SignedTransaction};
+use protocol::types::{Address, Hash, SignedTransaction};
 use protocol::ProtocolResult;
 
 use crate::map::Map;
 use crate::MemPoolError;
 
+/// The txs of a sender with account nonces may arrive in any order, so they
+/// are put back into the slots of that sender, sorted by their nonces. The
+/// other txs keep their slots.
+fn sort_by_account_nonce(txs: &mut [SharedTx]) {
+    let mut sender_slots: HashMap<Address, Vec<usize>> = HashMap::new();
+    for (idx, shared_tx) in txs.iter().enumerate() {
+        if shared_tx.tx.raw.account_nonce().is_none() {
+            continue;
+        }
+        if let Ok(sender) = shared_tx.tx.sender() {
+            sender_slots.entry(sender).or_default().push(idx);
+        }
+    }
+
+    for slots in sender_slots.values().filter(|slots| slots.len() > 1) {
+        let mut sender_txs = slots
+            .iter()
+            .map(|idx| Arc::clone(&txs[*idx]))
+            .collect::<Vec<_>>();
+        sender_txs.sort_by_key(|shared_tx| shared_tx.tx.raw.account_nonce());
+
+        for (idx, shared_tx) in slots.iter().zip(sender_txs.into_iter()) {
+            txs[*idx] = shared_tx;
+        }
+    }
+}
+
 /// Wrap `SignedTransaction` with two marks for mempool management.
 ///
 /// Each new transaction inserting into mempool will set `removed` false,
@@ -191,8 +219,8 @@ impl TxCache {
     ) -> ProtocolResult<MixedTxHashes> {
         let queue_role = self.get_queue_role();
 
-        let mut order_tx_hashes = Vec::new();
-        let mut propose_tx_hashes = Vec::new();
+        let mut order_txs = Vec::new();
+        let mut propose_txs = Vec::new();
         let mut timeout_tx_hashes = Vec::new();
 
         let mut tx_count: u64 = 0;
@@ -234,8 +262,8 @@ impl TxCache {
                 }
 
                 match stage {
-                    Stage::OrderTxs => order_tx_hashes.push(tx_hash.clone()),
-                    Stage::ProposeTxs => propose_tx_hashes.push(tx_hash.clone()),
+                    Stage::OrderTxs => order_txs.push(Arc::clone(&shared_tx)),
+                    Stage::ProposeTxs => propose_txs.push(Arc::clone(&shared_tx)),
                     Stage::Finished => {}
                 }
             } else {
@@ -249,6 +277,17 @@ impl TxCache {
         // Remove timeout tx in map
         self.map.remove_batch(&timeout_tx_hashes).await;
 
+        let order_len = order_txs.len();
+        let mut txs = order_txs;
+        txs.append(&mut propose_txs);
+        sort_by_account_nonce(&mut txs);
+
+        let mut order_tx_hashes = txs
+            .into_iter()
+            .map(|shared_tx| shared_tx.tx.tx_hash.clone())
+            .collect::<Vec<_>>();
+        let propose_tx_hashes = order_tx_hashes.split_off(order_len);
+
         Ok(MixedTxHashes {
             order_tx_hashes,
             propose_tx_hashes,
@@ -410,7 +449,9 @@ mod tests {
     use rand::random;
     use test::Bencher;
 
-    use protocol::types::{Hash, RawTransaction, SignedTransaction, TransactionRequest};
+    use protocol::types::{
+        Hash, PubkeyKind, RawTransaction, SignatureScheme, SignedTransaction, TransactionRequest,
+    };
     use protocol::Bytes;
 
     use crate::map::Map;
@@ -464,6 +505,8 @@ mod tests {
             tx_hash,
             pubkey: bytes.clone(),
             signature: bytes,
+            scheme: SignatureScheme::Secp256k1,
+            kind: PubkeyKind::Single,
         }
     }
 
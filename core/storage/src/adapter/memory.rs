@@ -1,5 +1,6 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::ops::Bound;
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -7,19 +8,22 @@ use derive_more::{Display, From};
 use parking_lot::RwLock;
 
 use protocol::codec::ProtocolCodec;
-use protocol::traits::{StorageAdapter, StorageBatchModify, StorageSchema};
+use protocol::traits::{
+    prefix_successor, StorageAdapter, StorageBatch, StorageBatchModify, StorageCategory,
+    StorageIterDirection, StorageScanMode, StorageSchema,
+};
 use protocol::Bytes;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 #[derive(Debug)]
 pub struct MemoryAdapter {
-    db: Arc<RwLock<HashMap<Vec<u8>, Vec<u8>>>>,
+    db: Arc<RwLock<BTreeMap<Vec<u8>, Vec<u8>>>>,
 }
 
 impl MemoryAdapter {
     pub fn new() -> Self {
         MemoryAdapter {
-            db: Arc::new(RwLock::new(HashMap::new())),
+            db: Arc::new(RwLock::new(BTreeMap::new())),
         }
     }
 }
@@ -27,7 +31,7 @@ impl MemoryAdapter {
 impl Default for MemoryAdapter {
     fn default() -> Self {
         MemoryAdapter {
-            db: Arc::new(RwLock::new(HashMap::new())),
+            db: Arc::new(RwLock::new(BTreeMap::new())),
         }
     }
 }
@@ -39,7 +43,7 @@ impl StorageAdapter for MemoryAdapter {
         mut key: <S as StorageSchema>::Key,
         mut val: <S as StorageSchema>::Value,
     ) -> ProtocolResult<()> {
-        let key = key.encode().await?.to_vec();
+        let key = category_key(S::category(), &key.encode().await?);
         let val = val.encode().await?.to_vec();
 
         self.db.write().insert(key, val);
@@ -51,9 +55,9 @@ impl StorageAdapter for MemoryAdapter {
         &self,
         mut key: <S as StorageSchema>::Key,
     ) -> ProtocolResult<Option<<S as StorageSchema>::Value>> {
-        let key = key.encode().await?;
+        let key = category_key(S::category(), &key.encode().await?);
 
-        let opt_bytes = self.db.read().get(&key.to_vec()).cloned();
+        let opt_bytes = self.db.read().get(&key).cloned();
 
         if let Some(bytes) = opt_bytes {
             let val = <_>::decode(bytes).await?;
@@ -68,7 +72,7 @@ impl StorageAdapter for MemoryAdapter {
         &self,
         mut key: <S as StorageSchema>::Key,
     ) -> ProtocolResult<()> {
-        let key = key.encode().await?.to_vec();
+        let key = category_key(S::category(), &key.encode().await?);
 
         self.db.write().remove(&key);
 
@@ -79,7 +83,7 @@ impl StorageAdapter for MemoryAdapter {
         &self,
         mut key: <S as StorageSchema>::Key,
     ) -> ProtocolResult<bool> {
-        let key = key.encode().await?.to_vec();
+        let key = category_key(S::category(), &key.encode().await?);
 
         Ok(self.db.read().get(&key).is_some())
     }
@@ -93,10 +97,10 @@ impl StorageAdapter for MemoryAdapter {
             return Err(MemoryAdapterError::BatchLengthMismatch.into());
         }
 
-        let mut pairs: Vec<(Bytes, Option<Bytes>)> = Vec::with_capacity(keys.len());
+        let mut pairs: Vec<(Vec<u8>, Option<Bytes>)> = Vec::with_capacity(keys.len());
 
         for (mut key, value) in keys.into_iter().zip(vals.into_iter()) {
-            let key = key.encode().await?;
+            let key = category_key(S::category(), &key.encode().await?);
 
             let value = match value {
                 StorageBatchModify::Insert(mut value) => Some(value.encode().await?),
@@ -108,13 +112,83 @@ impl StorageAdapter for MemoryAdapter {
 
         for (key, value) in pairs.into_iter() {
             match value {
-                Some(value) => self.db.write().insert(key.to_vec(), value.to_vec()),
-                None => self.db.write().remove(&key.to_vec()),
+                Some(value) => self.db.write().insert(key, value.to_vec()),
+                None => self.db.write().remove(&key),
             };
         }
 
         Ok(())
     }
+
+    async fn write_batch(&self, batch: StorageBatch) -> ProtocolResult<()> {
+        let mut db = self.db.write();
+
+        for (category, key, value) in batch.into_ops().into_iter() {
+            let key = category_key(category, &key);
+
+            match value {
+                Some(value) => db.insert(key, value.to_vec()),
+                None => db.remove(&key),
+            };
+        }
+
+        Ok(())
+    }
+
+    async fn scan(
+        &self,
+        category: StorageCategory,
+        mode: StorageScanMode,
+        direction: StorageIterDirection,
+        limit: usize,
+    ) -> ProtocolResult<Vec<(Bytes, Bytes)>> {
+        let category_prefix = category_key(category, &[]);
+        let (start, end) = match (mode, direction) {
+            (StorageScanMode::Prefix(prefix), _) => {
+                let start = category_key(category, &prefix);
+                let end = match prefix_successor(&start) {
+                    Some(successor) => Bound::Excluded(successor),
+                    None => Bound::Unbounded,
+                };
+                (Bound::Included(start), end)
+            }
+            (StorageScanMode::From(from), StorageIterDirection::Forward) => {
+                let end = match prefix_successor(&category_prefix) {
+                    Some(successor) => Bound::Excluded(successor),
+                    None => Bound::Unbounded,
+                };
+                (Bound::Included(category_key(category, &from)), end)
+            }
+            (StorageScanMode::From(from), StorageIterDirection::Reverse) => (
+                Bound::Included(category_prefix.clone()),
+                Bound::Included(category_key(category, &from)),
+            ),
+        };
+
+        let db = self.db.read();
+        let range = db.range::<Vec<u8>, _>((start, end));
+        let strip = |(key, val): (&Vec<u8>, &Vec<u8>)| {
+            (
+                Bytes::from(key[category_prefix.len()..].to_vec()),
+                Bytes::from(val.clone()),
+            )
+        };
+
+        let pairs = match direction {
+            StorageIterDirection::Forward => range.take(limit).map(strip).collect(),
+            StorageIterDirection::Reverse => range.rev().take(limit).map(strip).collect(),
+        };
+
+        Ok(pairs)
+    }
+}
+
+// Schemas of different categories may share keys, e.g. a signed transaction
+// and its receipt are both keyed by the transaction hash.
+fn category_key(category: StorageCategory, key: &[u8]) -> Vec<u8> {
+    let mut category_key = format!("{}:", category).into_bytes();
+    category_key.extend_from_slice(key);
+    category_key
 }
 
 #[derive(Debug, Display, From)]
@@ -1,16 +1,74 @@
+use std::collections::HashMap;
 use std::error::Error;
 use std::path::Path;
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use derive_more::{Display, From};
-use rocksdb::{ColumnFamily, Options, WriteBatch, DB};
+use rocksdb::{
+    BlockBasedOptions, ColumnFamily, ColumnFamilyDescriptor, DBCompressionType, Direction,
+    IteratorMode, Options, WriteBatch, DB,
+};
 
 use protocol::codec::ProtocolCodec;
-use protocol::traits::{StorageAdapter, StorageBatchModify, StorageCategory, StorageSchema};
+use protocol::traits::{
+    prefix_successor, StorageAdapter, StorageBatch, StorageBatchModify, StorageCategory,
+    StorageIterDirection, StorageScanMode, StorageSchema,
+};
 use protocol::Bytes;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RocksCompression {
+    None,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+impl From<RocksCompression> for DBCompressionType {
+    fn from(compression: RocksCompression) -> Self {
+        match compression {
+            RocksCompression::None => DBCompressionType::None,
+            RocksCompression::Snappy => DBCompressionType::Snappy,
+            RocksCompression::Lz4 => DBCompressionType::Lz4,
+            RocksCompression::Zstd => DBCompressionType::Zstd,
+        }
+    }
+}
+
+/// Tuning options of a column family, unset options keep the rocksdb
+/// defaults.
+#[derive(Debug, Clone, Default)]
+pub struct RocksColumnOptions {
+    pub block_cache_size:  Option<usize>,
+    pub compression:       Option<RocksCompression>,
+    pub bloom_filter_bits: Option<i32>,
+    pub write_buffer_size: Option<usize>,
+}
+
+impl RocksColumnOptions {
+    pub fn apply(&self, opts: &mut Options) {
+        if let Some(compression) = self.compression {
+            opts.set_compression_type(compression.into());
+        }
+        if let Some(size) = self.write_buffer_size {
+            opts.set_write_buffer_size(size);
+        }
+
+        if self.block_cache_size.is_some() || self.bloom_filter_bits.is_some() {
+            let mut block_opts = BlockBasedOptions::default();
+            if let Some(size) = self.block_cache_size {
+                block_opts.set_lru_cache(size);
+            }
+            if let Some(bits) = self.bloom_filter_bits {
+                block_opts.set_bloom_filter(bits, false);
+            }
+            opts.set_block_based_table_factory(&block_opts);
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct RocksAdapter {
     db: Arc<DB>,
@@ -18,19 +76,39 @@ pub struct RocksAdapter {
 
 impl RocksAdapter {
     pub fn new<P: AsRef<Path>>(path: P, max_open_files: i32) -> ProtocolResult<Self> {
+        Self::with_options(path, max_open_files, HashMap::new())
+    }
+
+    pub fn with_options<P: AsRef<Path>>(
+        path: P,
+        max_open_files: i32,
+        column_options: HashMap<StorageCategory, RocksColumnOptions>,
+    ) -> ProtocolResult<Self> {
         let mut opts = Options::default();
         opts.create_if_missing(true);
         opts.create_missing_column_families(true);
         opts.set_max_open_files(max_open_files);
 
         let categories = [
-            map_category(StorageCategory::Block),
-            map_category(StorageCategory::Receipt),
-            map_category(StorageCategory::SignedTransaction),
-            map_category(StorageCategory::Wal),
+            StorageCategory::Block,
+            StorageCategory::Receipt,
+            StorageCategory::SignedTransaction,
+            StorageCategory::Wal,
         ];
+        let descriptors = categories
+            .iter()
+            .map(|category| {
+                let mut cf_opts = Options::default();
+                if let Some(options) = column_options.get(category) {
+                    options.apply(&mut cf_opts);
+                }
 
-        let db = DB::open_cf(&opts, path, categories.iter()).map_err(RocksAdapterError::from)?;
+                ColumnFamilyDescriptor::new(map_category(*category), cf_opts)
+            })
+            .collect::<Vec<_>>();
+
+        let db =
+            DB::open_cf_descriptors(&opts, path, descriptors).map_err(RocksAdapterError::from)?;
 
         Ok(RocksAdapter { db: Arc::new(db) })
     }
@@ -138,6 +216,75 @@ impl StorageAdapter for RocksAdapter {
         self.db.write(batch).map_err(RocksAdapterError::from)?;
         Ok(())
     }
+
+    async fn write_batch(&self, batch: StorageBatch) -> ProtocolResult<()> {
+        let mut write_batch = WriteBatch::default();
+
+        for (category, key, value) in batch.into_ops().into_iter() {
+            let column = get_column_by_category(&self.db, category)?;
+
+            match value {
+                Some(value) => db!(write_batch, put_cf, column, key, value)?,
+                None => db!(write_batch, delete_cf, column, key)?,
+            }
+        }
+
+        self.db
+            .write(write_batch)
+            .map_err(RocksAdapterError::from)?;
+        Ok(())
+    }
+
+    async fn scan(
+        &self,
+        category: StorageCategory,
+        mode: StorageScanMode,
+        direction: StorageIterDirection,
+        limit: usize,
+    ) -> ProtocolResult<Vec<(Bytes, Bytes)>> {
+        let column = get_column_by_category(&self.db, category)?;
+
+        let (prefix, start) = match mode {
+            StorageScanMode::Prefix(prefix) => {
+                let start = match direction {
+                    StorageIterDirection::Forward => Some(prefix.to_vec()),
+                    StorageIterDirection::Reverse => prefix_successor(&prefix),
+                };
+                (prefix, start)
+            }
+            StorageScanMode::From(from) => (Bytes::new(), Some(from.to_vec())),
+        };
+
+        let mode = match (&start, direction) {
+            (Some(start), StorageIterDirection::Forward) => {
+                IteratorMode::From(start, Direction::Forward)
+            }
+            (Some(start), StorageIterDirection::Reverse) => {
+                IteratorMode::From(start, Direction::Reverse)
+            }
+            (None, StorageIterDirection::Forward) => IteratorMode::Start,
+            (None, StorageIterDirection::Reverse) => IteratorMode::End,
+        };
+
+        // A reverse prefix scan starts at the successor of the prefix, which
+        // is not part of the result if it exists.
+        let skip = match direction {
+            StorageIterDirection::Reverse if !prefix.is_empty() => start.clone(),
+            _ => None,
+        };
+
+        let pairs = db!(self.db, iterator_cf, column, mode)?
+            .skip_while(|(key, _)| {
+                skip.as_ref()
+                    .map_or(false, |skip| key.as_ref() == &skip[..])
+            })
+            .take_while(|(key, _)| key.starts_with(&prefix))
+            .take(limit)
+            .map(|(key, val)| (Bytes::from(key.to_vec()), Bytes::from(val.to_vec())))
+            .collect::<Vec<_>>();
+
+        Ok(pairs)
+    }
 }
 
 #[derive(Debug, Display, From)]
@@ -178,7 +325,14 @@ fn map_category(c: StorageCategory) -> &'static str {
 }
 
 fn get_column<S: StorageSchema>(db: &DB) -> Result<ColumnFamily, RocksAdapterError> {
-    let category = map_category(S::category());
+    get_column_by_category(db, S::category())
+}
+
+fn get_column_by_category(
+    db: &DB,
+    category: StorageCategory,
+) -> Result<ColumnFamily, RocksAdapterError> {
+    let category = map_category(category);
 
     let column = db
         .cf_handle(category)
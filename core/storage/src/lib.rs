@@ -18,7 +18,8 @@ use common_apm::muta_apm;
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
-    Context, Storage, StorageAdapter, StorageBatchModify, StorageCategory, StorageSchema,
+    Context, Storage, StorageAdapter, StorageBatch, StorageBatchModify, StorageCategory,
+    StorageSchema,
 };
 use protocol::types::{Block, Hash, Proof, Receipt, SignedTransaction};
 use protocol::Bytes;
@@ -44,6 +45,186 @@ impl<Adapter: StorageAdapter> ImplStorage<Adapter> {
             latest_block: RwLock::new(None),
         }
     }
+
+    /// Check that the latest block pointer, the latest proof and the blocks
+    /// around them agree with each other. This is meant to run at startup,
+    /// before anything reads from the storage.
+    ///
+    /// A block that was written without moving the latest block pointer is
+    /// adopted if it is complete and matches the latest proof, and removed
+    /// otherwise. Problems that can not be fixed locally are only reported.
+    pub async fn check_consistency(&self) -> ProtocolResult<ConsistencyReport> {
+        let mut report = ConsistencyReport::default();
+
+        let mut latest_block = match self
+            .adapter
+            .get::<LatestBlockSchema>(LATEST_BLOCK_KEY.clone())
+            .await?
+        {
+            Some(block) => block,
+            None => return Ok(report),
+        };
+        let opt_proof = self
+            .adapter
+            .get::<LatestProofSchema>(LATEST_PROOF_KEY.clone())
+            .await?;
+
+        // Blocks above the latest block pointer are leftovers of an
+        // interrupted commit.
+        let mut height = latest_block.header.height + 1;
+        while let Some(block) = self.adapter.get::<BlockSchema>(height).await? {
+            let block_hash = Hash::digest(block.encode_fixed()?);
+            let is_next = height == latest_block.header.height + 1;
+            let is_proven = opt_proof.as_ref().map_or(false, |proof| {
+                proof.height == height && proof.block_hash == block_hash
+            });
+            let is_complete = self
+                .adapter
+                .get_batch::<TransactionSchema>(block.ordered_tx_hashes.clone())
+                .await?
+                .iter()
+                .all(Option::is_some);
+
+            if is_next && is_proven && is_complete {
+                self.adapter
+                    .insert::<LatestBlockSchema>(LATEST_BLOCK_KEY.clone(), block.clone())
+                    .await?;
+                report.repaired.push(Inconsistency::UnlinkedBlock(height));
+                latest_block = block;
+            } else {
+                let mut batch = StorageBatch::new();
+                batch.remove::<BlockSchema>(height).await?;
+                batch.remove::<HashBlockSchema>(block_hash).await?;
+                self.adapter.write_batch(batch).await?;
+                report.repaired.push(Inconsistency::DanglingBlock(height));
+            }
+
+            height += 1;
+        }
+
+        let latest_height = latest_block.header.height;
+        let latest_hash = Hash::digest(latest_block.encode_fixed()?);
+
+        match opt_proof {
+            Some(proof) => {
+                if proof.height != latest_height || proof.block_hash != latest_hash {
+                    report.unrepaired.push(Inconsistency::ProofMismatch {
+                        proof_height: proof.height,
+                        block_height: latest_height,
+                    });
+                }
+            }
+            None => report
+                .unrepaired
+                .push(Inconsistency::MissingProof(latest_height)),
+        }
+
+        let txs = self
+            .adapter
+            .get_batch::<TransactionSchema>(latest_block.ordered_tx_hashes.clone())
+            .await?;
+        for (tx_hash, tx) in latest_block.ordered_tx_hashes.iter().zip(txs.iter()) {
+            if tx.is_none() {
+                report.unrepaired.push(Inconsistency::MissingTransaction(
+                    latest_height,
+                    tx_hash.clone(),
+                ));
+            }
+        }
+
+        // Receipts are written once a block is executed, apart from the block
+        // itself. A block above the latest exec height whose receipts are
+        // missing is repaired by executing it again at startup, so only the
+        // block at the latest exec height is guaranteed to have them here.
+        let exec_height = latest_block.header.exec_height;
+        for height in exec_height + 1..=latest_height {
+            let block = match self.adapter.get::<BlockSchema>(height).await? {
+                Some(block) => block,
+                None => {
+                    report.unrepaired.push(Inconsistency::MissingBlock(height));
+                    continue;
+                }
+            };
+            let receipts = self
+                .adapter
+                .get_batch::<ReceiptSchema>(block.ordered_tx_hashes)
+                .await?;
+            if !receipts.iter().all(Option::is_some) {
+                report.unexecuted.push(height);
+            }
+        }
+
+        if let Some(exec_block) = self.adapter.get::<BlockSchema>(exec_height).await? {
+            let receipts = self
+                .adapter
+                .get_batch::<ReceiptSchema>(exec_block.ordered_tx_hashes.clone())
+                .await?;
+            for (tx_hash, receipt) in exec_block.ordered_tx_hashes.iter().zip(receipts.iter()) {
+                if receipt.is_none() {
+                    report
+                        .unrepaired
+                        .push(Inconsistency::MissingReceipt(exec_height, tx_hash.clone()));
+                }
+            }
+        } else {
+            report
+                .unrepaired
+                .push(Inconsistency::MissingBlock(exec_height));
+        }
+
+        self.latest_block.write().await.replace(latest_block);
+
+        Ok(report)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ConsistencyReport {
+    pub repaired:   Vec<Inconsistency>,
+    pub unrepaired: Vec<Inconsistency>,
+    /// The heights of the committed blocks whose receipts are missing, which
+    /// have to be executed again.
+    pub unexecuted: Vec<u64>,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.repaired.is_empty() && self.unrepaired.is_empty() && self.unexecuted.is_empty()
+    }
+}
+
+#[derive(Debug, Display)]
+pub enum Inconsistency {
+    #[display(
+        fmt = "block {} is stored but the latest block pointer is behind it",
+        _0
+    )]
+    UnlinkedBlock(u64),
+
+    #[display(fmt = "block {} is a leftover of an interrupted commit", _0)]
+    DanglingBlock(u64),
+
+    #[display(fmt = "block {} not found", _0)]
+    MissingBlock(u64),
+
+    #[display(fmt = "latest proof not found, latest block height {}", _0)]
+    MissingProof(u64),
+
+    #[display(
+        fmt = "latest proof height {} does not match latest block height {}",
+        proof_height,
+        block_height
+    )]
+    ProofMismatch {
+        proof_height: u64,
+        block_height: u64,
+    },
+
+    #[display(fmt = "transaction {:?} of block {} not found", _1, _0)]
+    MissingTransaction(u64, Hash),
+
+    #[display(fmt = "receipt {:?} of block {} not found", _1, _0)]
+    MissingReceipt(u64, Hash),
 }
 
 macro_rules! impl_storage_schema_for {
@@ -154,6 +335,47 @@ impl<Adapter: StorageAdapter> Storage for ImplStorage<Adapter> {
         Ok(())
     }
 
+    #[muta_apm::derive::tracing_span(
+        kind = "storage",
+        logs = "{'txs_len': 'signed_txs.len()', 'receipts_len': 'receipts.len()'}"
+    )]
+    async fn commit_block_bundle(
+        &self,
+        ctx: Context,
+        block: Block,
+        proof: Proof,
+        signed_txs: Vec<SignedTransaction>,
+        receipts: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        let height = block.header.height;
+        let block_hash = Hash::digest(block.encode_fixed()?);
+
+        let mut batch = StorageBatch::new();
+        for stx in signed_txs.into_iter() {
+            batch
+                .insert::<TransactionSchema>(stx.tx_hash.clone(), stx)
+                .await?;
+        }
+        for receipt in receipts.into_iter() {
+            batch
+                .insert::<ReceiptSchema>(receipt.tx_hash.clone(), receipt)
+                .await?;
+        }
+        batch.insert::<BlockSchema>(height, block.clone()).await?;
+        batch.insert::<HashBlockSchema>(block_hash, height).await?;
+        batch
+            .insert::<LatestBlockSchema>(LATEST_BLOCK_KEY.clone(), block.clone())
+            .await?;
+        batch
+            .insert::<LatestProofSchema>(LATEST_PROOF_KEY.clone(), proof)
+            .await?;
+
+        self.adapter.write_batch(batch).await?;
+        self.latest_block.write().await.replace(block);
+
+        Ok(())
+    }
+
     #[muta_apm::derive::tracing_span(kind = "storage")]
     async fn get_transaction_by_hash(
         &self,
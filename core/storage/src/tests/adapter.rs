@@ -1,10 +1,16 @@
-use protocol::traits::{StorageAdapter, StorageBatchModify};
+use std::collections::HashMap;
+
+use protocol::codec::ProtocolCodec;
+use protocol::traits::{
+    StorageAdapter, StorageBatch, StorageBatchModify, StorageCategory, StorageIterDirection,
+};
 use protocol::types::Hash;
+use protocol::Bytes;
 
 use crate::adapter::memory::MemoryAdapter;
-use crate::adapter::rocks::RocksAdapter;
-use crate::tests::{get_random_bytes, mock_signed_tx};
-use crate::TransactionSchema;
+use crate::adapter::rocks::{RocksAdapter, RocksColumnOptions, RocksCompression};
+use crate::tests::{get_random_bytes, mock_receipt, mock_signed_tx};
+use crate::{ReceiptSchema, TransactionSchema};
 
 #[test]
 fn test_adapter_insert() {
@@ -12,6 +18,22 @@ fn test_adapter_insert() {
     adapter_insert_test(RocksAdapter::new("rocksdb/test_adapter_insert".to_string(), 64).unwrap())
 }
 
+#[test]
+fn test_adapter_with_column_options() {
+    let mut options = HashMap::new();
+    options.insert(StorageCategory::SignedTransaction, RocksColumnOptions {
+        block_cache_size:  Some(1 << 20),
+        compression:       Some(RocksCompression::Lz4),
+        bloom_filter_bits: Some(10),
+        write_buffer_size: Some(1 << 20),
+    });
+
+    adapter_insert_test(
+        RocksAdapter::with_options("rocksdb/test_adapter_with_column_options", 64, options)
+            .unwrap(),
+    )
+}
+
 #[test]
 fn test_adapter_batch_modify() {
     adapter_batch_modify_test(MemoryAdapter::new());
@@ -26,6 +48,20 @@ fn test_adapter_remove() {
     adapter_remove_test(RocksAdapter::new("rocksdb/test_adapter_remove".to_string(), 64).unwrap())
 }
 
+#[test]
+fn test_adapter_write_batch() {
+    adapter_write_batch_test(MemoryAdapter::new());
+    adapter_write_batch_test(
+        RocksAdapter::new("rocksdb/test_adapter_write_batch".to_string(), 64).unwrap(),
+    )
+}
+
+#[test]
+fn test_adapter_iter() {
+    adapter_iter_test(MemoryAdapter::new());
+    adapter_iter_test(RocksAdapter::new("rocksdb/test_adapter_iter".to_string(), 64).unwrap())
+}
+
 fn adapter_insert_test(db: impl StorageAdapter) {
     let tx_hash = Hash::digest(get_random_bytes(10));
     let stx = mock_signed_tx(tx_hash.clone());
@@ -74,3 +110,80 @@ fn adapter_remove_test(db: impl StorageAdapter) {
     let is_exist = exec!(db.contains::<TransactionSchema>(tx_hash.clone()));
     assert!(!is_exist);
 }
+
+fn adapter_write_batch_test(db: impl StorageAdapter) {
+    let tx_hash = Hash::digest(get_random_bytes(10));
+    let removed_hash = Hash::digest(get_random_bytes(10));
+    exec!(
+        db.insert::<TransactionSchema>(removed_hash.clone(), mock_signed_tx(removed_hash.clone()))
+    );
+
+    let mut batch = StorageBatch::new();
+    exec!(batch.insert::<TransactionSchema>(tx_hash.clone(), mock_signed_tx(tx_hash.clone())));
+    exec!(batch.insert::<ReceiptSchema>(tx_hash.clone(), mock_receipt(tx_hash.clone())));
+    exec!(batch.remove::<TransactionSchema>(removed_hash.clone()));
+    assert_eq!(batch.len(), 3);
+
+    exec!(db.write_batch(batch));
+
+    assert!(exec!(db.contains::<TransactionSchema>(tx_hash.clone())));
+    assert!(exec!(db.contains::<ReceiptSchema>(tx_hash)));
+    assert!(!exec!(db.contains::<TransactionSchema>(removed_hash)));
+}
+
+fn adapter_iter_test(db: impl StorageAdapter) {
+    let hash = |prefix: u8, i: u8| {
+        let mut bytes = vec![0u8; 32];
+        bytes[0] = prefix;
+        bytes[1] = i;
+        Hash::from_bytes(Bytes::from(bytes)).unwrap()
+    };
+
+    for (prefix, count) in [(0xaa, 5), (0xbb, 3)].iter() {
+        for i in 0..*count {
+            let tx_hash = hash(*prefix, i);
+            exec!(db.insert::<TransactionSchema>(tx_hash.clone(), mock_signed_tx(tx_hash)));
+        }
+    }
+
+    // Keys are encoded hashes, the prefix covers the encoding header and the
+    // first byte of the hash.
+    let mut encoded = exec!(hash(0xaa, 0).encode());
+    let prefix = encoded.split_to(encoded.len() - 31);
+
+    let tx_hashes = |entries: Vec<(Hash, _)>| {
+        entries
+            .into_iter()
+            .map(|(key, stx): (Hash, protocol::types::SignedTransaction)| {
+                assert_eq!(key, stx.tx_hash);
+                key
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let entries =
+        exec!(db.iter::<TransactionSchema>(prefix.clone(), StorageIterDirection::Forward, 100));
+    assert_eq!(
+        tx_hashes(entries),
+        (0..5).map(|i| hash(0xaa, i)).collect::<Vec<_>>()
+    );
+
+    let entries =
+        exec!(db.iter::<TransactionSchema>(prefix.clone(), StorageIterDirection::Reverse, 2));
+    assert_eq!(tx_hashes(entries), vec![hash(0xaa, 4), hash(0xaa, 3)]);
+
+    let entries = exec!(db.range::<TransactionSchema>(None, StorageIterDirection::Forward, 100));
+    assert_eq!(entries.len(), 8);
+
+    let entries =
+        exec!(db.range::<TransactionSchema>(Some(hash(0xaa, 2)), StorageIterDirection::Forward, 3));
+    assert_eq!(tx_hashes(entries), vec![
+        hash(0xaa, 2),
+        hash(0xaa, 3),
+        hash(0xaa, 4)
+    ]);
+
+    let entries =
+        exec!(db.range::<TransactionSchema>(Some(hash(0xbb, 0)), StorageIterDirection::Reverse, 2));
+    assert_eq!(tx_hashes(entries), vec![hash(0xbb, 0), hash(0xaa, 4)]);
+}
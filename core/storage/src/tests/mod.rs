@@ -41,6 +41,8 @@ fn mock_signed_tx(tx_hash: Hash) -> SignedTransaction {
         tx_hash,
         pubkey: Default::default(),
         signature: Default::default(),
+        scheme: Default::default(),
+        kind: Default::default(),
     }
 }
 
@@ -61,6 +63,8 @@ fn mock_receipt(tx_hash: Hash) -> Receipt {
         height: 10,
         tx_hash,
         cycles_used: 10,
+        cycles_refunded: 0,
+        cycles_breakdown: vec![],
         events: vec![],
         response,
     }
@@ -82,6 +86,7 @@ fn mock_block(height: u64, block_hash: Hash) -> Block {
         receipt_root: Vec::new(),
         cycles_used: vec![999_999],
         proposer: Address::from_hex(addr_str).unwrap(),
+        seed_signature: Bytes::new(),
         proof: mock_proof(block_hash),
         validator_version: 1,
         validators: Vec::new(),
@@ -1,12 +1,12 @@
 use std::sync::Arc;
 
 use protocol::fixed_codec::FixedCodec;
-use protocol::traits::{Context, Storage};
+use protocol::traits::{Context, Storage, StorageAdapter};
 use protocol::types::Hash;
 
 use crate::adapter::memory::MemoryAdapter;
 use crate::tests::{get_random_bytes, mock_block, mock_proof, mock_receipt, mock_signed_tx};
-use crate::ImplStorage;
+use crate::{BlockSchema, ImplStorage, Inconsistency};
 
 #[test]
 fn test_storage_block_insert() {
@@ -100,3 +100,111 @@ fn test_storage_wal_insert() {
     let info_2 = exec!(storage.load_overlord_wal(Context::new(),));
     assert_eq!(info, info_2);
 }
+
+#[test]
+fn test_storage_commit_block_bundle() {
+    let storage = ImplStorage::new(Arc::new(MemoryAdapter::new()));
+
+    let mut block = mock_block(100, Hash::digest(get_random_bytes(10)));
+    let mut txs = Vec::new();
+    let mut receipts = Vec::new();
+
+    for _ in 0..10 {
+        let tx_hash = Hash::digest(get_random_bytes(10));
+        block.ordered_tx_hashes.push(tx_hash.clone());
+        txs.push(mock_signed_tx(tx_hash.clone()));
+        receipts.push(mock_receipt(tx_hash));
+    }
+
+    let block_hash = Hash::digest(block.encode_fixed().unwrap());
+    let mut proof = mock_proof(block_hash.clone());
+    proof.height = 100;
+
+    exec!(storage.commit_block_bundle(Context::new(), block.clone(), proof, txs, receipts));
+
+    let latest_block = exec!(storage.get_latest_block(Context::new()));
+    assert_eq!(latest_block.header.height, 100);
+
+    let latest_proof = exec!(storage.get_latest_proof(Context::new()));
+    assert_eq!(latest_proof.block_hash, block_hash);
+
+    let block_2 = exec!(storage.get_block_by_hash(Context::new(), block_hash));
+    assert_eq!(block_2.header.height, 100);
+
+    let txs = exec!(storage.get_transactions(Context::new(), block.ordered_tx_hashes.clone()));
+    assert_eq!(txs.len(), 10);
+
+    let receipts = exec!(storage.get_receipts(Context::new(), block.ordered_tx_hashes));
+    assert_eq!(receipts.len(), 10);
+}
+
+#[test]
+fn test_storage_check_consistency() {
+    let adapter = Arc::new(MemoryAdapter::new());
+    let storage = ImplStorage::new(Arc::clone(&adapter));
+
+    let report = exec!(storage.check_consistency());
+    assert!(report.is_consistent());
+
+    let mut block = mock_block(1, Hash::digest(get_random_bytes(10)));
+    block.header.exec_height = 1;
+    let block_hash = Hash::digest(block.encode_fixed().unwrap());
+    let mut proof = mock_proof(block_hash);
+    proof.height = 1;
+
+    exec!(storage.commit_block_bundle(Context::new(), block, proof, vec![], vec![]));
+
+    let report = exec!(storage.check_consistency());
+    assert!(report.is_consistent());
+
+    // A block written without its proof is removed.
+    let dangling = mock_block(2, Hash::digest(get_random_bytes(10)));
+    exec!(adapter.insert::<BlockSchema>(2, dangling));
+
+    let report = exec!(storage.check_consistency());
+    match report.repaired.as_slice() {
+        [Inconsistency::DanglingBlock(2)] => (),
+        _ => panic!("unexpected report {:?}", report),
+    }
+    assert!(exec!(adapter.get::<BlockSchema>(2)).is_none());
+
+    // A block written with its proof but without moving the latest block
+    // pointer is adopted.
+    let mut unlinked = mock_block(2, Hash::digest(get_random_bytes(10)));
+    unlinked.header.exec_height = 2;
+    let unlinked_hash = Hash::digest(unlinked.encode_fixed().unwrap());
+    let mut proof = mock_proof(unlinked_hash);
+    proof.height = 2;
+    exec!(adapter.insert::<BlockSchema>(2, unlinked));
+    exec!(storage.update_latest_proof(Context::new(), proof));
+
+    let report = exec!(storage.check_consistency());
+    match report.repaired.as_slice() {
+        [Inconsistency::UnlinkedBlock(2)] => (),
+        _ => panic!("unexpected report {:?}", report),
+    }
+    assert!(report.unrepaired.is_empty());
+
+    let latest_block = exec!(storage.get_latest_block(Context::new()));
+    assert_eq!(latest_block.header.height, 2);
+
+    // A block committed before it's executed has no receipts, it has to be
+    // executed again.
+    let mut block = mock_block(3, Hash::digest(get_random_bytes(10)));
+    let tx_hash = Hash::digest(get_random_bytes(10));
+    block.ordered_tx_hashes.push(tx_hash.clone());
+    let block_hash = Hash::digest(block.encode_fixed().unwrap());
+    let mut proof = mock_proof(block_hash);
+    proof.height = 3;
+    let txs = vec![mock_signed_tx(tx_hash.clone())];
+    exec!(storage.commit_block_bundle(Context::new(), block, proof, txs, vec![]));
+
+    let report = exec!(storage.check_consistency());
+    assert_eq!(report.unexecuted, vec![3]);
+    assert!(report.repaired.is_empty());
+    assert!(report.unrepaired.is_empty());
+
+    exec!(storage.insert_receipts(Context::new(), vec![mock_receipt(tx_hash)]));
+    let report = exec!(storage.check_consistency());
+    assert!(report.is_consistent());
+}
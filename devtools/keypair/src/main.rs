@@ -5,9 +5,11 @@ use std::convert::TryFrom;
 use std::default::Default;
 
 use clap::App;
-use ophelia::{PublicKey, ToBlsPublicKey};
+use common_crypto::Sm2;
+use ophelia::{PublicKey, ToBlsPublicKey, ToPublicKey};
 use ophelia_bls_amcl::BlsPrivateKey;
-use protocol::types::{Address, Hash};
+use ophelia_ed25519::Ed25519PrivateKey;
+use protocol::types::{Address, Hash, SignatureScheme};
 use protocol::{Bytes, BytesMut};
 use rand::distributions::Alphanumeric;
 use rand::Rng;
@@ -18,6 +20,7 @@ use tentacle_secio::SecioKeyPair;
 #[derive(Default, Serialize, Debug)]
 struct Keypair {
     pub index:          usize,
+    pub scheme:         String,
     pub private_key:    String,
     pub public_key:     String,
     pub address:        String,
@@ -41,6 +44,7 @@ pub fn main() {
         panic!("private keys length can not be larger than number");
     }
 
+    let scheme = value_t!(m, "scheme", String).unwrap();
     let common_ref_encoded = value_t!(m, "common_ref", String).unwrap();
     let common_ref = if common_ref_encoded.is_empty() {
         rand::thread_rng()
@@ -68,12 +72,11 @@ pub fn main() {
             OsRng.fill_bytes(&mut seed);
             Hash::digest(BytesMut::from(seed.as_ref()).freeze()).as_bytes()
         };
-        let keypair = SecioKeyPair::secp256k1_raw_key(seckey.as_ref()).expect("secp256k1 keypair");
-        let pubkey = keypair.to_public_key().inner();
-        let user_addr = Address::from_pubkey_bytes(pubkey.into()).expect("user addr");
+        let (pubkey, user_addr) = scheme_pubkey(&scheme, seckey.as_ref());
 
+        k.scheme = scheme.clone();
         k.private_key = add_0x(hex::encode(seckey.as_ref()));
-        k.public_key = add_0x(hex::encode(keypair.to_public_key().inner()));
+        k.public_key = add_0x(hex::encode(pubkey));
         k.address = add_0x(user_addr.as_hex());
 
         let priv_key =
@@ -87,6 +90,26 @@ pub fn main() {
     println!("{}", output_str);
 }
 
+// The pubkey of the scheme and its address.
+fn scheme_pubkey(scheme: &str, seckey: &[u8]) -> (Vec<u8>, Address) {
+    let scheme = SignatureScheme::from_name(scheme)
+        .expect("scheme should be one of secp256k1, ed25519 and sm2");
+    let pubkey = match scheme {
+        SignatureScheme::Secp256k1 => {
+            let keypair = SecioKeyPair::secp256k1_raw_key(seckey).expect("secp256k1 keypair");
+            keypair.to_public_key().inner()
+        }
+        SignatureScheme::Ed25519 => {
+            let priv_key = Ed25519PrivateKey::try_from(seckey).expect("ed25519 private key");
+            priv_key.pub_key().to_bytes().to_vec()
+        }
+        SignatureScheme::Sm2 => Sm2::pub_key(seckey).expect("sm2 private key"),
+    };
+
+    let addr = Address::from_scheme_pubkey(scheme, Bytes::from(pubkey.clone())).expect("user addr");
+    (pubkey, addr)
+}
+
 fn add_0x(s: String) -> String {
     "0x".to_owned() + &s
 }
@@ -1,10 +1,16 @@
+#[macro_use]
+extern crate clap;
+
 use asset::AssetService;
+use clap::{App, Arg, SubCommand};
 use derive_more::{Display, From};
 use metadata::MetadataService;
-use muta::MutaBuilder;
+use muta::{ExportFormat, ExportOptions, MutaBuilder};
 use protocol::traits::{Service, ServiceMapping, ServiceSDK};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+use riscv::RiscvService;
 use util::UtilService;
+use wasm::WasmService;
 
 struct DefaultServiceMapping;
 
@@ -18,6 +24,8 @@ impl ServiceMapping for DefaultServiceMapping {
             "asset" => Box::new(AssetService::new(sdk)) as Box<dyn Service>,
             "metadata" => Box::new(MetadataService::new(sdk)) as Box<dyn Service>,
             "util" => Box::new(UtilService::new(sdk)) as Box<dyn Service>,
+            "wasm" => Box::new(WasmService::new(sdk)) as Box<dyn Service>,
+            "riscv" => Box::new(RiscvService::new(sdk)) as Box<dyn Service>,
             _ => {
                 return Err(MappingError::NotFoundService {
                     service: name.to_owned(),
@@ -30,7 +38,13 @@ impl ServiceMapping for DefaultServiceMapping {
     }
 
     fn list_service_name(&self) -> Vec<String> {
-        vec!["asset".to_owned(), "metadata".to_owned(), "util".to_owned()]
+        vec![
+            "asset".to_owned(),
+            "metadata".to_owned(),
+            "util".to_owned(),
+            "wasm".to_owned(),
+            "riscv".to_owned(),
+        ]
     }
 }
 
@@ -51,6 +65,86 @@ fn main() {
     let builer = builder.service_mapping(DefaultServiceMapping {});
 
     let muta = builer.build().expect("build");
+
+    let matches = App::new("muta-chain")
+        .subcommand(
+            SubCommand::with_name("db")
+                .about("Inspect the chain data")
+                .subcommand(
+                    SubCommand::with_name("check")
+                        .about("Check the stored chain from genesis to the latest block")
+                        .arg(
+                            Arg::with_name("truncate")
+                                .long("truncate")
+                                .help("Truncate the chain to the last good height"),
+                        ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("export")
+                .about("Export blocks, transactions, receipts and events for analytics")
+                .arg(
+                    Arg::with_name("output")
+                        .long("output")
+                        .short("o")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Output directory, an export in it is resumed"),
+                )
+                .arg(
+                    Arg::with_name("format")
+                        .long("format")
+                        .takes_value(true)
+                        .possible_values(&["ndjson"])
+                        .default_value("ndjson"),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .default_value("0")
+                        .help("First height to export if there is nothing to resume"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .help("Last height to export, defaults to the latest executed height"),
+                ),
+        )
+        .get_matches();
+
+    if let Some(db) = matches.subcommand_matches("db") {
+        if let Some(check) = db.subcommand_matches("check") {
+            let report = muta
+                .check_db(check.is_present("truncate"))
+                .expect("check db");
+            println!("{}", report);
+
+            if !report.is_ok() {
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(export) = matches.subcommand_matches("export") {
+        let options = ExportOptions {
+            output: export.value_of("output").expect("output").into(),
+            format: value_t!(export, "format", ExportFormat).unwrap_or_else(|e| e.exit()),
+            from:   value_t!(export, "from", u64).unwrap_or_else(|e| e.exit()),
+            to:     export
+                .value_of("to")
+                .map(|_| value_t!(export, "to", u64).unwrap_or_else(|e| e.exit())),
+        };
+
+        match muta.export(options).expect("export") {
+            Some(height) => println!("exported up to height {}", height),
+            None => println!("nothing to export"),
+        }
+        return;
+    }
+
     muta.run().expect("run");
 }
 
@@ -7,13 +7,16 @@ use std::rc::Rc;
 
 use bytes::Bytes;
 use derive_more::{Display, From};
+use serde::Serialize;
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
-    ChainQuerier, Dispatcher, ServiceResponse, ServiceSDK, ServiceState, StoreArray, StoreBool,
-    StoreMap, StoreString, StoreUint64,
+    ChainQuerier, Dispatcher, ServiceRegistration, ServiceResponse, ServiceSDK, ServiceState,
+    StoreArray, StoreBool, StoreMap, StoreString, StoreUint64,
+};
+use protocol::types::{
+    Address, Block, Hash, Receipt, ServiceContext, SignedTransaction, MAX_EVENT_TOPICS,
 };
-use protocol::types::{Address, Block, Hash, Receipt, ServiceContext, SignedTransaction};
 use protocol::{ProtocolError, ProtocolErrorKind};
 
 use crate::binding::store::{
@@ -24,6 +27,9 @@ pub struct DefalutServiceSDK<S: ServiceState, C: ChainQuerier, D: Dispatcher> {
     state:         Rc<RefCell<S>>,
     chain_querier: Rc<C>,
     dispatcher:    D,
+    // The context of the call the service is built for, the storage refunds
+    // go to it.
+    context:       Option<ServiceContext>,
 }
 
 impl<S: ServiceState, C: ChainQuerier, D: Dispatcher> DefalutServiceSDK<S, C, D> {
@@ -32,8 +38,14 @@ impl<S: ServiceState, C: ChainQuerier, D: Dispatcher> DefalutServiceSDK<S, C, D>
             state,
             chain_querier,
             dispatcher,
+            context: None,
         }
     }
+
+    pub fn with_context(mut self, context: ServiceContext) -> Self {
+        self.context = Some(context);
+        self
+    }
 }
 
 impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
@@ -44,10 +56,10 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
         &mut self,
         var_name: &str,
     ) -> Box<dyn StoreMap<K, V>> {
-        Box::new(DefaultStoreMap::<S, K, V>::new(
-            Rc::clone(&self.state),
-            var_name,
-        ))
+        Box::new(
+            DefaultStoreMap::<S, K, V>::new(Rc::clone(&self.state), var_name)
+                .with_context(self.context.clone()),
+        )
     }
 
     // Alloc or recover a `Array` by` var_name`
@@ -165,6 +177,7 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
     }
 
     // Call other writable methods of `service` and return the results synchronously
+    // If the call returns an error, its writes and events are reverted
     // NOTE: You can use recursive calls, but the maximum call stack is 1024
     fn write(
         &mut self,
@@ -184,6 +197,65 @@ impl<S: 'static + ServiceState, C: ChainQuerier, D: Dispatcher> ServiceSDK
 
         self.dispatcher.write(ctx)
     }
+
+    fn checkpoint(&mut self) {
+        self.dispatcher.checkpoint()
+    }
+
+    fn revert_to_checkpoint(&mut self) {
+        self.dispatcher.revert_to_checkpoint()
+    }
+
+    fn release_checkpoint(&mut self) {
+        self.dispatcher.release_checkpoint()
+    }
+
+    fn register_service(
+        &mut self,
+        ctx: &ServiceContext,
+        registration: ServiceRegistration,
+    ) -> ServiceResponse<String> {
+        self.dispatcher.register(ctx.clone(), registration)
+    }
+
+    fn schedule(
+        &mut self,
+        ctx: &ServiceContext,
+        height: u64,
+        service: &str,
+        method: &str,
+        payload: &str,
+    ) -> ServiceResponse<String> {
+        let ctx = ServiceContext::with_context(
+            ctx,
+            None,
+            service.to_string(),
+            method.to_string(),
+            payload.to_string(),
+        );
+
+        self.dispatcher.schedule(ctx, height)
+    }
+
+    fn emit_event<Data: Serialize>(
+        &self,
+        ctx: &ServiceContext,
+        name: &str,
+        topics: Vec<String>,
+        data: &Data,
+    ) {
+        if topics.len() > MAX_EVENT_TOPICS {
+            panic!(
+                "service sdk emit event failed: {} topics, at most {}",
+                topics.len(),
+                MAX_EVENT_TOPICS
+            );
+        }
+
+        let data = serde_json::to_string(data)
+            .unwrap_or_else(|e| panic!("service sdk emit event failed: {}", e));
+        ctx.emit_indexed_event(name.to_owned(), topics, data);
+    }
 }
 
 #[derive(Debug, Display, From)]
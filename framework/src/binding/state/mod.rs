@@ -1,10 +1,13 @@
+mod readonly;
 mod trie;
 mod trie_db;
 
+pub use readonly::{ReadonlyServiceState, ReadonlyStateError};
 pub use trie::{MPTTrie, MPTTrieError};
 pub use trie_db::{RocksTrieDB, RocksTrieDBError};
 
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 
 use bytes::Bytes;
 use cita_trie::DB as TrieDB;
@@ -21,6 +24,20 @@ pub struct GeneralServiceState<DB: TrieDB> {
     // serializations.
     cache_map: HashMap<Bytes, Bytes>,
     stash_map: HashMap<Bytes, Bytes>,
+
+    // The cache at the start of every unfinished nested call
+    cache_checkpoints: Vec<HashMap<Bytes, Bytes>>,
+
+    // Only in the parallel execution
+    access:     Option<RefCell<AccessSet>>,
+    checkpoint: HashMap<Bytes, Bytes>,
+}
+
+// The encoded keys read and the pairs stashed since the tracking started.
+#[derive(Default)]
+pub struct AccessSet {
+    pub reads:  HashSet<Bytes>,
+    pub writes: HashMap<Bytes, Bytes>,
 }
 
 impl<DB: TrieDB> GeneralServiceState<DB> {
@@ -30,6 +47,94 @@ impl<DB: TrieDB> GeneralServiceState<DB> {
 
             cache_map: HashMap::new(),
             stash_map: HashMap::new(),
+
+            cache_checkpoints: Vec::new(),
+
+            access: None,
+            checkpoint: HashMap::new(),
+        }
+    }
+
+    pub fn root(&self) -> MerkleRoot {
+        self.trie.root()
+    }
+
+    pub fn cache_checkpoint(&mut self) {
+        self.cache_checkpoints.push(self.cache_map.clone());
+    }
+
+    // Drop the writes since the last cache checkpoint.
+    pub fn revert_to_cache_checkpoint(&mut self) {
+        if let Some(cache_map) = self.cache_checkpoints.pop() {
+            self.cache_map = cache_map;
+        }
+    }
+
+    // Keep the writes since the last cache checkpoint.
+    pub fn release_cache_checkpoint(&mut self) {
+        self.cache_checkpoints.pop();
+    }
+
+    pub fn start_tracking(&mut self) {
+        self.access = Some(RefCell::new(AccessSet::default()));
+    }
+
+    pub fn stop_tracking(&mut self) {
+        self.access = None;
+    }
+
+    // Take the access set tracked so far, and start over.
+    pub fn take_access_set(&mut self) -> AccessSet {
+        match self.access.as_ref() {
+            Some(access) => access.replace(AccessSet::default()),
+            None => AccessSet::default(),
+        }
+    }
+
+    // Cache the writes of an access set as if they were inserted.
+    pub fn apply_writes(&mut self, writes: HashMap<Bytes, Bytes>) {
+        self.cache_map.extend(writes);
+    }
+
+    // The writes not committed yet, stashed or not.
+    pub fn uncommitted(&self) -> HashMap<Bytes, Bytes> {
+        let mut writes = self.stash_map.clone();
+        writes.extend(self.cache_map.clone());
+        writes
+    }
+
+    // Start over from the uncommitted writes of another state of the same
+    // root, stashed.
+    pub fn set_uncommitted(&mut self, writes: HashMap<Bytes, Bytes>) {
+        self.cache_map.clear();
+        self.cache_checkpoints.clear();
+        self.stash_map = writes;
+    }
+
+    pub fn checkpoint(&mut self) {
+        self.checkpoint = self.stash_map.clone();
+    }
+
+    // Drop everything since the last checkpoint.
+    pub fn restore_checkpoint(&mut self) {
+        self.cache_map.clear();
+        self.cache_checkpoints.clear();
+        self.stash_map = self.checkpoint.clone();
+    }
+
+    fn track_read(&self, encoded_key: &Bytes) {
+        if let Some(access) = self.access.as_ref() {
+            access.borrow_mut().reads.insert(encoded_key.clone());
+        }
+    }
+
+    fn stash_and_track(&mut self) {
+        self.cache_checkpoints.clear();
+        for (k, v) in self.cache_map.drain() {
+            if let Some(access) = self.access.as_ref() {
+                access.borrow_mut().writes.insert(k.clone(), v.clone());
+            }
+            self.stash_map.insert(k, v);
         }
     }
 }
@@ -37,6 +142,7 @@ impl<DB: TrieDB> GeneralServiceState<DB> {
 impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
     fn get<Key: FixedCodec, Ret: FixedCodec>(&self, key: &Key) -> ProtocolResult<Option<Ret>> {
         let encoded_key = key.encode_fixed()?;
+        self.track_read(&encoded_key);
 
         if let Some(value_bytes) = self.cache_map.get(&encoded_key) {
             let inst = <_>::decode_fixed(value_bytes.clone())?;
@@ -57,6 +163,7 @@ impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
 
     fn contains<Key: FixedCodec>(&self, key: &Key) -> ProtocolResult<bool> {
         let encoded_key = key.encode_fixed()?;
+        self.track_read(&encoded_key);
 
         if self.cache_map.contains_key(&encoded_key) {
             return Ok(true);
@@ -104,15 +211,13 @@ impl<DB: TrieDB> ServiceState for GeneralServiceState<DB> {
     // Roll back all data in the cache
     fn revert_cache(&mut self) -> ProtocolResult<()> {
         self.cache_map.clear();
+        self.cache_checkpoints.clear();
         Ok(())
     }
 
     // Move data from cache to stash
     fn stash(&mut self) -> ProtocolResult<()> {
-        for (k, v) in self.cache_map.drain() {
-            self.stash_map.insert(k, v);
-        }
-
+        self.stash_and_track();
         Ok(())
     }
 
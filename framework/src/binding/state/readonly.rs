@@ -0,0 +1,89 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use cita_trie::DB as TrieDB;
+use derive_more::Display;
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::ServiceState;
+use protocol::types::{Address, MerkleRoot};
+use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
+
+use crate::binding::state::GeneralServiceState;
+
+// A view of a service state for `#[read]` methods, every write is rejected.
+pub struct ReadonlyServiceState<DB: TrieDB> {
+    state: Rc<RefCell<GeneralServiceState<DB>>>,
+}
+
+impl<DB: TrieDB> ReadonlyServiceState<DB> {
+    pub fn new(state: Rc<RefCell<GeneralServiceState<DB>>>) -> Self {
+        Self { state }
+    }
+}
+
+impl<DB: TrieDB> ServiceState for ReadonlyServiceState<DB> {
+    fn get<Key: FixedCodec, Ret: FixedCodec>(&self, key: &Key) -> ProtocolResult<Option<Ret>> {
+        self.state.borrow().get(key)
+    }
+
+    fn contains<Key: FixedCodec>(&self, key: &Key) -> ProtocolResult<bool> {
+        self.state.borrow().contains(key)
+    }
+
+    fn insert<Key: FixedCodec, Value: FixedCodec>(
+        &mut self,
+        _key: Key,
+        _value: Value,
+    ) -> ProtocolResult<()> {
+        Err(ReadonlyStateError::Write.into())
+    }
+
+    fn get_account_value<Key: FixedCodec, Ret: FixedCodec>(
+        &self,
+        address: &Address,
+        key: &Key,
+    ) -> ProtocolResult<Option<Ret>> {
+        self.state.borrow().get_account_value(address, key)
+    }
+
+    fn set_account_value<Key: FixedCodec, Val: FixedCodec>(
+        &mut self,
+        _address: &Address,
+        _key: Key,
+        _val: Val,
+    ) -> ProtocolResult<()> {
+        Err(ReadonlyStateError::Write.into())
+    }
+
+    // Nothing to roll back
+    fn revert_cache(&mut self) -> ProtocolResult<()> {
+        Ok(())
+    }
+
+    fn stash(&mut self) -> ProtocolResult<()> {
+        Err(ReadonlyStateError::Write.into())
+    }
+
+    fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
+        Err(ReadonlyStateError::Write.into())
+    }
+
+    fn is_readonly(&self) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Display)]
+pub enum ReadonlyStateError {
+    #[display(fmt = "write to a read-only service state")]
+    Write,
+}
+
+impl std::error::Error for ReadonlyStateError {}
+
+impl From<ReadonlyStateError> for ProtocolError {
+    fn from(err: ReadonlyStateError) -> ProtocolError {
+        ProtocolError::new(ProtocolErrorKind::Binding, Box::new(err))
+    }
+}
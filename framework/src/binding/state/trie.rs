@@ -35,6 +35,10 @@ impl<DB: TrieDB> MPTTrie<DB> {
         Ok(Self { root, trie })
     }
 
+    pub fn root(&self) -> MerkleRoot {
+        self.root.clone()
+    }
+
     pub fn get(&self, key: &Bytes) -> ProtocolResult<Option<Bytes>> {
         Ok(self
             .trie
@@ -5,6 +5,7 @@ use bytes::Bytes;
 use derive_more::{Display, From};
 use rocksdb::{Options, WriteBatch, DB};
 
+use core_storage::adapter::rocks::RocksColumnOptions;
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 pub struct RocksTrieDB {
@@ -14,10 +15,20 @@ pub struct RocksTrieDB {
 
 impl RocksTrieDB {
     pub fn new<P: AsRef<Path>>(path: P, light: bool, max_open_files: i32) -> ProtocolResult<Self> {
+        Self::with_options(path, light, max_open_files, RocksColumnOptions::default())
+    }
+
+    pub fn with_options<P: AsRef<Path>>(
+        path: P,
+        light: bool,
+        max_open_files: i32,
+        options: RocksColumnOptions,
+    ) -> ProtocolResult<Self> {
         let mut opts = Options::default();
         opts.create_if_missing(true);
         opts.create_missing_column_families(true);
         opts.set_max_open_files(max_open_files);
+        options.apply(&mut opts);
 
         let db = DB::open(&opts, path).map_err(RocksTrieDBError::from)?;
 
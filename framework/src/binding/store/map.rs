@@ -7,15 +7,19 @@ use bytes::Bytes;
 
 use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{ServiceState, StoreMap};
-use protocol::types::Hash;
+use protocol::types::{Hash, ServiceContext};
 use protocol::{ProtocolError, ProtocolResult};
 
 use crate::binding::store::{FixedKeys, StoreError};
 
+// The cycles refunded to the tx for each entry it removes.
+pub const REMOVE_REFUND_CYCLES: u64 = 5_000;
+
 pub struct DefaultStoreMap<S: ServiceState, K: FixedCodec + PartialEq, V: FixedCodec> {
     state:    Rc<RefCell<S>>,
     var_name: Hash,
     keys:     FixedKeys<K>,
+    context:  Option<ServiceContext>,
     phantom:  PhantomData<V>,
 }
 
@@ -40,10 +44,16 @@ impl<S: 'static + ServiceState, K: 'static + FixedCodec + PartialEq, V: 'static
             state,
             var_name,
             keys,
+            context: None,
             phantom: PhantomData,
         }
     }
 
+    pub fn with_context(mut self, context: Option<ServiceContext>) -> Self {
+        self.context = context;
+        self
+    }
+
     fn get_map_key(&self, key: &K) -> ProtocolResult<Hash> {
         let mut name_bytes = self.var_name.as_bytes().to_vec();
         name_bytes.extend_from_slice(key.encode_fixed()?.as_ref());
@@ -96,6 +106,9 @@ impl<S: 'static + ServiceState, K: 'static + FixedCodec + PartialEq, V: 'static
                 .borrow_mut()
                 .insert(self.get_map_key(key)?, Bytes::new())?;
 
+            if let Some(context) = self.context.as_ref() {
+                context.refund_cycles(REMOVE_REFUND_CYCLES);
+            }
             Ok(Some(value))
         } else {
             Ok(None)
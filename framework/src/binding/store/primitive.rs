@@ -25,6 +25,7 @@ impl<S: ServiceState> DefaultStoreBool<S> {
 
         match b {
             Some(v) => Ok(v),
+            None if self.state.borrow().is_readonly() => Ok(false),
             None => {
                 self.state.borrow_mut().insert(self.key.clone(), false)?;
                 Ok(false)
@@ -72,6 +73,7 @@ impl<S: ServiceState> DefaultStoreUint64<S> {
 
         match u {
             Some(v) => v,
+            None if self.state.borrow().is_readonly() => 0,
             None => {
                 self.state
                     .borrow_mut()
@@ -242,6 +244,7 @@ impl<S: ServiceState> DefaultStoreString<S> {
 
         match s {
             Some(v) => Ok(v),
+            None if self.state.borrow().is_readonly() => Ok("".to_string()),
             None => {
                 self.state
                     .borrow_mut()
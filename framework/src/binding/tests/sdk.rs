@@ -127,6 +127,17 @@ impl Storage for MockStorage {
         Ok(())
     }
 
+    async fn commit_block_bundle(
+        &self,
+        _ctx: Context,
+        _block: Block,
+        _proof: Proof,
+        _signed_txs: Vec<SignedTransaction>,
+        _receipts: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        Ok(())
+    }
+
     async fn get_transaction_by_hash(
         &self,
         _ctx: Context,
@@ -222,6 +233,8 @@ pub fn mock_signed_tx() -> SignedTransaction {
         tx_hash:   mock_hash(),
         pubkey:    Default::default(),
         signature: Default::default(),
+        scheme:    Default::default(),
+        kind:      Default::default(),
     }
 }
 
@@ -231,12 +244,14 @@ pub fn mock_signed_tx() -> SignedTransaction {
 
 pub fn mock_receipt() -> Receipt {
     Receipt {
-        state_root:  mock_merkle_root(),
-        height:      13,
-        tx_hash:     mock_hash(),
-        cycles_used: 100,
-        events:      vec![mock_event()],
-        response:    mock_receipt_response(),
+        state_root:       mock_merkle_root(),
+        height:           13,
+        tx_hash:          mock_hash(),
+        cycles_used:      100,
+        cycles_refunded:  0,
+        cycles_breakdown: vec![],
+        events:           vec![mock_event()],
+        response:         mock_receipt_response(),
     }
 }
 
@@ -255,6 +270,8 @@ pub fn mock_receipt_response() -> ReceiptResponse {
 pub fn mock_event() -> Event {
     Event {
         service: "mock-event".to_owned(),
+        name:    "mock-name".to_owned(),
+        topics:  vec!["mock-topic".to_owned()],
         data:    "mock-data".to_owned(),
     }
 }
@@ -295,6 +312,7 @@ pub fn mock_block_header() -> BlockHeader {
         receipt_root:      vec![mock_hash(), mock_hash()],
         cycles_used:       vec![999_999],
         proposer:          mock_address(),
+        seed_signature:    Default::default(),
         proof:             mock_proof(),
         validator_version: 1,
         validators:        vec![
@@ -1,12 +1,15 @@
+use std::cell::RefCell;
+use std::rc::Rc;
 use std::sync::Arc;
 
 use bytes::Bytes;
 use cita_trie::MemoryDB;
 
+use protocol::fixed_codec::FixedCodec;
 use protocol::traits::ServiceState;
 use protocol::types::{Address, Hash, MerkleRoot};
 
-use crate::binding::state::{GeneralServiceState, MPTTrie};
+use crate::binding::state::{GeneralServiceState, MPTTrie, ReadonlyServiceState};
 
 #[test]
 fn test_state_insert() {
@@ -56,6 +59,72 @@ fn test_state_account() {
     assert_eq!(val, value);
 }
 
+#[test]
+fn test_state_access_set() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let mut state = new_state(Arc::clone(&memdb), None);
+
+    let key = Hash::digest(Bytes::from("key".to_owned()));
+    let value = Hash::digest(Bytes::from("value".to_owned()));
+    let other_key = Hash::digest(Bytes::from("other_key".to_owned()));
+    let encoded_key = key.encode_fixed().unwrap();
+
+    state.checkpoint();
+    state.start_tracking();
+    state.insert(key.clone(), value.clone()).unwrap();
+    state.revert_cache().unwrap();
+    let _: Option<Hash> = state.get(&other_key).unwrap();
+    state.insert(key.clone(), value.clone()).unwrap();
+    state.stash().unwrap();
+
+    let access_set = state.take_access_set();
+    assert_eq!(access_set.reads.len(), 1);
+    assert!(access_set
+        .reads
+        .contains(&other_key.encode_fixed().unwrap()));
+    assert_eq!(access_set.writes.len(), 1);
+    assert!(access_set.writes.contains_key(&encoded_key));
+
+    state.restore_checkpoint();
+    assert!(!state.contains(&key).unwrap());
+
+    // The applied writes are cached, they can still be reverted
+    state.apply_writes(access_set.writes.clone());
+    let val: Hash = state.get(&key).unwrap().unwrap();
+    assert_eq!(val, value);
+    state.revert_cache().unwrap();
+    assert!(!state.contains(&key).unwrap());
+
+    state.apply_writes(access_set.writes);
+    state.stash().unwrap();
+    let mut other = new_state(Arc::clone(&memdb), None);
+    other.set_uncommitted(state.uncommitted());
+    let val: Hash = other.get(&key).unwrap().unwrap();
+    assert_eq!(val, value);
+}
+
+#[test]
+fn test_readonly_state() {
+    let memdb = Arc::new(MemoryDB::new(false));
+    let state = Rc::new(RefCell::new(new_state(Arc::clone(&memdb), None)));
+
+    let key = Hash::digest(Bytes::from("key".to_owned()));
+    let value = Hash::digest(Bytes::from("value".to_owned()));
+    state
+        .borrow_mut()
+        .insert(key.clone(), value.clone())
+        .unwrap();
+
+    let mut readonly = ReadonlyServiceState::new(Rc::clone(&state));
+    let val: Hash = readonly.get(&key).unwrap().unwrap();
+    assert_eq!(val, value);
+    assert!(readonly.is_readonly());
+
+    let other_key = Hash::digest(Bytes::from("other_key".to_owned()));
+    assert!(readonly.insert(other_key.clone(), value.clone()).is_err());
+    assert!(!state.borrow().contains(&other_key).unwrap());
+}
+
 pub fn new_state(memdb: Arc<MemoryDB>, root: Option<MerkleRoot>) -> GeneralServiceState<MemoryDB> {
     let trie = match root {
         Some(root) => MPTTrie::from(root, memdb).unwrap(),
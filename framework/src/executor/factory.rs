@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use protocol::traits::{Executor, ExecutorFactory, ServiceMapping, Storage};
+use protocol::traits::{Executor, ExecutorFactory, ExecutorOptions, ServiceMapping, Storage};
 use protocol::types::MerkleRoot;
 use protocol::ProtocolResult;
 
@@ -16,8 +16,9 @@ impl<DB: 'static + cita_trie::DB, S: 'static + Storage, Mapping: 'static + Servi
         db: Arc<DB>,
         storage: Arc<S>,
         mapping: Arc<Mapping>,
+        options: ExecutorOptions,
     ) -> ProtocolResult<Box<dyn Executor>> {
-        let executor = ServiceExecutor::with_root(root, db, storage, mapping)?;
+        let executor = ServiceExecutor::with_options(root, db, storage, mapping, options)?;
         Ok(Box::new(executor))
     }
 }
@@ -1,10 +1,19 @@
 mod factory;
+mod nonce;
+mod parallel;
+mod schedule;
+mod state_reader;
 #[cfg(test)]
 mod tests;
+mod verifier;
 
 pub use factory::ServiceExecutorFactory;
+pub use nonce::DefaultNonceQuerier;
+pub use schedule::{scheduled_call_id, scheduler_address};
+pub use verifier::DefaultSignatureVerifier;
 
 use std::cell::RefCell;
+use std::cmp;
 use std::collections::HashMap;
 use std::panic::{self, AssertUnwindSafe};
 use std::rc::Rc;
@@ -13,19 +22,79 @@ use std::sync::Arc;
 use cita_trie::DB as TrieDB;
 use derive_more::{Display, From};
 
-use bytes::BytesMut;
+use bytes::{Bytes, BytesMut};
 use protocol::traits::{
-    Dispatcher, Executor, ExecutorParams, ExecutorResp, NoopDispatcher, ServiceMapping,
-    ServiceResponse, ServiceState, Storage,
+    Dispatcher, Executor, ExecutorOptions, ExecutorParams, ExecutorResp, NoopDispatcher, Service,
+    ServiceMapping, ServiceRegistration, ServiceResponse, ServiceSDK, ServiceState, Storage,
+    FEE_HOOK_EXTRA,
 };
 use protocol::types::{
-    Address, Bloom, BloomInput, Hash, MerkleRoot, Receipt, ReceiptResponse, ServiceContext,
-    ServiceContextParams, ServiceParam, SignedTransaction, TransactionRequest,
+    Address, Bloom, BloomInput, Event, Hash, MerkleRoot, NonceMode, RawTransaction, Receipt,
+    ReceiptResponse, ServiceContext, ServiceContextParams, ServiceParam, SignedTransaction,
+    TransactionRequest,
 };
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::binding::sdk::{DefalutServiceSDK, DefaultChainQuerier};
-use crate::binding::state::{GeneralServiceState, MPTTrie};
+use crate::binding::state::{GeneralServiceState, MPTTrie, ReadonlyServiceState};
+
+// Same code as the one returned by the `cycles` macro.
+pub const OUT_OF_CYCLES_CODE: u64 = 3;
+pub const CHARGE_FEE_FAILED_CODE: u64 = 4;
+pub const SERVICE_PANICKED_CODE: u64 = 5;
+pub const INVALID_NONCE_CODE: u64 = 6;
+pub const CALL_DEPTH_EXCEEDED_CODE: u64 = 7;
+pub const WRITE_IN_READONLY_CODE: u64 = 8;
+pub const REGISTER_SERVICE_FAILED_CODE: u64 = 9;
+pub const BLOCK_CYCLES_EXCEEDED_CODE: u64 = 10;
+pub const SCHEDULE_CALL_FAILED_CODE: u64 = 11;
+pub const INVALID_SIGNATURE_CODE: u64 = 12;
+
+// The maximum call stack of cross-service calls, including the transaction's
+// own call.
+pub const MAX_CALL_DEPTH: u64 = 1024;
+
+// Each nested call takes native stack frames. Once less than the red zone is
+// left, the call runs on a new stack segment of the given size, so the deepest
+// call doesn't depend on the stack of the thread running the executor.
+const CALL_STACK_RED_ZONE: usize = 256 * 1024;
+const CALL_STACK_SEGMENT_SIZE: usize = 4 * 1024 * 1024;
+
+// A tx rejected for its nonce still takes block space, it's charged for these
+// cycles.
+pub const INVALID_NONCE_CYCLES: u64 = 21_000;
+
+// A tx rejected for its signature is charged for these cycles on top of the
+// verification, they count against the block but no fee is paid.
+pub const INVALID_SIGNATURE_CYCLES: u64 = 21_000;
+
+// A scheduled call can use at most these cycles, which are charged to the tx
+// that schedules it.
+pub const SCHEDULED_CALL_CYCLES: u64 = 100_000;
+
+// The max number of calls scheduled at a height.
+pub const MAX_SCHEDULED_CALLS: usize = 64;
+
+// The refund can't be more than the cycles used divided by it, so that a tx
+// always pays for a part of its work.
+pub const MAX_REFUND_QUOTIENT: u64 = 2;
+
+// The account nonces live in their own state, which is kept in the root state
+// under this name. The name can't collide with services, since it's not a
+// valid identifier.
+const ACCOUNT_NONCE_STATE: &str = "-account-nonce";
+// The version of a service is kept in the root state under this prefix plus
+// its name, once it's above 0.
+const SERVICE_VERSION_PREFIX: &str = "-version-";
+// The services registered at runtime live in their own state too, the
+// pending ones and the active ones in the order of activation.
+const SERVICE_REGISTRY_STATE: &str = "-service-registry";
+const PENDING_SERVICES_KEY: &str = "pending";
+const ACTIVE_SERVICES_KEY: &str = "active";
+// The calls scheduled by services live in their own state too, along with the
+// number of calls ever scheduled, which tells them apart.
+const SCHEDULED_CALLS_STATE: &str = "-scheduled-calls";
+const SCHEDULED_COUNT_KEY: &str = "count";
 
 enum HookType {
     Before,
@@ -38,20 +107,45 @@ enum ExecType {
     Write,
 }
 
+// The tx is either called, with its fee to be charged, or done already.
+enum TxCall {
+    Called(ServiceContext, ServiceResponse<String>),
+    Done(Receipt),
+}
+
 pub struct ServiceExecutor<S: Storage, DB: TrieDB, Mapping: ServiceMapping> {
     service_mapping: Arc<Mapping>,
+    trie_db:         Arc<DB>,
+    storage:         Arc<S>,
     querier:         Rc<DefaultChainQuerier<S>>,
     states:          Rc<HashMap<String, Rc<RefCell<GeneralServiceState<DB>>>>>,
     root_state:      Rc<RefCell<GeneralServiceState<DB>>>,
+    // Only in the account nonce mode
+    nonce_state:     Option<Rc<RefCell<GeneralServiceState<DB>>>>,
+    registry_state:  Rc<RefCell<GeneralServiceState<DB>>>,
+    schedule_state:  Rc<RefCell<GeneralServiceState<DB>>>,
+    // The services registered at runtime which are active
+    registered:      Rc<Vec<ServiceRegistration>>,
+    parallel:        bool,
+    // Set for the dispatcher of read-only calls
+    readonly:        bool,
 }
 
 impl<S: Storage, DB: TrieDB, Mapping: ServiceMapping> Clone for ServiceExecutor<S, DB, Mapping> {
     fn clone(&self) -> Self {
         Self {
             service_mapping: Arc::clone(&self.service_mapping),
+            trie_db:         Arc::clone(&self.trie_db),
+            storage:         Arc::clone(&self.storage),
             querier:         Rc::clone(&self.querier),
             states:          Rc::clone(&self.states),
             root_state:      Rc::clone(&self.root_state),
+            nonce_state:     self.nonce_state.as_ref().map(Rc::clone),
+            registry_state:  Rc::clone(&self.registry_state),
+            schedule_state:  Rc::clone(&self.schedule_state),
+            registered:      Rc::clone(&self.registered),
+            parallel:        self.parallel,
+            readonly:        self.readonly,
         }
     }
 }
@@ -83,7 +177,7 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
             let sdk =
                 DefalutServiceSDK::new(Rc::clone(state), Rc::clone(&querier), NoopDispatcher {});
 
-            let mut service = mapping.get_service(&params.name, sdk)?;
+            let mut service = mapping.get_service_at(&params.name, 0, sdk)?;
             panic::catch_unwind(AssertUnwindSafe(|| {
                 service.genesis_(params.payload.clone())
             }))
@@ -97,6 +191,12 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         for (name, state) in states.iter() {
             let root = state.borrow_mut().commit()?;
             root_state.insert(name.to_owned(), root)?;
+
+            // The genesis state is in the version at height 0 already
+            let version = mapping.service_version(name, 0);
+            if version > 0 {
+                root_state.insert(service_version_key(name), version)?;
+            }
         }
         root_state.stash()?;
         root_state.commit()
@@ -107,6 +207,22 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         trie_db: Arc<DB>,
         storage: Arc<S>,
         service_mapping: Arc<Mapping>,
+    ) -> ProtocolResult<Self> {
+        Self::with_options(
+            root,
+            trie_db,
+            storage,
+            service_mapping,
+            ExecutorOptions::default(),
+        )
+    }
+
+    pub fn with_options(
+        root: MerkleRoot,
+        trie_db: Arc<DB>,
+        storage: Arc<S>,
+        service_mapping: Arc<Mapping>,
+        options: ExecutorOptions,
     ) -> ProtocolResult<Self> {
         let trie = MPTTrie::from(root, Arc::clone(&trie_db))?;
         let root_state = GeneralServiceState::new(trie);
@@ -122,19 +238,101 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
             states.insert(name.to_owned(), Rc::new(RefCell::new(service_state)));
         }
 
+        let nonce_state = match service_mapping.nonce_mode() {
+            NonceMode::Account => {
+                let trie = match root_state.get(&ACCOUNT_NONCE_STATE.to_owned())? {
+                    Some(nonce_root) => MPTTrie::from(nonce_root, Arc::clone(&trie_db))?,
+                    None => MPTTrie::new(Arc::clone(&trie_db)),
+                };
+                Some(Rc::new(RefCell::new(GeneralServiceState::new(trie))))
+            }
+            NonceMode::Random => None,
+        };
+
+        let trie = match root_state.get(&SERVICE_REGISTRY_STATE.to_owned())? {
+            Some(registry_root) => MPTTrie::from(registry_root, Arc::clone(&trie_db))?,
+            None => MPTTrie::new(Arc::clone(&trie_db)),
+        };
+        let registry_state = GeneralServiceState::new(trie);
+        let registered = get_registrations(&registry_state, ACTIVE_SERVICES_KEY)?;
+        for registration in registered.iter() {
+            let trie = match root_state.get(&registration.name)? {
+                Some(service_root) => MPTTrie::from(service_root, Arc::clone(&trie_db))?,
+                None => MPTTrie::new(Arc::clone(&trie_db)),
+            };
+
+            let service_state = GeneralServiceState::new(trie);
+            states.insert(
+                registration.name.to_owned(),
+                Rc::new(RefCell::new(service_state)),
+            );
+        }
+
+        let trie = match root_state.get(&SCHEDULED_CALLS_STATE.to_owned())? {
+            Some(schedule_root) => MPTTrie::from(schedule_root, Arc::clone(&trie_db))?,
+            None => MPTTrie::new(Arc::clone(&trie_db)),
+        };
+        let schedule_state = GeneralServiceState::new(trie);
+
         Ok(Self {
             service_mapping,
-            querier: Rc::new(DefaultChainQuerier::new(storage)),
+            trie_db,
+            querier: Rc::new(DefaultChainQuerier::new(Arc::clone(&storage))),
+            storage,
             states: Rc::new(states),
             root_state: Rc::new(RefCell::new(root_state)),
+            nonce_state,
+            registry_state: Rc::new(RefCell::new(registry_state)),
+            schedule_state: Rc::new(RefCell::new(schedule_state)),
+            registered: Rc::new(registered),
+            parallel: options.parallel,
+            readonly: false,
         })
     }
 
+    // The next nonce of the address in the account nonce mode.
+    pub fn get_account_nonce(&self, address: &Address) -> ProtocolResult<u64> {
+        let nonce_state = self
+            .nonce_state
+            .as_ref()
+            .ok_or(ExecutorError::AccountNonceDisabled)?;
+        let nonce = nonce_state.borrow().get(address)?;
+
+        Ok(nonce.unwrap_or(0))
+    }
+
     fn commit(&mut self) -> ProtocolResult<MerkleRoot> {
         for (name, state) in self.states.iter() {
             let root = state.borrow_mut().commit()?;
             self.root_state.borrow_mut().insert(name.to_owned(), root)?;
         }
+        if let Some(nonce_state) = self.nonce_state.as_ref() {
+            let root = nonce_state.borrow_mut().commit()?;
+            self.root_state
+                .borrow_mut()
+                .insert(ACCOUNT_NONCE_STATE.to_owned(), root)?;
+        }
+        // Chains which never registered a service keep their state root
+        if self
+            .registry_state
+            .borrow()
+            .contains(&PENDING_SERVICES_KEY.to_owned())?
+        {
+            let root = self.registry_state.borrow_mut().commit()?;
+            self.root_state
+                .borrow_mut()
+                .insert(SERVICE_REGISTRY_STATE.to_owned(), root)?;
+        }
+        if self
+            .schedule_state
+            .borrow()
+            .contains(&SCHEDULED_COUNT_KEY.to_owned())?
+        {
+            let root = self.schedule_state.borrow_mut().commit()?;
+            self.root_state
+                .borrow_mut()
+                .insert(SCHEDULED_CALLS_STATE.to_owned(), root)?;
+        }
         self.root_state.borrow_mut().stash()?;
         self.root_state.borrow_mut().commit()
     }
@@ -143,22 +341,61 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         for state in self.states.values() {
             state.borrow_mut().stash()?;
         }
+        self.registry_state.borrow_mut().stash()?;
+        self.schedule_state.borrow_mut().stash()?;
 
         Ok(())
     }
 
-    fn revert_cache(&mut self) -> ProtocolResult<()> {
+    // The states a call can write, see `Dispatcher::checkpoint`.
+    fn cached_states(&self) -> impl Iterator<Item = &Rc<RefCell<GeneralServiceState<DB>>>> {
+        self.states
+            .values()
+            .chain(std::iter::once(&self.registry_state))
+            .chain(std::iter::once(&self.schedule_state))
+    }
+
+    fn revert_cache(&self) -> ProtocolResult<()> {
         for state in self.states.values() {
             state.borrow_mut().revert_cache()?;
         }
+        self.registry_state.borrow_mut().revert_cache()?;
+        self.schedule_state.borrow_mut().revert_cache()?;
 
         Ok(())
     }
 
+    // The static services followed by the registered ones.
+    fn list_service_name(&self) -> Vec<String> {
+        let mut names = self.service_mapping.list_service_name();
+        names.extend(self.registered.iter().map(|r| r.name.to_owned()));
+
+        names
+    }
+
+    // A registered service runs the code it was registered with.
+    fn service_code<'a>(&'a self, name: &'a str) -> &'a str {
+        self.registered
+            .iter()
+            .find(|r| r.name == name)
+            .map(|r| r.code.as_str())
+            .unwrap_or(name)
+    }
+
+    fn get_service<SDK: 'static + ServiceSDK>(
+        &self,
+        name: &str,
+        height: u64,
+        sdk: SDK,
+    ) -> ProtocolResult<Box<dyn Service>> {
+        self.service_mapping
+            .get_service_at(self.service_code(name), height, sdk)
+    }
+
     fn hook(&mut self, hook: HookType, exec_params: &ExecutorParams) -> ProtocolResult<()> {
-        for name in self.service_mapping.list_service_name().into_iter() {
+        for name in self.list_service_name().into_iter() {
             let sdk = self.get_sdk(&name)?;
-            let mut service = self.service_mapping.get_service(name.as_str(), sdk)?;
+            let mut service = self.get_service(name.as_str(), exec_params.height, sdk)?;
 
             let hook_ret = match hook {
                 HookType::Before => {
@@ -178,6 +415,162 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         Ok(())
     }
 
+    // Run the migrations of the services whose version is raised at this
+    // height, from the stored version to the new one, each step stashed on its
+    // own. Like a failed genesis in `activate_services`, a step that panics
+    // doesn't fail the block: it's logged and its writes are dropped, the
+    // remaining steps of the service are skipped and the service is at the new
+    // version anyway, so the migration isn't retried.
+    fn migrate(&mut self, params: &ExecutorParams) -> ProtocolResult<()> {
+        for name in self.list_service_name().into_iter() {
+            let version = self
+                .service_mapping
+                .service_version(self.service_code(&name), params.height);
+            let version_key = service_version_key(&name);
+            let stored: u64 = self.root_state.borrow().get(&version_key)?.unwrap_or(0);
+            if version <= stored {
+                continue;
+            }
+
+            for from in stored..version {
+                let sdk = self.get_sdk(&name)?;
+                let mut service = self.get_service(name.as_str(), params.height, sdk)?;
+
+                let result =
+                    panic::catch_unwind(AssertUnwindSafe(|| service.migrate_(params, from)));
+                if let Err(e) = result {
+                    log::error!(
+                        "migrate service {:?} from version {} failed: {:?}",
+                        name,
+                        from,
+                        e
+                    );
+                    self.revert_cache()?;
+                    break;
+                }
+                self.stash()?;
+            }
+            self.root_state.borrow_mut().insert(version_key, version)?;
+        }
+
+        Ok(())
+    }
+
+    // Activate the services registered for this height or before. A service
+    // whose genesis fails is dropped.
+    fn activate_services(&mut self, params: &ExecutorParams) -> ProtocolResult<()> {
+        let pending = get_registrations(&self.registry_state.borrow(), PENDING_SERVICES_KEY)?;
+        let (ready, pending): (Vec<_>, Vec<_>) =
+            pending.into_iter().partition(|r| r.height <= params.height);
+        if ready.is_empty() {
+            return Ok(());
+        }
+
+        let mut states = (*self.states).clone();
+        let mut registered = (*self.registered).clone();
+        for registration in ready.into_iter() {
+            let trie = MPTTrie::new(Arc::clone(&self.trie_db));
+            let state = Rc::new(RefCell::new(GeneralServiceState::new(trie)));
+            let sdk = DefalutServiceSDK::new(
+                Rc::clone(&state),
+                Rc::clone(&self.querier),
+                NoopDispatcher {},
+            );
+
+            let genesis = panic::catch_unwind(AssertUnwindSafe(|| {
+                let mut service =
+                    self.service_mapping
+                        .get_service_at(&registration.code, params.height, sdk)?;
+                service.genesis_(registration.payload.clone());
+                Ok::<(), ProtocolError>(())
+            }));
+            match genesis {
+                Ok(Ok(())) => state.borrow_mut().stash()?,
+                _ => {
+                    log::warn!("drop service {:?}, genesis failed", registration.name);
+                    continue;
+                }
+            }
+
+            // The genesis state is in the version at this height already
+            let version = self
+                .service_mapping
+                .service_version(&registration.code, params.height);
+            if version > 0 {
+                self.root_state
+                    .borrow_mut()
+                    .insert(service_version_key(&registration.name), version)?;
+            }
+
+            states.insert(registration.name.to_owned(), state);
+            registered.push(registration);
+        }
+
+        {
+            let mut registry_state = self.registry_state.borrow_mut();
+            set_registrations(&mut registry_state, PENDING_SERVICES_KEY, &pending)?;
+            set_registrations(&mut registry_state, ACTIVE_SERVICES_KEY, &registered)?;
+            registry_state.stash()?;
+        }
+        self.states = Rc::new(states);
+        self.registered = Rc::new(registered);
+
+        Ok(())
+    }
+
+    // The registration is pending until its height, so that every service is
+    // active for whole blocks.
+    fn register_service(
+        &self,
+        context: &ServiceContext,
+        registration: ServiceRegistration,
+    ) -> ProtocolResult<()> {
+        let register_err = |msg: &str| -> ProtocolResult<()> {
+            Err(ExecutorError::RegisterService(msg.to_owned()).into())
+        };
+
+        match self.service_mapping.governance_service() {
+            Some(ref governance) if governance == context.get_service_name() => (),
+            _ => return register_err("not the governance service"),
+        }
+        if registration.height <= context.get_current_height() {
+            return register_err("height must be above the current one");
+        }
+        if registration.name.is_empty() || registration.name.starts_with('-') {
+            return register_err("invalid service name");
+        }
+
+        let mut pending = get_registrations(&self.registry_state.borrow(), PENDING_SERVICES_KEY)?;
+        if self.list_service_name().contains(&registration.name)
+            || pending.iter().any(|r| r.name == registration.name)
+        {
+            return register_err("service name exists");
+        }
+
+        // The code must be known to the mapping itself
+        let trie = MPTTrie::new(Arc::clone(&self.trie_db));
+        let sdk = DefalutServiceSDK::new(
+            Rc::new(RefCell::new(GeneralServiceState::new(trie))),
+            Rc::clone(&self.querier),
+            NoopDispatcher {},
+        );
+        let code = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.service_mapping
+                .get_service_at(&registration.code, registration.height, sdk)
+                .is_ok()
+        }));
+        if registration.code.starts_with('-') || !code.unwrap_or(false) {
+            return register_err("service code was not found");
+        }
+
+        pending.push(registration);
+        set_registrations(
+            &mut self.registry_state.borrow_mut(),
+            PENDING_SERVICES_KEY,
+            &pending,
+        )
+    }
+
     fn get_sdk(
         &self,
         service: &str,
@@ -197,6 +590,29 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         ))
     }
 
+    // The services called through it can't write, nor call writable methods.
+    fn get_readonly_sdk(
+        &self,
+        service: &str,
+    ) -> ProtocolResult<DefalutServiceSDK<ReadonlyServiceState<DB>, DefaultChainQuerier<S>, Self>>
+    {
+        let state = self
+            .states
+            .get(service)
+            .ok_or(ExecutorError::NotFoundService {
+                service: service.to_owned(),
+            })?;
+
+        let mut dispatcher = (*self).clone();
+        dispatcher.readonly = true;
+
+        Ok(DefalutServiceSDK::new(
+            Rc::new(RefCell::new(ReadonlyServiceState::new(Rc::clone(&state)))),
+            Rc::clone(&self.querier),
+            dispatcher,
+        ))
+    }
+
     fn get_context(
         &self,
         tx_hash: Option<Hash>,
@@ -206,6 +622,30 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         cycles_limit: u64,
         params: &ExecutorParams,
         request: &TransactionRequest,
+    ) -> ProtocolResult<ServiceContext> {
+        self.get_context_with_extra(
+            tx_hash,
+            nonce,
+            caller,
+            cycles_price,
+            cycles_limit,
+            params,
+            request,
+            None,
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn get_context_with_extra(
+        &self,
+        tx_hash: Option<Hash>,
+        nonce: Option<Hash>,
+        caller: &Address,
+        cycles_price: u64,
+        cycles_limit: u64,
+        params: &ExecutorParams,
+        request: &TransactionRequest,
+        extra: Option<Bytes>,
     ) -> ProtocolResult<ServiceContext> {
         let ctx_params = ServiceContextParams {
             tx_hash,
@@ -216,10 +656,11 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
             caller: caller.clone(),
             height: params.height,
             timestamp: params.timestamp,
+            random_seed: params.random_seed.clone(),
             service_name: request.service_name.to_owned(),
             service_method: request.method.to_owned(),
             service_payload: request.payload.to_owned(),
-            extra: None,
+            extra,
             events: Rc::new(RefCell::new(vec![])),
         };
 
@@ -236,79 +677,384 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
                 self.call(context.clone(), exec_type.clone())
             })),
             ExecType::Write => panic::catch_unwind(AssertUnwindSafe(|| {
-                self.call_with_tx_hooks(context.clone(), exec_type.clone())
+                self.call_with_tx_hook_before(context.clone(), exec_type.clone())
             })),
         };
+        self.check_call_result(&context, result)
+    }
+
+    fn check_call_result(
+        &self,
+        context: &ServiceContext,
+        result: std::thread::Result<ServiceResponse<String>>,
+    ) -> ProtocolResult<ServiceResponse<String>> {
         match result {
-            Ok(r) => {
-                self.stash()?;
-                Ok(r)
+            // Nested calls may swallow the out of cycles error, so it's checked
+            // on the context.
+            Ok(_) if context.is_out_of_cycles() => {
+                self.revert_cache()?;
+                context.revert_cycles_refund(0);
+                Ok(ServiceResponse::from_error(
+                    OUT_OF_CYCLES_CODE,
+                    "out of cycles".to_owned(),
+                ))
             }
+            Ok(r) => Ok(r),
             Err(e) => {
                 self.revert_cache()?;
+                context.revert_cycles_refund(0);
                 log::error!("inner chain error occurred when calling service: {:?}", e);
-                Err(ExecutorError::CallService(format!("{:?}", e)).into())
+                Ok(ServiceResponse::from_error(
+                    SERVICE_PANICKED_CODE,
+                    ExecutorError::CallService(format!("{:?}", e)).to_string(),
+                ))
             }
         }
     }
 
-    fn call_with_tx_hooks(
+    // A tx whose nonce isn't its sender's next one is not executed, but it's
+    // charged for `INVALID_NONCE_CYCLES`.
+    fn verify_account_nonce(
+        &self,
+        caller: &Address,
+        raw: &RawTransaction,
+    ) -> ProtocolResult<Option<ServiceResponse<String>>> {
+        if self.nonce_state.is_none() {
+            return Ok(None);
+        }
+
+        let expect = self.get_account_nonce(caller)?;
+        match raw.account_nonce() {
+            Some(nonce) if nonce == expect => Ok(None),
+            Some(nonce) => Ok(Some(ServiceResponse::from_error(
+                INVALID_NONCE_CODE,
+                format!("invalid nonce {}, expect {}", nonce, expect),
+            ))),
+            None => Ok(Some(ServiceResponse::from_error(
+                INVALID_NONCE_CODE,
+                "nonce is not an account nonce".to_owned(),
+            ))),
+        }
+    }
+
+    // The nonce is bumped even if the tx failed.
+    fn bump_account_nonce(&self, caller: &Address) -> ProtocolResult<()> {
+        if let Some(nonce_state) = self.nonce_state.as_ref() {
+            let nonce = self.get_account_nonce(caller)? + 1;
+
+            let mut nonce_state = nonce_state.borrow_mut();
+            nonce_state.insert(caller.clone(), nonce)?;
+            nonce_state.stash()?;
+        }
+
+        Ok(())
+    }
+
+    // The tx can't use more than the cycles left in the block. Once they run
+    // out, the txs left are kept with a failed receipt, but none of their
+    // effects.
+    fn exec_tx(
+        &mut self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+        remaining_cycles: u64,
+    ) -> ProtocolResult<Receipt> {
+        match self.call_tx(params, stx, remaining_cycles)? {
+            TxCall::Called(context, exec_resp) => self.finish_tx(params, stx, &context, exec_resp),
+            TxCall::Done(receipt) => Ok(receipt),
+        }
+    }
+
+    // Call the service of the tx, unless the tx is rejected before. The
+    // writes of the call are left in the cache.
+    fn call_tx(
+        &mut self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+        remaining_cycles: u64,
+    ) -> ProtocolResult<TxCall> {
+        let context = self.tx_context(params, stx, remaining_cycles)?;
+        let caller = context.get_caller();
+
+        if remaining_cycles == 0 {
+            let resp = ServiceResponse::from_error(
+                BLOCK_CYCLES_EXCEEDED_CODE,
+                "block cycles exceeded".to_owned(),
+            );
+            let receipt = self.receipt(&stx.tx_hash, &context, resp, vec![]);
+            return Ok(TxCall::Done(receipt));
+        }
+
+        // A tx whose signature isn't valid has no payer, no fee is charged.
+        // Its cycles still take block space, since an order tx isn't verified
+        // by the mempool.
+        if let Some(sig_resp) = self.verify_tx_signature(params, stx, &context)? {
+            context.sub_cycles(INVALID_SIGNATURE_CYCLES);
+            let receipt = self.receipt(&stx.tx_hash, &context, sig_resp, vec![]);
+            return Ok(TxCall::Done(receipt));
+        }
+
+        // The nonce isn't bumped, but the fee is charged
+        if let Some(nonce_resp) = self.verify_account_nonce(&caller, &stx.raw)? {
+            context.sub_cycles(INVALID_NONCE_CYCLES);
+            let (fee_resp, fee_events) = self.charge_fee(params, &context)?;
+            let fee_events = if fee_resp.is_error() {
+                self.revert_cache()?;
+                vec![]
+            } else {
+                self.stash()?;
+                fee_events
+            };
+            let receipt = self.receipt(&stx.tx_hash, &context, nonce_resp, fee_events);
+            return Ok(TxCall::Done(receipt));
+        }
+
+        let exec_resp = self.catch_call(context.clone(), ExecType::Write)?;
+        // Like the txs after it, the tx has no effect and isn't charged, since
+        // it's the block which ran out of cycles.
+        if context.is_out_of_cycles() && context.get_cycles_limit() < stx.raw.cycles_limit {
+            let resp = ServiceResponse::from_error(
+                BLOCK_CYCLES_EXCEEDED_CODE,
+                "block cycles exceeded".to_owned(),
+            );
+            let receipt = self.receipt(&stx.tx_hash, &context, resp, vec![]);
+            return Ok(TxCall::Done(receipt));
+        }
+
+        Ok(TxCall::Called(context, exec_resp))
+    }
+
+    // Charge the fee of the called tx, then run the tx hooks after and bump
+    // the caller's nonce.
+    fn finish_tx(
+        &mut self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+        context: &ServiceContext,
+        mut exec_resp: ServiceResponse<String>,
+    ) -> ProtocolResult<Receipt> {
+        // The fee is charged even if the tx failed, but a tx whose fee can't
+        // be charged is reverted.
+        let (fee_resp, fee_events) = self.charge_fee(params, context)?;
+        if fee_resp.is_error() {
+            self.revert_cache()?;
+            context.revert_cycles_refund(0);
+            exec_resp = fee_resp;
+        }
+        self.stash()?;
+        self.tx_hook_after(context)?;
+        self.bump_account_nonce(&context.get_caller())?;
+
+        Ok(self.receipt(&stx.tx_hash, context, exec_resp, fee_events))
+    }
+
+    fn tx_context(
+        &self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+        remaining_cycles: u64,
+    ) -> ProtocolResult<ServiceContext> {
+        self.get_context(
+            Some(stx.tx_hash.clone()),
+            Some(stx.raw.nonce.clone()),
+            &stx.sender()?,
+            stx.raw.cycles_price,
+            cmp::min(stx.raw.cycles_limit, remaining_cycles),
+            params,
+            &stx.raw.request,
+        )
+    }
+
+    fn receipt(
+        &self,
+        tx_hash: &Hash,
+        context: &ServiceContext,
+        response: ServiceResponse<String>,
+        fee_events: Vec<Event>,
+    ) -> Receipt {
+        let mut events = context.get_events();
+        events.extend(fee_events);
+        let refunded = cycles_refunded(context);
+
+        Receipt {
+            state_root: MerkleRoot::from_empty(),
+            height: context.get_current_height(),
+            tx_hash: tx_hash.clone(),
+            cycles_used: context.get_cycles_used() - refunded,
+            cycles_refunded: refunded,
+            cycles_breakdown: context.get_cycles_records(),
+            events,
+            response: ReceiptResponse {
+                service_name: context.get_service_name().to_owned(),
+                method: context.get_service_method().to_owned(),
+                response,
+            },
+        }
+    }
+
+    fn charge_fee(
+        &self,
+        params: &ExecutorParams,
+        context: &ServiceContext,
+    ) -> ProtocolResult<(ServiceResponse<String>, Vec<Event>)> {
+        let fee_hook = match self.service_mapping.fee_hook() {
+            Some(fee_hook) => fee_hook,
+            None => return Ok((ServiceResponse::from_succeed("".to_owned()), vec![])),
+        };
+
+        let fee = match (context.get_cycles_used() - cycles_refunded(context))
+            .checked_mul(context.get_cycles_price())
+        {
+            Some(0) => return Ok((ServiceResponse::from_succeed("".to_owned()), vec![])),
+            Some(fee) => fee,
+            None => {
+                let resp = ServiceResponse::from_error(
+                    CHARGE_FEE_FAILED_CODE,
+                    "charge fee failed: fee overflow".to_owned(),
+                );
+                return Ok((resp, vec![]));
+            }
+        };
+
+        let request = TransactionRequest {
+            service_name: fee_hook.service,
+            method:       fee_hook.method,
+            payload:      serde_json::json!({ "fee": fee }).to_string(),
+        };
+        // The hook has its own cycles, it can't run out of the tx's ones. Its
+        // extra tells it apart from the calls of services.
+        let fee_context = self.get_context_with_extra(
+            context.get_tx_hash(),
+            context.get_nonce(),
+            &context.get_caller(),
+            context.get_cycles_price(),
+            std::u64::MAX,
+            params,
+            &request,
+            Some(Bytes::from(FEE_HOOK_EXTRA)),
+        )?;
+
+        let resp = panic::catch_unwind(AssertUnwindSafe(|| {
+            self.call(fee_context.clone(), ExecType::Write)
+        }))
+        .unwrap_or_else(|e| {
+            ServiceResponse::from_error(CHARGE_FEE_FAILED_CODE, format!("{:?}", e))
+        });
+
+        if resp.is_error() {
+            let resp = ServiceResponse::from_error(
+                CHARGE_FEE_FAILED_CODE,
+                format!("charge fee failed: {}", resp.error_message),
+            );
+            Ok((resp, vec![]))
+        } else {
+            Ok((resp, fee_context.get_events()))
+        }
+    }
+
+    fn call_with_tx_hook_before(
         &self,
         context: ServiceContext,
         exec_type: ExecType,
     ) -> ServiceResponse<String> {
-        let mut tx_hook_services = vec![];
-        for name in self.service_mapping.list_service_name().into_iter() {
+        for name in self.list_service_name().into_iter() {
             let sdk = self
                 .get_sdk(&name)
                 .unwrap_or_else(|e| panic!("get target service sdk failed: {}", e));
-            let tx_hook_service = self
-                .service_mapping
-                .get_service(name.as_str(), sdk)
+            let mut tx_hook_service = self
+                .get_service(name.as_str(), context.get_current_height(), sdk)
                 .unwrap_or_else(|e| panic!("get target service sdk failed: {}", e));
-            tx_hook_services.push(tx_hook_service);
-        }
-        // TODO: If tx_hook_before_ failed, we should not exec the tx.
-        // Need a mechanism for this.
-        for tx_hook_service in tx_hook_services.iter_mut() {
-            tx_hook_service.tx_hook_before_(context.clone());
+
+            let hook_res = tx_hook_service.tx_hook_before_(context.clone());
+            // The tx is rejected, drop everything the hooks have done.
+            if hook_res.is_error() {
+                self.revert_cache()
+                    .unwrap_or_else(|e| panic!("revert cache failed: {}", e));
+                return hook_res;
+            }
         }
-        let original_res = self.call(context.clone(), exec_type);
-        // TODO: If the tx fails, status tx_hook_after_ changes will also be reverted.
-        // It may not be what the developer want.
-        // Need a new mechanism for this.
-        for tx_hook_service in tx_hook_services.iter_mut() {
-            tx_hook_service.tx_hook_after_(context.clone());
+
+        self.call(context, exec_type)
+    }
+
+    // The tx hook after runs once the tx's changes are stashed, each service in
+    // its own layer, so that its changes are kept even if the tx failed.
+    fn tx_hook_after(&mut self, context: &ServiceContext) -> ProtocolResult<()> {
+        for name in self.list_service_name().into_iter() {
+            let sdk = self.get_sdk(&name)?;
+            let mut service = self.get_service(name.as_str(), context.get_current_height(), sdk)?;
+
+            let hook_ret =
+                panic::catch_unwind(AssertUnwindSafe(|| service.tx_hook_after_(context.clone())));
+
+            if hook_ret.is_err() {
+                self.revert_cache()?;
+            } else {
+                self.stash()?;
+            }
         }
-        original_res
+        Ok(())
     }
 
     fn call(&self, context: ServiceContext, exec_type: ExecType) -> ServiceResponse<String> {
-        let sdk = self
-            .get_sdk(context.get_service_name())
-            .unwrap_or_else(|e| panic!("get target service sdk failed: {}", e));
+        let mut service = match exec_type {
+            ExecType::Read => self
+                .get_readonly_sdk(context.get_service_name())
+                .and_then(|sdk| {
+                    self.get_service(
+                        context.get_service_name(),
+                        context.get_current_height(),
+                        sdk,
+                    )
+                }),
+            ExecType::Write => self.get_sdk(context.get_service_name()).and_then(|sdk| {
+                self.get_service(
+                    context.get_service_name(),
+                    context.get_current_height(),
+                    sdk.with_context(context.clone()),
+                )
+            }),
+        }
+        .unwrap_or_else(|e| panic!("get target service failed: {}", e));
 
-        let mut service = self
-            .service_mapping
-            .get_service(context.get_service_name(), sdk)
-            .unwrap_or_else(|e| panic!("get target service failed: {}", e));
+        let index = context.start_cycles_record();
+        let resp = match exec_type {
+            ExecType::Read => service.read_(context.clone()),
+            ExecType::Write => service.write_(context.clone()),
+        };
+        context.finish_cycles_record(index);
 
-        match exec_type {
-            ExecType::Read => service.read_(context),
-            ExecType::Write => service.write_(context),
-        }
+        resp
+    }
+
+    // A call made by another service, on a new stack segment if the current
+    // one is running out.
+    fn nested_call(&self, context: ServiceContext, exec_type: ExecType) -> ServiceResponse<String> {
+        stacker::maybe_grow(CALL_STACK_RED_ZONE, CALL_STACK_SEGMENT_SIZE, || {
+            self.call(context, exec_type)
+        })
     }
 
-    fn logs_bloom(&self, receipts: &[Receipt]) -> Bloom {
+    // Besides the whole event, its type and each of its topics are added on
+    // their own, so that the events can be filtered by them. A topic is added
+    // with the type and its position.
+    fn logs_bloom<'a>(&self, receipts: impl Iterator<Item = &'a Receipt>) -> Bloom {
         let mut bloom = Bloom::default();
         for receipt in receipts {
             for event in receipt.events.iter() {
-                let bytes =
-                    BytesMut::from((event.service.clone() + &event.data).as_bytes()).freeze();
-                let hash = Hash::digest(bytes).as_bytes();
+                let mut items = vec![event.service.clone() + &event.data];
+                if !event.name.is_empty() {
+                    items.push(event.service.clone() + &event.name);
+                }
+                items.extend((0..event.topics.len()).filter_map(|i| event.topic_bloom_item(i)));
+
+                for item in items.into_iter() {
+                    let bytes = BytesMut::from(item.as_bytes()).freeze();
+                    let hash = Hash::digest(bytes).as_bytes();
 
-                let input = BloomInput::Raw(hash.as_ref());
-                bloom.accrue(input)
+                    let input = BloomInput::Raw(hash.as_ref());
+                    bloom.accrue(input)
+                }
             }
         }
 
@@ -324,52 +1070,41 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
         params: &ExecutorParams,
         txs: &[SignedTransaction],
     ) -> ProtocolResult<ExecutorResp> {
+        self.activate_services(params)?;
+        self.migrate(params)?;
         self.hook(HookType::Before, params)?;
 
-        let mut receipts = txs
-            .iter()
-            .map(|stx| {
-                let caller = Address::from_pubkey_bytes(stx.pubkey.clone())?;
-                let context = self.get_context(
-                    Some(stx.tx_hash.clone()),
-                    Some(stx.raw.nonce.clone()),
-                    &caller,
-                    stx.raw.cycles_price,
-                    stx.raw.cycles_limit,
-                    params,
-                    &stx.raw.request,
-                )?;
-
-                let exec_resp = self.catch_call(context.clone(), ExecType::Write)?;
-
-                Ok(Receipt {
-                    state_root:  MerkleRoot::from_empty(),
-                    height:      context.get_current_height(),
-                    tx_hash:     stx.tx_hash.clone(),
-                    cycles_used: context.get_cycles_used(),
-                    events:      context.get_events(),
-                    response:    ReceiptResponse {
-                        service_name: context.get_service_name().to_owned(),
-                        method:       context.get_service_method().to_owned(),
-                        response:     exec_resp,
-                    },
-                })
-            })
-            .collect::<Result<Vec<Receipt>, ProtocolError>>()?;
+        let mut scheduled_receipts = self.exec_scheduled_calls(params)?;
+        let scheduled_cycles_used = scheduled_receipts.iter().map(|r| r.cycles_used).sum();
+
+        let mut receipts = if self.parallel && txs.len() > 1 {
+            self.exec_parallel(params, txs, scheduled_cycles_used)?
+        } else {
+            let mut receipts = Vec::with_capacity(txs.len());
+            let mut cycles_used: u64 = scheduled_cycles_used;
+            for stx in txs.iter() {
+                let receipt =
+                    self.exec_tx(params, stx, params.cycles_limit.saturating_sub(cycles_used))?;
+                cycles_used += receipt.cycles_used;
+                receipts.push(receipt);
+            }
+            receipts
+        };
 
         self.hook(HookType::After, params)?;
 
         let state_root = self.commit()?;
         let mut all_cycles_used = 0;
 
-        for receipt in receipts.iter_mut() {
+        for receipt in scheduled_receipts.iter_mut().chain(receipts.iter_mut()) {
             receipt.state_root = state_root.clone();
             all_cycles_used += receipt.cycles_used;
         }
-        let logs_bloom = self.logs_bloom(&receipts);
+        let logs_bloom = self.logs_bloom(scheduled_receipts.iter().chain(receipts.iter()));
 
         Ok(ExecutorResp {
             receipts,
+            scheduled_receipts,
             all_cycles_used,
             state_root,
             logs_bloom,
@@ -401,14 +1136,136 @@ impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMappi
     for ServiceExecutor<S, DB, Mapping>
 {
     fn read(&self, context: ServiceContext) -> ServiceResponse<String> {
-        self.call(context, ExecType::Read)
+        if let Some(resp) = check_call_depth(&context) {
+            return resp;
+        }
+
+        self.nested_call(context, ExecType::Read)
     }
 
+    // A nested write which returns an error is reverted, including its events.
     fn write(&self, context: ServiceContext) -> ServiceResponse<String> {
-        self.call(context, ExecType::Write)
+        if let Some(resp) = check_call_depth(&context) {
+            return resp;
+        }
+        if self.readonly {
+            return ServiceResponse::from_error(
+                WRITE_IN_READONLY_CODE,
+                "write in a read-only call".to_owned(),
+            );
+        }
+
+        let events_len = context.get_events_len();
+        let refund = context.get_cycles_refund();
+        self.checkpoint();
+
+        let resp = self.nested_call(context.clone(), ExecType::Write);
+
+        if resp.is_error() {
+            self.revert_to_checkpoint();
+            context.revert_events(events_len);
+            context.revert_cycles_refund(refund);
+        } else {
+            self.release_checkpoint();
+        }
+
+        resp
+    }
+
+    fn checkpoint(&self) {
+        for state in self.cached_states() {
+            state.borrow_mut().cache_checkpoint();
+        }
+    }
+
+    fn revert_to_checkpoint(&self) {
+        for state in self.cached_states() {
+            state.borrow_mut().revert_to_cache_checkpoint();
+        }
+    }
+
+    fn release_checkpoint(&self) {
+        for state in self.cached_states() {
+            state.borrow_mut().release_cache_checkpoint();
+        }
+    }
+
+    fn register(
+        &self,
+        context: ServiceContext,
+        registration: ServiceRegistration,
+    ) -> ServiceResponse<String> {
+        if self.readonly {
+            return ServiceResponse::from_error(
+                WRITE_IN_READONLY_CODE,
+                "write in a read-only call".to_owned(),
+            );
+        }
+
+        match self.register_service(&context, registration) {
+            Ok(()) => ServiceResponse::from_succeed("".to_owned()),
+            Err(e) => ServiceResponse::from_error(REGISTER_SERVICE_FAILED_CODE, e.to_string()),
+        }
+    }
+
+    fn schedule(&self, context: ServiceContext, height: u64) -> ServiceResponse<String> {
+        if self.readonly {
+            return ServiceResponse::from_error(
+                WRITE_IN_READONLY_CODE,
+                "write in a read-only call".to_owned(),
+            );
+        }
+
+        match self.schedule_call(&context, height) {
+            Ok(id) => ServiceResponse::from_succeed(id.as_hex()),
+            Err(e) => ServiceResponse::from_error(SCHEDULE_CALL_FAILED_CODE, e.to_string()),
+        }
     }
 }
 
+fn get_registrations<DB: TrieDB>(
+    state: &GeneralServiceState<DB>,
+    key: &str,
+) -> ProtocolResult<Vec<ServiceRegistration>> {
+    match state.get::<String, String>(&key.to_owned())? {
+        Some(registrations) => {
+            Ok(serde_json::from_str(&registrations).map_err(ExecutorError::from)?)
+        }
+        None => Ok(vec![]),
+    }
+}
+
+fn set_registrations<DB: TrieDB>(
+    state: &mut GeneralServiceState<DB>,
+    key: &str,
+    registrations: &[ServiceRegistration],
+) -> ProtocolResult<()> {
+    let registrations = serde_json::to_string(registrations).map_err(ExecutorError::from)?;
+    state.insert(key.to_owned(), registrations)
+}
+
+fn service_version_key(name: &str) -> String {
+    SERVICE_VERSION_PREFIX.to_owned() + name
+}
+
+fn cycles_refunded(context: &ServiceContext) -> u64 {
+    cmp::min(
+        context.get_cycles_refund(),
+        context.get_cycles_used() / MAX_REFUND_QUOTIENT,
+    )
+}
+
+fn check_call_depth(context: &ServiceContext) -> Option<ServiceResponse<String>> {
+    if context.get_call_depth() < MAX_CALL_DEPTH {
+        return None;
+    }
+
+    Some(ServiceResponse::from_error(
+        CALL_DEPTH_EXCEEDED_CODE,
+        format!("call depth exceeds {}", MAX_CALL_DEPTH),
+    ))
+}
+
 #[derive(Debug, Display, From)]
 pub enum ExecutorError {
     #[display(fmt = "service {:?} was not found", service)]
@@ -424,6 +1281,17 @@ pub enum ExecutorError {
     QueryService(String),
     #[display(fmt = "Call service failed: {:?}", _0)]
     CallService(String),
+
+    #[display(fmt = "Register service failed: {}", _0)]
+    RegisterService(String),
+    #[display(fmt = "Schedule call failed: {}", _0)]
+    ScheduleCall(String),
+
+    #[display(fmt = "Account nonce mode is disabled")]
+    AccountNonceDisabled,
+
+    #[display(fmt = "State reader stopped")]
+    StateReaderStopped,
 }
 
 impl std::error::Error for ExecutorError {}
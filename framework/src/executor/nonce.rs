@@ -0,0 +1,47 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cita_trie::DB as TrieDB;
+
+use protocol::traits::{Context, NonceQuerier, ServiceMapping, Storage};
+use protocol::types::Address;
+use protocol::ProtocolResult;
+
+use crate::executor::state_reader::StateReader;
+
+pub struct DefaultNonceQuerier<S, DB, Mapping> {
+    storage: Arc<S>,
+    reader:  StateReader<S, DB, Mapping>,
+}
+
+impl<S, DB, Mapping> DefaultNonceQuerier<S, DB, Mapping>
+where
+    S: 'static + Storage,
+    DB: 'static + TrieDB,
+    Mapping: 'static + ServiceMapping,
+{
+    pub fn new(storage: Arc<S>, trie_db: Arc<DB>, service_mapping: Arc<Mapping>) -> Self {
+        Self {
+            reader: StateReader::new(Arc::clone(&storage), trie_db, service_mapping),
+            storage,
+        }
+    }
+}
+
+#[async_trait]
+impl<S, DB, Mapping> NonceQuerier for DefaultNonceQuerier<S, DB, Mapping>
+where
+    S: 'static + Storage,
+    DB: 'static + TrieDB,
+    Mapping: 'static + ServiceMapping,
+{
+    async fn get_account_nonce(&self, ctx: Context, address: Address) -> ProtocolResult<u64> {
+        let block = self.storage.get_latest_block(ctx).await?;
+
+        self.reader
+            .read(block.header.state_root, move |executor| {
+                executor.get_account_nonce(&address)
+            })
+            .await
+    }
+}
@@ -0,0 +1,262 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use cita_trie::DB as TrieDB;
+use rayon::prelude::*;
+
+use protocol::traits::{
+    ExecutorParams, ServiceMapping, ServiceRegistration, ServiceResponse, Storage,
+};
+use protocol::types::{MerkleRoot, Receipt, ServiceContextCounters, SignedTransaction};
+use protocol::ProtocolResult;
+
+use crate::binding::state::{AccessSet, GeneralServiceState, MPTTrie};
+use crate::executor::{
+    ServiceExecutor, TxCall, ACCOUNT_NONCE_STATE, SCHEDULED_CALLS_STATE, SERVICE_REGISTRY_STATE,
+};
+
+type AccessSets = HashMap<String, AccessSet>;
+
+// The uncommitted writes of the block once its hooks and scheduled calls ran,
+// which every optimistic execution starts from.
+struct Snapshot {
+    root:       MerkleRoot,
+    registered: Vec<ServiceRegistration>,
+    root_state: HashMap<Bytes, Bytes>,
+    states:     HashMap<String, HashMap<Bytes, Bytes>>,
+}
+
+// The call of a tx executed optimistically. The rest of the tx, its fee
+// included, runs on the merged state, so it's not part of the access sets.
+struct OptimisticCall {
+    response:    ServiceResponse<String>,
+    counters:    ServiceContextCounters,
+    access_sets: AccessSets,
+}
+
+impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMapping>
+    ServiceExecutor<S, DB, Mapping>
+{
+    // The call of every tx is executed concurrently against the state before
+    // the txs of the block, tracking what it read and wrote. Then in order, the
+    // writes of a call are applied if none of the keys it read was written by
+    // an earlier tx of the block, and the tx is finished, otherwise it's
+    // re-executed on the latest state. The scheduled calls have used
+    // `cycles_used` of the block already.
+    pub(super) fn exec_parallel(
+        &mut self,
+        params: &ExecutorParams,
+        txs: &[SignedTransaction],
+        cycles_used: u64,
+    ) -> ProtocolResult<Vec<Receipt>> {
+        let snapshot = self.snapshot();
+        let trie_db = Arc::clone(&self.trie_db);
+        let storage = Arc::clone(&self.storage);
+        let mapping = Arc::clone(&self.service_mapping);
+
+        // An error in a snapshot is left to the re-execution.
+        let optimistic = txs
+            .par_iter()
+            .map_init(
+                || {
+                    Self::from_snapshot(
+                        &snapshot,
+                        Arc::clone(&trie_db),
+                        Arc::clone(&storage),
+                        Arc::clone(&mapping),
+                    )
+                    .ok()
+                },
+                |executor, stx| {
+                    executor
+                        .as_mut()
+                        .and_then(|executor| executor.call_optimistic(params, stx).ok().flatten())
+                },
+            )
+            .collect::<Vec<Option<OptimisticCall>>>();
+
+        self.set_tracking(true);
+        let receipts = self.apply_optimistic(params, txs, optimistic, cycles_used);
+        self.set_tracking(false);
+
+        receipts
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            root:       self.root_state.borrow().root(),
+            registered: (*self.registered).clone(),
+            root_state: self.root_state.borrow().uncommitted(),
+            states:     self
+                .all_states()
+                .into_iter()
+                .map(|(name, state)| (name, state.borrow().uncommitted()))
+                .collect(),
+        }
+    }
+
+    // The services activated in the block have no root yet, their states
+    // start empty like in `activate_services`.
+    fn from_snapshot(
+        snapshot: &Snapshot,
+        trie_db: Arc<DB>,
+        storage: Arc<S>,
+        mapping: Arc<Mapping>,
+    ) -> ProtocolResult<Self> {
+        let mut executor = Self::with_root(snapshot.root.clone(), trie_db, storage, mapping)?;
+
+        let mut states = (*executor.states).clone();
+        for registration in snapshot.registered.iter() {
+            states
+                .entry(registration.name.to_owned())
+                .or_insert_with(|| {
+                    let trie = MPTTrie::new(Arc::clone(&executor.trie_db));
+                    Rc::new(RefCell::new(GeneralServiceState::new(trie)))
+                });
+        }
+        executor.states = Rc::new(states);
+        executor.registered = Rc::new(snapshot.registered.clone());
+
+        executor
+            .root_state
+            .borrow_mut()
+            .set_uncommitted(snapshot.root_state.clone());
+        for (name, state) in executor.all_states() {
+            let mut state = state.borrow_mut();
+            if let Some(writes) = snapshot.states.get(&name) {
+                state.set_uncommitted(writes.clone());
+            }
+            state.checkpoint();
+        }
+        executor.set_tracking(true);
+
+        Ok(executor)
+    }
+
+    // A tx rejected before its call is left to the re-execution.
+    fn call_optimistic(
+        &mut self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+    ) -> ProtocolResult<Option<OptimisticCall>> {
+        let called = match self.call_tx(params, stx, params.cycles_limit) {
+            Ok(TxCall::Called(context, response)) => self
+                .stash()
+                .map(|_| Some((context.get_counters(), response))),
+            Ok(TxCall::Done(_)) => Ok(None),
+            Err(e) => Err(e),
+        };
+        let access_sets = self.take_access_sets();
+
+        for (_, state) in self.all_states() {
+            state.borrow_mut().restore_checkpoint();
+        }
+
+        Ok(called?.map(|(counters, response)| OptimisticCall {
+            response,
+            counters,
+            access_sets,
+        }))
+    }
+
+    fn apply_optimistic(
+        &mut self,
+        params: &ExecutorParams,
+        txs: &[SignedTransaction],
+        optimistic: Vec<Option<OptimisticCall>>,
+        mut cycles_used: u64,
+    ) -> ProtocolResult<Vec<Receipt>> {
+        let mut written: HashMap<String, HashSet<Bytes>> = HashMap::new();
+        let mut receipts = Vec::with_capacity(txs.len());
+
+        for (stx, call) in txs.iter().zip(optimistic.into_iter()) {
+            // A call executed with all the cycles of the block is the same as
+            // the serial one only if it used no more than the ones left. The
+            // limit applies to the cycles before the refund.
+            let remaining_cycles = params.cycles_limit.saturating_sub(cycles_used);
+            let receipt = match call {
+                Some(call)
+                    if remaining_cycles > 0
+                        && call.counters.cycles_used <= remaining_cycles
+                        && !is_conflicted(&call.access_sets, &written) =>
+                {
+                    let context = self.tx_context(params, stx, remaining_cycles)?;
+                    context.resume(call.counters);
+
+                    let states = self.all_states();
+                    for (name, access_set) in call.access_sets.into_iter() {
+                        if let Some(state) = states.get(&name) {
+                            state.borrow_mut().apply_writes(access_set.writes);
+                        }
+                    }
+                    self.finish_tx(params, stx, &context, call.response)?
+                }
+                _ => self.exec_tx(params, stx, remaining_cycles)?,
+            };
+            cycles_used += receipt.cycles_used;
+
+            for (name, access_set) in self.take_access_sets().into_iter() {
+                written
+                    .entry(name)
+                    .or_insert_with(HashSet::new)
+                    .extend(access_set.writes.into_iter().map(|(key, _)| key));
+            }
+            receipts.push(receipt);
+        }
+
+        Ok(receipts)
+    }
+
+    fn all_states(&self) -> HashMap<String, Rc<RefCell<GeneralServiceState<DB>>>> {
+        let mut states = self
+            .states
+            .iter()
+            .map(|(name, state)| (name.to_owned(), Rc::clone(state)))
+            .collect::<HashMap<_, _>>();
+        if let Some(nonce_state) = self.nonce_state.as_ref() {
+            states.insert(ACCOUNT_NONCE_STATE.to_owned(), Rc::clone(nonce_state));
+        }
+        states.insert(
+            SERVICE_REGISTRY_STATE.to_owned(),
+            Rc::clone(&self.registry_state),
+        );
+        states.insert(
+            SCHEDULED_CALLS_STATE.to_owned(),
+            Rc::clone(&self.schedule_state),
+        );
+
+        states
+    }
+
+    fn set_tracking(&self, enable: bool) {
+        for (_, state) in self.all_states() {
+            if enable {
+                state.borrow_mut().start_tracking();
+            } else {
+                state.borrow_mut().stop_tracking();
+            }
+        }
+    }
+
+    fn take_access_sets(&self) -> AccessSets {
+        self.all_states()
+            .into_iter()
+            .map(|(name, state)| {
+                let access_set = state.borrow_mut().take_access_set();
+                (name, access_set)
+            })
+            .collect()
+    }
+}
+
+fn is_conflicted(access_sets: &AccessSets, written: &HashMap<String, HashSet<Bytes>>) -> bool {
+    access_sets.iter().any(|(name, access_set)| {
+        written
+            .get(name)
+            .map(|keys| !access_set.reads.is_disjoint(keys))
+            .unwrap_or(false)
+    })
+}
@@ -0,0 +1,170 @@
+use std::cmp;
+use std::panic::{self, AssertUnwindSafe};
+
+use bytes::Bytes;
+use cita_trie::DB as TrieDB;
+use serde::{Deserialize, Serialize};
+
+use protocol::traits::{ExecutorParams, ServiceMapping, ServiceResponse, ServiceState, Storage};
+use protocol::types::{Address, Hash, Receipt, ServiceContext, TransactionRequest};
+use protocol::ProtocolResult;
+
+use crate::binding::state::GeneralServiceState;
+use crate::executor::{
+    ExecType, ExecutorError, ServiceExecutor, BLOCK_CYCLES_EXCEEDED_CODE, MAX_SCHEDULED_CALLS,
+    SCHEDULED_CALL_CYCLES, SCHEDULED_COUNT_KEY,
+};
+
+// The caller of every scheduled call, so that a service can't take the call
+// for one made by the tx that scheduled it.
+pub fn scheduler_address() -> Address {
+    Address::from_hash(Hash::digest(Bytes::from("scheduler")))
+        .expect("scheduler address should be valid")
+}
+
+// The id of a call is the tx hash of its receipt. It's made of the height and
+// the index of the call there, so that the receipts of a height can be found
+// from the height.
+pub fn scheduled_call_id(height: u64, index: usize) -> Hash {
+    Hash::digest(Bytes::from(format!("scheduled-call-{}-{}", height, index)))
+}
+
+// The scheduled calls of a height are kept in the schedule state under the
+// height, in the order they were scheduled.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(super) struct ScheduledCall {
+    // The hash of the call's receipt
+    pub id:      Hash,
+    pub service: String,
+    pub method:  String,
+    pub payload: String,
+}
+
+impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMapping>
+    ServiceExecutor<S, DB, Mapping>
+{
+    pub(super) fn schedule_call(
+        &self,
+        context: &ServiceContext,
+        height: u64,
+    ) -> ProtocolResult<Hash> {
+        if height <= context.get_current_height() {
+            return Err(ExecutorError::ScheduleCall(
+                "height must be above the current one".to_owned(),
+            )
+            .into());
+        }
+        if !self
+            .list_service_name()
+            .iter()
+            .any(|name| name == context.get_service_name())
+        {
+            return Err(ExecutorError::ScheduleCall(format!(
+                "service {:?} was not found",
+                context.get_service_name()
+            ))
+            .into());
+        }
+
+        let mut state = self.schedule_state.borrow_mut();
+        let mut calls = get_scheduled_calls(&state, height)?;
+        if calls.len() >= MAX_SCHEDULED_CALLS {
+            return Err(ExecutorError::ScheduleCall(format!(
+                "at most {} calls at a height",
+                MAX_SCHEDULED_CALLS
+            ))
+            .into());
+        }
+
+        // The cycles of the call are paid by the scheduling tx
+        if !context.sub_cycles(SCHEDULED_CALL_CYCLES) {
+            return Err(ExecutorError::ScheduleCall("out of cycles".to_owned()).into());
+        }
+
+        let count: u64 = state.get(&SCHEDULED_COUNT_KEY.to_owned())?.unwrap_or(0);
+        let id = scheduled_call_id(height, calls.len());
+        calls.push(ScheduledCall {
+            id:      id.clone(),
+            service: context.get_service_name().to_owned(),
+            method:  context.get_service_method().to_owned(),
+            payload: context.get_payload().to_owned(),
+        });
+        let calls = serde_json::to_string(&calls).map_err(ExecutorError::from)?;
+        state.insert(height, calls)?;
+        state.insert(SCHEDULED_COUNT_KEY.to_owned(), count + 1)?;
+
+        Ok(id)
+    }
+
+    // The calls due at the height run one by one like txs with the scheduler as
+    // their caller, but without the tx hooks and the fee, which was paid when
+    // they were scheduled. They share the cycles of the block with the txs.
+    // The calls of the height are dropped once they have run.
+    pub(super) fn exec_scheduled_calls(
+        &mut self,
+        params: &ExecutorParams,
+    ) -> ProtocolResult<Vec<Receipt>> {
+        let calls = get_scheduled_calls(&self.schedule_state.borrow(), params.height)?;
+        if calls.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let scheduler = scheduler_address();
+        let mut receipts = Vec::with_capacity(calls.len());
+        let mut cycles_used = 0u64;
+        for call in calls.into_iter() {
+            let remaining_cycles = params.cycles_limit.saturating_sub(cycles_used);
+            let request = TransactionRequest {
+                service_name: call.service,
+                method:       call.method,
+                payload:      call.payload,
+            };
+            let context = self.get_context(
+                Some(call.id.clone()),
+                None,
+                &scheduler,
+                0,
+                cmp::min(SCHEDULED_CALL_CYCLES, remaining_cycles),
+                params,
+                &request,
+            )?;
+
+            let resp = if remaining_cycles == 0 {
+                ServiceResponse::from_error(
+                    BLOCK_CYCLES_EXCEEDED_CODE,
+                    "block cycles exceeded".to_owned(),
+                )
+            } else {
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    self.call(context.clone(), ExecType::Write)
+                }));
+                let resp = self.check_call_result(&context, result)?;
+                self.stash()?;
+                resp
+            };
+
+            let receipt = self.receipt(&call.id, &context, resp, vec![]);
+            cycles_used += receipt.cycles_used;
+            receipts.push(receipt);
+        }
+
+        let mut state = self.schedule_state.borrow_mut();
+        state.insert(params.height, String::new())?;
+        state.stash()?;
+
+        Ok(receipts)
+    }
+}
+
+fn get_scheduled_calls<DB: TrieDB>(
+    state: &GeneralServiceState<DB>,
+    height: u64,
+) -> ProtocolResult<Vec<ScheduledCall>> {
+    // The calls which have run are removed by an empty value
+    match state.get::<u64, String>(&height)? {
+        Some(ref calls) if !calls.is_empty() => {
+            Ok(serde_json::from_str(calls).map_err(ExecutorError::from)?)
+        }
+        _ => Ok(vec![]),
+    }
+}
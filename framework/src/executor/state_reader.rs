@@ -0,0 +1,89 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+
+use cita_trie::DB as TrieDB;
+use futures::channel::oneshot;
+
+use protocol::traits::{ServiceMapping, Storage};
+use protocol::types::MerkleRoot;
+use protocol::ProtocolResult;
+
+use crate::executor::{ExecutorError, ServiceExecutor};
+
+type Job<S, DB, Mapping> =
+    Box<dyn FnOnce(ProtocolResult<&ServiceExecutor<S, DB, Mapping>>) + Send + 'static>;
+
+// Runs the reads of the mempool against a state on a thread of its own, so
+// that they don't block the async runtime. The executor is built once for a
+// root, and kept until a read of another root comes.
+pub struct StateReader<S, DB, Mapping> {
+    jobs: Mutex<mpsc::Sender<(MerkleRoot, Job<S, DB, Mapping>)>>,
+}
+
+impl<S, DB, Mapping> StateReader<S, DB, Mapping>
+where
+    S: 'static + Storage,
+    DB: 'static + TrieDB,
+    Mapping: 'static + ServiceMapping,
+{
+    pub fn new(storage: Arc<S>, trie_db: Arc<DB>, service_mapping: Arc<Mapping>) -> Self {
+        let (jobs, job_rx) = mpsc::channel::<(MerkleRoot, Job<S, DB, Mapping>)>();
+
+        thread::spawn(move || {
+            let mut cached: Option<(MerkleRoot, ServiceExecutor<S, DB, Mapping>)> = None;
+
+            for (root, job) in job_rx.iter() {
+                let stale = match cached.as_ref() {
+                    Some((cached_root, _)) => cached_root != &root,
+                    None => true,
+                };
+                if stale {
+                    let executor = ServiceExecutor::with_root(
+                        root.clone(),
+                        Arc::clone(&trie_db),
+                        Arc::clone(&storage),
+                        Arc::clone(&service_mapping),
+                    );
+                    match executor {
+                        Ok(executor) => cached = Some((root, executor)),
+                        Err(e) => {
+                            job(Err(e));
+                            continue;
+                        }
+                    }
+                }
+
+                if let Some((_, executor)) = cached.as_ref() {
+                    // The reply is dropped if the job panics
+                    let _ = panic::catch_unwind(AssertUnwindSafe(|| job(Ok(executor))));
+                }
+            }
+        });
+
+        Self {
+            jobs: Mutex::new(jobs),
+        }
+    }
+
+    pub async fn read<T, F>(&self, root: MerkleRoot, f: F) -> ProtocolResult<T>
+    where
+        T: 'static + Send,
+        F: 'static + Send + FnOnce(&ServiceExecutor<S, DB, Mapping>) -> ProtocolResult<T>,
+    {
+        let (reply, reply_rx) = oneshot::channel();
+        let job: Job<S, DB, Mapping> = Box::new(move |executor| {
+            let _ = reply.send(executor.and_then(f));
+        });
+
+        self.jobs
+            .lock()
+            .map_err(|_| ExecutorError::StateReaderStopped)?
+            .send((root, job))
+            .map_err(|_| ExecutorError::StateReaderStopped)?;
+
+        reply_rx
+            .await
+            .map_err(|_| ExecutorError::StateReaderStopped)?
+    }
+}
@@ -3,6 +3,7 @@ extern crate test;
 mod service_call_service;
 mod test_service;
 
+use std::panic::{self, AssertUnwindSafe};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -13,20 +14,877 @@ use test::Bencher;
 use asset::types::{Asset, GetBalanceResponse};
 use asset::AssetService;
 use metadata::MetadataService;
+use protocol::fixed_codec::FixedCodec;
 use protocol::traits::{
-    Context, Executor, ExecutorParams, Service, ServiceMapping, ServiceSDK, Storage,
+    Context, Executor, ExecutorFactory, ExecutorOptions, ExecutorParams, FeeHook, Service,
+    ServiceMapping, ServiceSDK, Storage,
 };
 use protocol::types::{
-    Address, Block, Genesis, Hash, Proof, RawTransaction, Receipt, SignedTransaction,
-    TransactionRequest,
+    account_nonce_to_hash, Address, Block, BloomInput, Genesis, Hash, NonceMode, Proof, PubkeyKind,
+    RawTransaction, Receipt, SignatureScheme, SignedTransaction, TransactionRequest,
+    VerifierPubkey,
 };
 use protocol::ProtocolResult;
 
-use crate::executor::ServiceExecutor;
-use test_service::TestService;
+use crate::binding::store::REMOVE_REFUND_CYCLES;
+use crate::executor::{
+    scheduled_call_id, service_version_key, ServiceExecutor, ServiceExecutorFactory,
+    BLOCK_CYCLES_EXCEEDED_CODE, CHARGE_FEE_FAILED_CODE, INVALID_NONCE_CODE, INVALID_NONCE_CYCLES,
+    INVALID_SIGNATURE_CODE, INVALID_SIGNATURE_CYCLES, MAX_SCHEDULED_CALLS, OUT_OF_CYCLES_CODE,
+    REGISTER_SERVICE_FAILED_CODE, SCHEDULED_CALL_CYCLES, SCHEDULE_CALL_FAILED_CODE,
+    SERVICE_PANICKED_CODE, WRITE_IN_READONLY_CODE,
+};
+use test_service::{TestReadResponse, TestService};
+
+#[test]
+fn test_create_genesis() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let request = TransactionRequest {
+        service_name: "asset".to_owned(),
+        method:       "get_balance".to_owned(),
+        payload:
+            r#"{"asset_id": "0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c", "user": "0xf8389d774afdad8755ef8e629e5a154fddc6325a"}"#
+                .to_owned(),
+    };
+    let res = executor.read(&params, &caller, 1, &request).unwrap();
+    let resp: GetBalanceResponse = serde_json::from_str(&res.succeed_data).unwrap();
+
+    assert_eq!(resp.balance, 320_000_011);
+}
+
+#[test]
+fn test_exec() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    let stx = mock_signed_tx();
+    let txs = vec![stx];
+    let executor_resp = executor.exec(&params, &txs).unwrap();
+    let receipt = &executor_resp.receipts[0];
+
+    assert_eq!(receipt.response.response.code, 0);
+    let asset: Asset = serde_json::from_str(&receipt.response.response.succeed_data).unwrap();
+    assert_eq!(asset.name, "MutaToken2");
+    assert_eq!(asset.symbol, "MT2");
+    assert_eq!(asset.supply, 320_000_011);
+}
+
+#[test]
+fn test_tx_hook() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    // no tx hook
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_write".to_owned();
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "bar",
+        "extra": ""
+    }"#
+    .to_owned();
+    let txs = vec![stx.clone()];
+    let executor_resp = executor.exec(&params, &txs).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(receipt.events.len(), 0);
+
+    // tx hook
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "bar",
+        "extra": "test_hook_before; test_hook_after"
+    }"#
+    .to_owned();
+    let txs = vec![stx.clone()];
+    let executor_resp = executor.exec(&params, &txs).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(receipt.events.len(), 2);
+    assert_eq!(&receipt.events[0].data, "test_tx_hook_before invoked");
+    assert_eq!(&receipt.events[1].data, "test_tx_hook_after invoked");
+
+    // test_service_call_invoke_hook_only_once
+    stx.raw.request.method = "test_service_call_invoke_hook_only_once".to_owned();
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "bar",
+        "extra": "test_hook_before; test_hook_after"
+    }"#
+    .to_owned();
+    let txs = vec![stx];
+    let executor_resp = executor.exec(&params, &txs).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(receipt.events.len(), 2);
+    assert_eq!(&receipt.events[0].data, "test_tx_hook_before invoked");
+    assert_eq!(&receipt.events[1].data, "test_tx_hook_after invoked");
+
+    // tx hook rejects the tx
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_write".to_owned();
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "baz",
+        "extra": "test_hook_reject"
+    }"#
+    .to_owned();
+    let executor_resp = executor.exec(&params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 111);
+    assert_eq!(
+        &receipt.response.response.error_message,
+        "rejected by tx hook"
+    );
+
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let request = TransactionRequest {
+        service_name: "test".to_owned(),
+        method:       "test_read".to_owned(),
+        payload:      r#"{ "key": "foo" }"#.to_owned(),
+    };
+    let res = executor.read(&params, &caller, 1, &request).unwrap();
+    let resp: TestReadResponse = serde_json::from_str(&res.succeed_data).unwrap();
+    assert_eq!(resp.value, "bar");
+}
+
+#[test]
+fn test_tx_hook_after_kept_on_failure() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_panic".to_owned();
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "bar",
+        "extra": "test_hook_after_write"
+    }"#
+    .to_owned();
+    let executor_resp = executor.exec(&params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, SERVICE_PANICKED_CODE);
+
+    let read = |key: &str| {
+        let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+        let request = TransactionRequest {
+            service_name: "test".to_owned(),
+            method:       "test_read".to_owned(),
+            payload:      format!(r#"{{ "key": "{}" }}"#, key),
+        };
+        let res = executor.read(&params, &caller, 1, &request).unwrap();
+        let resp: TestReadResponse = serde_json::from_str(&res.succeed_data).unwrap();
+        resp.value
+    };
+    assert_eq!(read("foo"), "");
+    assert_eq!(read("hook_after"), "invoked");
+}
+
+#[test]
+fn test_out_of_cycles() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    let mut stx = mock_signed_tx();
+    stx.raw.cycles_limit = 30000;
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_out_of_cycles".to_owned();
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "bar",
+        "extra": ""
+    }"#
+    .to_owned();
+    let executor_resp = executor.exec(&params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, OUT_OF_CYCLES_CODE);
+    assert_eq!(receipt.cycles_used, 30000);
+
+    // The write before running out of cycles is reverted
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let request = TransactionRequest {
+        service_name: "test".to_owned(),
+        method:       "test_read".to_owned(),
+        payload:      r#"{ "key": "foo" }"#.to_owned(),
+    };
+    let res = executor.read(&params, &caller, 1, &request).unwrap();
+    let resp: TestReadResponse = serde_json::from_str(&res.succeed_data).unwrap();
+    assert_eq!(resp.value, "");
+}
+
+#[test]
+fn test_charge_fee() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockFeeServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockFeeServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    let mut stx = mock_signed_tx();
+    stx.raw.cycles_price = 2;
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_write".to_owned();
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "bar",
+        "extra": ""
+    }"#
+    .to_owned();
+    let executor_resp = executor.exec(&params, &[stx.clone()]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(receipt.cycles_used, 21000);
+    assert_eq!(receipt.events.len(), 1);
+    assert_eq!(get_balance(&executor, &params), 320_000_011 - 21000 * 2);
+
+    // The fee event can be filtered by its type and by its payer
+    let payer = "0xf8389d774afdad8755ef8e629e5a154fddc6325a";
+    let event = &receipt.events[0];
+    assert_eq!(event.name, "ChargeFeeEvent");
+    assert_eq!(event.topics[1], payer);
+    let payer_item = event.topic_bloom_item(1).unwrap();
+    assert_eq!(payer_item, format!("ChargeFeeEvent:1:{}", payer));
+    for item in ["assetChargeFeeEvent", payer_item.as_str()].iter() {
+        let hash = Hash::digest(Bytes::from(item.as_bytes().to_vec())).as_bytes();
+        assert!(executor_resp
+            .logs_bloom
+            .contains_input(BloomInput::Raw(hash.as_ref())));
+    }
+
+    // The balance can't pay the fee, the tx is reverted
+    stx.raw.cycles_price = 1_000_000;
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "baz",
+        "extra": ""
+    }"#
+    .to_owned();
+    let executor_resp = executor.exec(&params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, CHARGE_FEE_FAILED_CODE);
+    assert_eq!(get_balance(&executor, &params), 320_000_011 - 21000 * 2);
+
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let request = TransactionRequest {
+        service_name: "test".to_owned(),
+        method:       "test_read".to_owned(),
+        payload:      r#"{ "key": "foo" }"#.to_owned(),
+    };
+    let res = executor.read(&params, &caller, 1, &request).unwrap();
+    let resp: TestReadResponse = serde_json::from_str(&res.succeed_data).unwrap();
+    assert_eq!(resp.value, "bar");
+
+    // A tx can't call the fee hook to burn more than its own fee
+    let balance = get_balance(&executor, &params);
+    stx.raw.cycles_price = 2;
+    stx.raw.request.service_name = "asset".to_owned();
+    stx.raw.request.method = "charge_fee".to_owned();
+    stx.raw.request.payload = r#"{ "fee": 1000000 }"#.to_owned();
+    let executor_resp = executor.exec(&params, &[stx.clone()]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 108);
+    assert_eq!(
+        get_balance(&executor, &params),
+        balance - receipt.cycles_used * 2
+    );
+
+    // A tx beyond the block's cycles limit isn't charged
+    let balance = get_balance(&executor, &params);
+    params.cycles_limit = 10000;
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_write".to_owned();
+    stx.raw.request.payload = r#"{ "key": "foo", "value": "qux", "extra": "" }"#.to_owned();
+    let executor_resp = executor.exec(&params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, BLOCK_CYCLES_EXCEEDED_CODE);
+    assert!(receipt.events.is_empty());
+    assert_eq!(get_balance(&executor, &params), balance);
+}
+
+#[test]
+fn test_cycles_breakdown_and_refund() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockFeeServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockFeeServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    // Each call is recorded with the cycles of its nested calls
+    let mut stx = mock_signed_tx();
+    stx.raw.cycles_price = 1;
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_service_call_invoke_hook_only_once".to_owned();
+    stx.raw.request.payload = r#"{ "key": "foo", "value": "bar", "extra": "" }"#.to_owned();
+    let executor_resp = executor.exec(&params, &[stx.clone()]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(receipt.cycles_used, 42000);
+    assert_eq!(receipt.cycles_refunded, 0);
+    let records = &receipt.cycles_breakdown;
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0].method, "test_service_call_invoke_hook_only_once");
+    assert_eq!(records[0].call_depth, 0);
+    assert_eq!(records[0].cycles, 42000);
+    assert_eq!(records[1].method, "test_write");
+    assert_eq!(records[1].call_depth, 1);
+    assert_eq!(records[1].cycles, 21000);
+    let balance = get_balance(&executor, &params);
+    assert_eq!(balance, 320_000_011 - 42000);
+
+    stx.raw.request.method = "test_map_insert".to_owned();
+    let executor_resp = executor.exec(&params, &[stx.clone()]).unwrap();
+    assert_eq!(executor_resp.receipts[0].cycles_refunded, 0);
+
+    // Removing the entry refunds a part of the cycles, only the rest is charged
+    stx.raw.request.method = "test_map_remove".to_owned();
+    stx.raw.request.payload = r#"{ "key": "foo" }"#.to_owned();
+    let executor_resp = executor.exec(&params, &[stx.clone()]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(receipt.cycles_refunded, REMOVE_REFUND_CYCLES);
+    assert_eq!(receipt.cycles_used, 21000 - REMOVE_REFUND_CYCLES);
+    assert_eq!(receipt.cycles_breakdown[0].cycles, 21000);
+    assert_eq!(
+        get_balance(&executor, &params),
+        balance - 21000 * 2 + REMOVE_REFUND_CYCLES
+    );
+
+    // There is nothing to remove any more
+    let executor_resp = executor.exec(&params, &[stx]).unwrap();
+    assert_eq!(executor_resp.receipts[0].cycles_refunded, 0);
+}
+
+#[test]
+fn test_account_nonce() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockNonceServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockNonceServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    assert_eq!(executor.get_account_nonce(&caller).unwrap(), 0);
+
+    let mut stx = mock_signed_tx();
+    stx.raw.nonce = account_nonce_to_hash(0);
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_write".to_owned();
+    stx.raw.request.payload = r#"{
+        "key": "foo",
+        "value": "bar",
+        "extra": ""
+    }"#
+    .to_owned();
+
+    // The replayed tx and the one with a random nonce are not executed, but
+    // they're charged
+    let mut random_stx = stx.clone();
+    random_stx.raw.nonce = Hash::from_empty();
+    let executor_resp = executor
+        .exec(&params, &[stx.clone(), stx.clone(), random_stx])
+        .unwrap();
+    let receipts = &executor_resp.receipts;
+    assert_eq!(receipts[0].response.response.code, 0);
+    assert_eq!(receipts[1].response.response.code, INVALID_NONCE_CODE);
+    assert_eq!(receipts[2].response.response.code, INVALID_NONCE_CODE);
+    assert_eq!(receipts[1].cycles_used, INVALID_NONCE_CYCLES);
+    assert_eq!(receipts[2].cycles_used, INVALID_NONCE_CYCLES);
+    assert_eq!(executor.get_account_nonce(&caller).unwrap(), 1);
+
+    // A failed tx still bumps the nonce
+    stx.raw.nonce = account_nonce_to_hash(1);
+    stx.raw.request.method = "test_panic".to_owned();
+    let executor_resp = executor.exec(&params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, SERVICE_PANICKED_CODE);
+    assert_eq!(executor.get_account_nonce(&caller).unwrap(), 2);
+}
+
+#[test]
+fn test_parallel_exec() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    // The mode is picked by the options passed to the factory
+    let new_executor = |parallel: bool| {
+        let db = Arc::new(MemoryDB::new(false));
+        let root = ServiceExecutor::create_genesis(
+            genesis.services.clone(),
+            Arc::clone(&db),
+            Arc::new(MockStorage {}),
+            Arc::new(MockNonceServiceMapping {}),
+        )
+        .unwrap();
+
+        let executor = <ServiceExecutorFactory as ExecutorFactory<_, _, _>>::from_root(
+            root.clone(),
+            db,
+            Arc::new(MockStorage {}),
+            Arc::new(MockNonceServiceMapping {}),
+            ExecutorOptions { parallel },
+        )
+        .unwrap();
+        (executor, root)
+    };
+
+    let (mut serial, root) = new_executor(false);
+    let (mut parallel, _) = new_executor(true);
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    // Later txs read the nonce written by earlier ones, so they conflict
+    let mut txs = vec![];
+    for (nonce, (method, value)) in [
+        ("test_write", "bar"),
+        ("test_write", "baz"),
+        ("test_panic", ""),
+    ]
+    .iter()
+    .enumerate()
+    {
+        let mut stx = mock_signed_tx();
+        stx.raw.nonce = account_nonce_to_hash(nonce as u64);
+        stx.raw.request.service_name = "test".to_owned();
+        stx.raw.request.method = (*method).to_owned();
+        stx.raw.request.payload =
+            format!(r#"{{ "key": "foo", "value": "{}", "extra": "" }}"#, value);
+        txs.push(stx);
+    }
+    txs.push(mock_signed_tx());
+
+    let serial_resp = serial.exec(&params, &txs).unwrap();
+    let parallel_resp = parallel.exec(&params, &txs).unwrap();
+    assert_eq!(parallel_resp.state_root, serial_resp.state_root);
+    assert_eq!(parallel_resp.receipts, serial_resp.receipts);
+
+    let codes = parallel_resp
+        .receipts
+        .iter()
+        .map(|receipt| receipt.response.response.code)
+        .collect::<Vec<_>>();
+    assert_eq!(codes, vec![0, 0, SERVICE_PANICKED_CODE, INVALID_NONCE_CODE]);
+}
+
+#[test]
+fn test_parallel_exec_with_fee() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let new_executor = || {
+        let db = Arc::new(MemoryDB::new(false));
+        let root = ServiceExecutor::create_genesis(
+            genesis.services.clone(),
+            Arc::clone(&db),
+            Arc::new(MockStorage {}),
+            Arc::new(MockFeeServiceMapping {}),
+        )
+        .unwrap();
+
+        let executor = ServiceExecutor::with_root(
+            root.clone(),
+            Arc::clone(&db),
+            Arc::new(MockStorage {}),
+            Arc::new(MockFeeServiceMapping {}),
+        )
+        .unwrap();
+        (executor, root)
+    };
+
+    let (mut serial, root) = new_executor();
+    let (mut parallel, _) = new_executor();
+    parallel.parallel = true;
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    // Every tx pays its fee from the same balance, the last one can't
+    let mut txs = vec![];
+    for (key, price) in [("foo", 2), ("bar", 2), ("baz", 1_000_000)].iter() {
+        let mut stx = mock_signed_tx();
+        stx.raw.cycles_price = *price;
+        stx.raw.request.service_name = "test".to_owned();
+        stx.raw.request.method = "test_write".to_owned();
+        stx.raw.request.payload = format!(r#"{{ "key": "{}", "value": "bar", "extra": "" }}"#, key);
+        txs.push(stx);
+    }
+
+    let serial_resp = serial.exec(&params, &txs).unwrap();
+    let parallel_resp = parallel.exec(&params, &txs).unwrap();
+    assert_eq!(parallel_resp.state_root, serial_resp.state_root);
+    assert_eq!(parallel_resp.receipts, serial_resp.receipts);
+
+    let codes = parallel_resp
+        .receipts
+        .iter()
+        .map(|receipt| receipt.response.response.code)
+        .collect::<Vec<_>>();
+    assert_eq!(codes, vec![0, 0, CHARGE_FEE_FAILED_CODE]);
+    assert_eq!(get_balance(&parallel, &params), 320_000_011 - 2 * 21000 * 2);
+}
+
+#[test]
+fn test_block_cycles_limit() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let new_executor = || {
+        let db = Arc::new(MemoryDB::new(false));
+        let root = ServiceExecutor::create_genesis(
+            genesis.services.clone(),
+            Arc::clone(&db),
+            Arc::new(MockStorage {}),
+            Arc::new(MockServiceMapping {}),
+        )
+        .unwrap();
+
+        let executor = ServiceExecutor::with_root(
+            root.clone(),
+            Arc::clone(&db),
+            Arc::new(MockStorage {}),
+            Arc::new(MockServiceMapping {}),
+        )
+        .unwrap();
+        (executor, root)
+    };
+
+    let (mut serial, root) = new_executor();
+    let (mut parallel, _) = new_executor();
+    parallel.parallel = true;
+
+    // Room for two writes and a part of the third one
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: 50000,
+        random_seed:  Hash::from_empty(),
+    };
+
+    let mut txs = vec![];
+    for value in ["bar", "baz", "qux", "quux"].iter() {
+        let mut stx = mock_signed_tx();
+        stx.raw.request.service_name = "test".to_owned();
+        stx.raw.request.method = "test_write".to_owned();
+        stx.raw.request.payload =
+            format!(r#"{{ "key": "foo", "value": "{}", "extra": "" }}"#, value);
+        txs.push(stx);
+    }
+
+    let serial_resp = serial.exec(&params, &txs).unwrap();
+    let parallel_resp = parallel.exec(&params, &txs).unwrap();
+    assert_eq!(parallel_resp.state_root, serial_resp.state_root);
+    assert_eq!(parallel_resp.receipts, serial_resp.receipts);
+    assert_eq!(serial_resp.all_cycles_used, 50000);
+
+    let results = serial_resp
+        .receipts
+        .iter()
+        .map(|receipt| (receipt.response.response.code, receipt.cycles_used))
+        .collect::<Vec<_>>();
+    assert_eq!(results, vec![
+        (0, 21000),
+        (0, 21000),
+        (BLOCK_CYCLES_EXCEEDED_CODE, 8000),
+        (BLOCK_CYCLES_EXCEEDED_CODE, 0)
+    ]);
+
+    // The txs beyond the limit have no effect
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let request = TransactionRequest {
+        service_name: "test".to_owned(),
+        method:       "test_read".to_owned(),
+        payload:      r#"{ "key": "foo" }"#.to_owned(),
+    };
+    let res = serial.read(&params, &caller, 1, &request).unwrap();
+    let resp: TestReadResponse = serde_json::from_str(&res.succeed_data).unwrap();
+    assert_eq!(resp.value, "baz");
+}
+
+#[test]
+fn test_block_cycles_limit_with_refund() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let new_executor = || {
+        let db = Arc::new(MemoryDB::new(false));
+        let root = ServiceExecutor::create_genesis(
+            genesis.services.clone(),
+            Arc::clone(&db),
+            Arc::new(MockStorage {}),
+            Arc::new(MockServiceMapping {}),
+        )
+        .unwrap();
+
+        let executor = ServiceExecutor::with_root(
+            root.clone(),
+            Arc::clone(&db),
+            Arc::new(MockStorage {}),
+            Arc::new(MockServiceMapping {}),
+        )
+        .unwrap();
+        (executor, root)
+    };
+
+    let (mut serial, root) = new_executor();
+    let (mut parallel, _) = new_executor();
+    parallel.parallel = true;
+
+    let mut params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    let mut insert = mock_signed_tx();
+    insert.raw.request.service_name = "test".to_owned();
+    insert.raw.request.method = "test_map_insert".to_owned();
+    insert.raw.request.payload = r#"{ "key": "foo", "value": "bar", "extra": "" }"#.to_owned();
+    let serial_resp = serial.exec(&params, &[insert.clone()]).unwrap();
+    let parallel_resp = parallel.exec(&params, &[insert]).unwrap();
+    assert_eq!(parallel_resp.state_root, serial_resp.state_root);
+
+    // The removal needs 21000 cycles before its refund, but only 18000 are
+    // left after the write.
+    params.height = 2;
+    params.cycles_limit = 39000;
+    let mut write = mock_signed_tx();
+    write.raw.request.service_name = "test".to_owned();
+    write.raw.request.method = "test_write".to_owned();
+    write.raw.request.payload = r#"{ "key": "foo", "value": "bar", "extra": "" }"#.to_owned();
+    let mut remove = mock_signed_tx();
+    remove.raw.request.service_name = "test".to_owned();
+    remove.raw.request.method = "test_map_remove".to_owned();
+    remove.raw.request.payload = r#"{ "key": "foo" }"#.to_owned();
+    let txs = vec![write, remove];
+
+    let serial_resp = serial.exec(&params, &txs).unwrap();
+    let parallel_resp = parallel.exec(&params, &txs).unwrap();
+    assert_eq!(parallel_resp.state_root, serial_resp.state_root);
+    assert_eq!(parallel_resp.receipts, serial_resp.receipts);
+
+    let receipt = &serial_resp.receipts[1];
+    assert_eq!(receipt.response.response.code, BLOCK_CYCLES_EXCEEDED_CODE);
+    assert_eq!(receipt.cycles_refunded, 0);
+}
 
 #[test]
-fn test_create_genesis() {
+fn test_readonly_call() {
     let toml_str = include_str!("./genesis_services.toml");
     let genesis: Genesis = toml::from_str(toml_str).unwrap();
 
@@ -47,28 +905,190 @@ fn test_create_genesis() {
         Arc::new(MockServiceMapping {}),
     )
     .unwrap();
+
     let params = ExecutorParams {
         state_root:   root,
         height:       1,
         timestamp:    0,
         cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
     };
+
+    let mut sdk = executor.get_readonly_sdk("test").unwrap();
+    let value: Option<String> = sdk.get_value(&"foo".to_owned());
+    assert_eq!(value, None);
+
+    let set_value = panic::catch_unwind(AssertUnwindSafe(|| {
+        sdk.set_value("foo".to_owned(), "bar".to_owned())
+    }));
+    assert!(set_value.is_err());
+
+    // Writable methods can't be called from a read-only call either
     let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
     let request = TransactionRequest {
-        service_name: "asset".to_owned(),
-        method:       "get_balance".to_owned(),
-        payload:
-            r#"{"asset_id": "0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c", "user": "0xf8389d774afdad8755ef8e629e5a154fddc6325a"}"#
-                .to_owned(),
+        service_name: "test".to_owned(),
+        method:       "test_read".to_owned(),
+        payload:      r#"{ "key": "foo" }"#.to_owned(),
     };
-    let res = executor.read(&params, &caller, 1, &request).unwrap();
-    let resp: GetBalanceResponse = serde_json::from_str(&res.succeed_data).unwrap();
+    let context = executor
+        .get_context(None, None, &caller, 1, std::u64::MAX, &params, &request)
+        .unwrap();
+    let resp = sdk.write(
+        &context,
+        None,
+        "test",
+        "test_write",
+        r#"{ "key": "foo", "value": "bar", "extra": "" }"#,
+    );
+    assert_eq!(resp.code, WRITE_IN_READONLY_CODE);
 
-    assert_eq!(resp.balance, 320_000_011);
+    let value: Option<String> = sdk.get_value(&"foo".to_owned());
+    assert_eq!(value, None);
 }
 
 #[test]
-fn test_exec() {
+fn test_service_migrate() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockVersionServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockVersionServiceMapping {}),
+    )
+    .unwrap();
+
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let request = TransactionRequest {
+        service_name: "test".to_owned(),
+        method:       "test_read".to_owned(),
+        payload:      r#"{ "key": "migrated" }"#.to_owned(),
+    };
+
+    // The test service is raised to version 1 at height 5, the migration runs
+    // only once. The migration to version 2 at height 6 panics, its write is
+    // dropped and the block goes on.
+    for height in 1..8 {
+        let params = ExecutorParams {
+            state_root: root.clone(),
+            height,
+            timestamp: 0,
+            cycles_limit: std::u64::MAX,
+            random_seed: Hash::from_empty(),
+        };
+        executor.exec(&params, &[]).unwrap();
+
+        let res = executor.read(&params, &caller, 1, &request).unwrap();
+        let resp: TestReadResponse = serde_json::from_str(&res.succeed_data).unwrap();
+        let expect = if height < 5 { "" } else { "v1" };
+        assert_eq!(resp.value, expect);
+    }
+
+    // The failed migration isn't retried
+    let version: Option<u64> = executor
+        .root_state
+        .borrow()
+        .get(&service_version_key("test"))
+        .unwrap();
+    assert_eq!(version, Some(2));
+}
+
+#[test]
+fn test_register_service() {
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let db = Arc::new(MemoryDB::new(false));
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockRegistryServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockRegistryServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    // The test code is registered again as test2 from height 3, the name can't
+    // be registered twice.
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_register_service".to_owned();
+    stx.raw.request.payload =
+        r#"{ "name": "test2", "code": "test", "payload": "", "height": 3 }"#.to_owned();
+    let resp = executor.exec(&params, &[stx.clone(), stx]).unwrap();
+    let codes = resp
+        .receipts
+        .iter()
+        .map(|receipt| receipt.response.response.code)
+        .collect::<Vec<_>>();
+    assert_eq!(codes, vec![0, REGISTER_SERVICE_FAILED_CODE]);
+
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let read = |executor: &ServiceExecutor<_, _, _>, params: &ExecutorParams, service: &str| {
+        let request = TransactionRequest {
+            service_name: service.to_owned(),
+            method:       "test_read".to_owned(),
+            payload:      r#"{ "key": "foo" }"#.to_owned(),
+        };
+        executor
+            .read(params, &caller, 1, &request)
+            .map(|res| serde_json::from_str::<TestReadResponse>(&res.succeed_data).unwrap())
+    };
+
+    params.height = 2;
+    executor.exec(&params, &[]).unwrap();
+    assert!(read(&executor, &params, "test2").is_err());
+
+    params.height = 3;
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test2".to_owned();
+    stx.raw.request.method = "test_write".to_owned();
+    stx.raw.request.payload = r#"{ "key": "foo", "value": "bar", "extra": "" }"#.to_owned();
+    let resp = executor.exec(&params, &[stx]).unwrap();
+    assert_eq!(resp.receipts[0].response.response.code, 0);
+    assert_eq!(read(&executor, &params, "test2").unwrap().value, "bar");
+    assert_eq!(read(&executor, &params, "test").unwrap().value, "");
+
+    // The registered service is recorded in the root state
+    let executor = ServiceExecutor::with_root(
+        resp.state_root,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockRegistryServiceMapping {}),
+    )
+    .unwrap();
+    assert_eq!(read(&executor, &params, "test2").unwrap().value, "bar");
+}
+
+#[test]
+fn test_schedule_call() {
     let toml_str = include_str!("./genesis_services.toml");
     let genesis: Genesis = toml::from_str(toml_str).unwrap();
 
@@ -90,27 +1110,89 @@ fn test_exec() {
     )
     .unwrap();
 
-    let params = ExecutorParams {
+    let mut params = ExecutorParams {
         state_root:   root,
         height:       1,
         timestamp:    0,
         cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
     };
 
-    let stx = mock_signed_tx();
-    let txs = vec![stx];
-    let executor_resp = executor.exec(&params, &txs).unwrap();
-    let receipt = &executor_resp.receipts[0];
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let read = |executor: &ServiceExecutor<_, _, _>, params: &ExecutorParams| {
+        let request = TransactionRequest {
+            service_name: "test".to_owned(),
+            method:       "test_read".to_owned(),
+            payload:      r#"{ "key": "foo" }"#.to_owned(),
+        };
+        let res = executor.read(params, &caller, 1, &request).unwrap();
+        serde_json::from_str::<TestReadResponse>(&res.succeed_data)
+            .unwrap()
+            .value
+    };
+
+    // The write is scheduled at height 2
+    let mut stx = mock_signed_tx();
+    stx.raw.request.service_name = "test".to_owned();
+    stx.raw.request.method = "test_schedule".to_owned();
+    stx.raw.request.payload = r#"{ "key": "foo", "value": "bar", "extra": "" }"#.to_owned();
+    let resp = executor.exec(&params, &[stx.clone()]).unwrap();
+    assert_eq!(resp.receipts[0].response.response.code, 0);
+    // The cycles of the call are charged up front
+    assert_eq!(resp.receipts[0].cycles_used, 21000 + SCHEDULED_CALL_CYCLES);
+    assert!(resp.scheduled_receipts.is_empty());
+    assert_eq!(read(&executor, &params), "");
+    let id: String =
+        serde_json::from_str(&resp.receipts[0].response.response.succeed_data).unwrap();
 
+    // It runs at the start of the block, before any tx
+    params.height = 2;
+    let mut executor = ServiceExecutor::with_root(
+        resp.state_root,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+    let resp = executor.exec(&params, &[]).unwrap();
+    assert_eq!(resp.scheduled_receipts.len(), 1);
+    let receipt = &resp.scheduled_receipts[0];
     assert_eq!(receipt.response.response.code, 0);
-    let asset: Asset = serde_json::from_str(&receipt.response.response.succeed_data).unwrap();
-    assert_eq!(asset.name, "MutaToken2");
-    assert_eq!(asset.symbol, "MT2");
-    assert_eq!(asset.supply, 320_000_011);
+    assert_eq!(receipt.tx_hash.as_hex(), id);
+    assert_eq!(receipt.tx_hash, scheduled_call_id(2, 0));
+    assert_eq!(receipt.cycles_used, 21000);
+    assert_eq!(resp.all_cycles_used, 21000);
+    assert_eq!(read(&executor, &params), "bar");
+
+    // The calls of the height are dropped once they have run
+    let mut executor = ServiceExecutor::with_root(
+        resp.state_root,
+        Arc::clone(&db),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+    let resp = executor.exec(&params, &[]).unwrap();
+    assert!(resp.scheduled_receipts.is_empty());
+
+    // The calls at a height are capped
+    let txs = vec![stx; MAX_SCHEDULED_CALLS + 1];
+    let resp = executor.exec(&params, &txs).unwrap();
+    assert!(resp.receipts[..MAX_SCHEDULED_CALLS]
+        .iter()
+        .all(|receipt| receipt.response.response.code == 0));
+    assert_eq!(
+        resp.receipts[MAX_SCHEDULED_CALLS].response.response.code,
+        SCHEDULE_CALL_FAILED_CODE
+    );
+
+    params.height = 3;
+    let resp = executor.exec(&params, &[]).unwrap();
+    assert_eq!(resp.scheduled_receipts.len(), MAX_SCHEDULED_CALLS);
 }
 
 #[test]
-fn test_tx_hook() {
+fn test_verify_signature() {
     let toml_str = include_str!("./genesis_services.toml");
     let genesis: Genesis = toml::from_str(toml_str).unwrap();
 
@@ -137,54 +1219,52 @@ fn test_tx_hook() {
         height:       1,
         timestamp:    0,
         cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
     };
 
-    // no tx hook
+    let mut pubkey = VerifierPubkey {
+        service: "test".to_owned(),
+        pubkey:  Bytes::from("secret"),
+    };
     let mut stx = mock_signed_tx();
-    stx.raw.request.service_name = "test".to_owned();
-    stx.raw.request.method = "test_write".to_owned();
-    stx.raw.request.payload = r#"{
-        "key": "foo",
-        "value": "bar",
-        "extra": ""
-    }"#
-    .to_owned();
-    let txs = vec![stx.clone()];
-    let executor_resp = executor.exec(&params, &txs).unwrap();
-    let receipt = &executor_resp.receipts[0];
-    assert_eq!(receipt.response.response.code, 0);
-    assert_eq!(receipt.events.len(), 0);
+    stx.kind = PubkeyKind::Verifier;
+    stx.pubkey = pubkey.encode_fixed().unwrap();
+    stx.signature = Bytes::from("secret");
+    let (verified, cycles_used) = executor
+        .verify_signature(&params, &stx, &pubkey, std::u64::MAX)
+        .unwrap();
+    assert!(verified);
+    assert_eq!(cycles_used, 100_00);
 
-    // tx hook
-    stx.raw.request.payload = r#"{
-        "key": "foo",
-        "value": "bar",
-        "extra": "test_hook_before; test_hook_after"
-    }"#
-    .to_owned();
-    let txs = vec![stx.clone()];
-    let executor_resp = executor.exec(&params, &txs).unwrap();
-    let receipt = &executor_resp.receipts[0];
-    assert_eq!(receipt.response.response.code, 0);
-    assert_eq!(receipt.events.len(), 2);
-    assert_eq!(&receipt.events[0].data, "test_tx_hook_before invoked");
-    assert_eq!(&receipt.events[1].data, "test_tx_hook_after invoked");
+    let mut wrong_stx = stx.clone();
+    wrong_stx.signature = Bytes::from("wrong");
+    let (verified, _) = executor
+        .verify_signature(&params, &wrong_stx, &pubkey, std::u64::MAX)
+        .unwrap();
+    assert!(!verified);
 
-    // test_service_call_invoke_hook_only_once
-    stx.raw.request.method = "test_service_call_invoke_hook_only_once".to_owned();
-    stx.raw.request.payload = r#"{
-        "key": "foo",
-        "value": "bar",
-        "extra": "test_hook_before; test_hook_after"
-    }"#
-    .to_owned();
-    let txs = vec![stx];
-    let executor_resp = executor.exec(&params, &txs).unwrap();
-    let receipt = &executor_resp.receipts[0];
-    assert_eq!(receipt.response.response.code, 0);
-    assert_eq!(receipt.events.len(), 2);
-    assert_eq!(&receipt.events[0].data, "test_tx_hook_before invoked");
-    assert_eq!(&receipt.events[1].data, "test_tx_hook_after invoked");
+    // The witness is verified again when the tx is executed, within its cycles
+    let resp = executor.exec(&params, &[stx.clone(), wrong_stx]).unwrap();
+    assert_ne!(
+        resp.receipts[0].response.response.code,
+        INVALID_SIGNATURE_CODE
+    );
+    assert!(resp.receipts[0].cycles_used >= 100_00);
+    assert_eq!(
+        resp.receipts[1].response.response.code,
+        INVALID_SIGNATURE_CODE
+    );
+    assert_eq!(
+        resp.receipts[1].cycles_used,
+        100_00 + INVALID_SIGNATURE_CYCLES
+    );
+
+    pubkey.service = "nonexistent".to_owned();
+    stx.pubkey = pubkey.encode_fixed().unwrap();
+    let (verified, _) = executor
+        .verify_signature(&params, &stx, &pubkey, std::u64::MAX)
+        .unwrap();
+    assert!(!verified);
 }
 
 #[bench]
@@ -218,6 +1298,7 @@ fn bench_execute(b: &mut Bencher) {
             height:       1,
             timestamp:    0,
             cycles_limit: std::u64::MAX,
+            random_seed:  Hash::from_empty(),
         };
         let txs = txs.clone();
         executor.exec(&params, &txs).unwrap();
@@ -247,6 +1328,8 @@ fn mock_signed_tx() -> SignedTransaction {
                 .unwrap(),
         ),
         signature: BytesMut::from("").freeze(),
+        scheme: SignatureScheme::Secp256k1,
+        kind: PubkeyKind::Single,
     }
 }
 
@@ -273,6 +1356,109 @@ impl ServiceMapping for MockServiceMapping {
     }
 }
 
+struct MockFeeServiceMapping;
+
+impl ServiceMapping for MockFeeServiceMapping {
+    fn get_service<SDK: 'static + ServiceSDK>(
+        &self,
+        name: &str,
+        sdk: SDK,
+    ) -> ProtocolResult<Box<dyn Service>> {
+        MockServiceMapping {}.get_service(name, sdk)
+    }
+
+    fn list_service_name(&self) -> Vec<String> {
+        MockServiceMapping {}.list_service_name()
+    }
+
+    fn fee_hook(&self) -> Option<FeeHook> {
+        Some(FeeHook {
+            service: "asset".to_owned(),
+            method:  "charge_fee".to_owned(),
+        })
+    }
+}
+
+struct MockNonceServiceMapping;
+
+impl ServiceMapping for MockNonceServiceMapping {
+    fn get_service<SDK: 'static + ServiceSDK>(
+        &self,
+        name: &str,
+        sdk: SDK,
+    ) -> ProtocolResult<Box<dyn Service>> {
+        MockServiceMapping {}.get_service(name, sdk)
+    }
+
+    fn list_service_name(&self) -> Vec<String> {
+        MockServiceMapping {}.list_service_name()
+    }
+
+    fn nonce_mode(&self) -> NonceMode {
+        NonceMode::Account
+    }
+}
+
+struct MockVersionServiceMapping;
+
+impl ServiceMapping for MockVersionServiceMapping {
+    fn get_service<SDK: 'static + ServiceSDK>(
+        &self,
+        name: &str,
+        sdk: SDK,
+    ) -> ProtocolResult<Box<dyn Service>> {
+        MockServiceMapping {}.get_service(name, sdk)
+    }
+
+    fn list_service_name(&self) -> Vec<String> {
+        MockServiceMapping {}.list_service_name()
+    }
+
+    fn service_version(&self, name: &str, height: u64) -> u64 {
+        match (name, height) {
+            ("test", 0..=4) => 0,
+            ("test", 5) => 1,
+            ("test", _) => 2,
+            _ => 0,
+        }
+    }
+}
+
+struct MockRegistryServiceMapping;
+
+impl ServiceMapping for MockRegistryServiceMapping {
+    fn get_service<SDK: 'static + ServiceSDK>(
+        &self,
+        name: &str,
+        sdk: SDK,
+    ) -> ProtocolResult<Box<dyn Service>> {
+        MockServiceMapping {}.get_service(name, sdk)
+    }
+
+    fn list_service_name(&self) -> Vec<String> {
+        MockServiceMapping {}.list_service_name()
+    }
+
+    fn governance_service(&self) -> Option<String> {
+        Some("test".to_owned())
+    }
+}
+
+fn get_balance<E: Executor>(executor: &E, params: &ExecutorParams) -> u64 {
+    let caller = Address::from_hex("0xf8389d774afdad8755ef8e629e5a154fddc6325a").unwrap();
+    let request = TransactionRequest {
+        service_name: "asset".to_owned(),
+        method:       "get_balance".to_owned(),
+        payload:
+            r#"{"asset_id": "0xf56924db538e77bb5951eb5ff0d02b88983c49c45eea30e8ae3e7234b311436c", "user": "0xf8389d774afdad8755ef8e629e5a154fddc6325a"}"#
+                .to_owned(),
+    };
+    let res = executor.read(params, &caller, 1, &request).unwrap();
+    let resp: GetBalanceResponse = serde_json::from_str(&res.succeed_data).unwrap();
+
+    resp.balance
+}
+
 struct MockStorage;
 
 #[async_trait]
@@ -297,6 +1483,17 @@ impl Storage for MockStorage {
         unimplemented!()
     }
 
+    async fn commit_block_bundle(
+        &self,
+        _ctx: Context,
+        _: Block,
+        _: Proof,
+        _: Vec<SignedTransaction>,
+        _: Vec<Receipt>,
+    ) -> ProtocolResult<()> {
+        unimplemented!()
+    }
+
     async fn get_transaction_by_hash(
         &self,
         _ctx: Context,
@@ -2,6 +2,7 @@ use std::sync::Arc;
 
 use bytes::{Bytes, BytesMut};
 use cita_trie::MemoryDB;
+use serde::{Deserialize, Serialize};
 
 use asset::types::{Asset, CreateAssetPayload};
 use asset::AssetService;
@@ -12,12 +13,13 @@ use protocol::traits::{
     Executor, ExecutorParams, Service, ServiceMapping, ServiceResponse, ServiceSDK,
 };
 use protocol::types::{
-    Genesis, Hash, RawTransaction, ServiceContext, SignedTransaction, TransactionRequest,
+    Genesis, Hash, PubkeyKind, RawTransaction, ServiceContext, SignatureScheme, SignedTransaction,
+    TransactionRequest,
 };
 use protocol::ProtocolResult;
 
 use crate::executor::tests::MockStorage;
-use crate::executor::ServiceExecutor;
+use crate::executor::{ServiceExecutor, CALL_DEPTH_EXCEEDED_CODE, MAX_CALL_DEPTH};
 
 #[test]
 fn test_service_call_service() {
@@ -48,6 +50,7 @@ fn test_service_call_service() {
         height:       1,
         timestamp:    0,
         cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
     };
 
     let raw = RawTransaction {
@@ -71,6 +74,8 @@ fn test_service_call_service() {
                 .unwrap(),
         ),
         signature: BytesMut::from("").freeze(),
+        scheme: SignatureScheme::Secp256k1,
+        kind: PubkeyKind::Single,
     };
 
     let txs = vec![stx];
@@ -91,6 +96,227 @@ fn test_service_call_service() {
     assert_eq!(asset.supply, 320_000_011);
 }
 
+#[test]
+fn test_service_call_service_reverted() {
+    let memdb = Arc::new(MemoryDB::new(false));
+
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&memdb),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&memdb),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    let raw = RawTransaction {
+        chain_id:     Hash::from_empty(),
+        nonce:        Hash::from_empty(),
+        timeout:      0,
+        cycles_price: 1,
+        cycles_limit: 60_000,
+        request:      TransactionRequest {
+            service_name: "mock".to_owned(),
+            method:       "call_failed_write".to_owned(),
+            payload:      "".to_owned(),
+        },
+    };
+    let stx = SignedTransaction {
+        raw,
+        tx_hash: Hash::from_empty(),
+        pubkey: Bytes::from(
+            hex::decode("031288a6788678c25952eba8693b2f278f66e2187004b64ac09416d07f83f96d5b")
+                .unwrap(),
+        ),
+        signature: BytesMut::from("").freeze(),
+        scheme: SignatureScheme::Secp256k1,
+        kind: PubkeyKind::Single,
+    };
+
+    let executor_resp = executor.exec(&params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 0);
+    assert_eq!(receipt.events.len(), 0);
+
+    // The writes of the caller are kept, the ones of the failed callee are not
+    let resp: CallFailedWriteResponse =
+        serde_json::from_str(&receipt.response.response.succeed_data).unwrap();
+    assert_eq!(resp.code, 112);
+    assert_eq!(resp.outer, Some("written".to_owned()));
+    assert_eq!(resp.inner, None);
+}
+
+#[test]
+fn test_revert_to_checkpoint() {
+    let memdb = Arc::new(MemoryDB::new(false));
+
+    let toml_str = include_str!("./genesis_services.toml");
+    let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+    let root = ServiceExecutor::create_genesis(
+        genesis.services,
+        Arc::clone(&memdb),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let mut executor = ServiceExecutor::with_root(
+        root.clone(),
+        Arc::clone(&memdb),
+        Arc::new(MockStorage {}),
+        Arc::new(MockServiceMapping {}),
+    )
+    .unwrap();
+
+    let params = ExecutorParams {
+        state_root:   root,
+        height:       1,
+        timestamp:    0,
+        cycles_limit: std::u64::MAX,
+        random_seed:  Hash::from_empty(),
+    };
+
+    let raw = RawTransaction {
+        chain_id:     Hash::from_empty(),
+        nonce:        Hash::from_empty(),
+        timeout:      0,
+        cycles_price: 1,
+        cycles_limit: 60_000,
+        request:      TransactionRequest {
+            service_name: "mock".to_owned(),
+            method:       "call_reverted_write".to_owned(),
+            payload:      "".to_owned(),
+        },
+    };
+    let stx = SignedTransaction {
+        raw,
+        tx_hash: Hash::from_empty(),
+        pubkey: Bytes::from(
+            hex::decode("031288a6788678c25952eba8693b2f278f66e2187004b64ac09416d07f83f96d5b")
+                .unwrap(),
+        ),
+        signature: BytesMut::from("").freeze(),
+        scheme: SignatureScheme::Secp256k1,
+        kind: PubkeyKind::Single,
+    };
+
+    let executor_resp = executor.exec(&params, &[stx]).unwrap();
+    let receipt = &executor_resp.receipts[0];
+    assert_eq!(receipt.response.response.code, 0);
+
+    // Both the writes of the caller and of the succeeded callee are dropped
+    let resp: CallFailedWriteResponse =
+        serde_json::from_str(&receipt.response.response.succeed_data).unwrap();
+    assert_eq!(resp.code, 0);
+    assert_eq!(resp.outer, None);
+    assert_eq!(resp.inner, None);
+}
+
+#[test]
+fn test_service_call_depth() {
+    let resp = exec_on_default_stack("recurse");
+    assert_eq!(resp.code, 0);
+    let depth: u64 = serde_json::from_str(&resp.succeed_data).unwrap();
+    assert_eq!(depth, MAX_CALL_DEPTH - 1);
+}
+
+#[test]
+fn test_service_mutual_call_depth() {
+    let resp = exec_on_default_stack("ping");
+    assert_eq!(resp.code, CALL_DEPTH_EXCEEDED_CODE);
+    assert_eq!(
+        resp.error_message,
+        format!("call depth exceeds {}", MAX_CALL_DEPTH)
+    );
+}
+
+// Every level of the calls takes some native stack, the deepest call must not
+// overflow the default stack of a thread.
+fn exec_on_default_stack(method: &'static str) -> ServiceResponse<String> {
+    let handle = std::thread::Builder::new()
+        .spawn(move || {
+            let memdb = Arc::new(MemoryDB::new(false));
+
+            let toml_str = include_str!("./genesis_services.toml");
+            let genesis: Genesis = toml::from_str(toml_str).unwrap();
+
+            let root = ServiceExecutor::create_genesis(
+                genesis.services,
+                Arc::clone(&memdb),
+                Arc::new(MockStorage {}),
+                Arc::new(MockServiceMapping {}),
+            )
+            .unwrap();
+
+            let mut executor = ServiceExecutor::with_root(
+                root.clone(),
+                Arc::clone(&memdb),
+                Arc::new(MockStorage {}),
+                Arc::new(MockServiceMapping {}),
+            )
+            .unwrap();
+
+            let params = ExecutorParams {
+                state_root:   root,
+                height:       1,
+                timestamp:    0,
+                cycles_limit: std::u64::MAX,
+                random_seed:  Hash::from_empty(),
+            };
+
+            let raw = RawTransaction {
+                chain_id:     Hash::from_empty(),
+                nonce:        Hash::from_empty(),
+                timeout:      0,
+                cycles_price: 1,
+                cycles_limit: 60_000,
+                request:      TransactionRequest {
+                    service_name: "mock".to_owned(),
+                    method:       method.to_owned(),
+                    payload:      "".to_owned(),
+                },
+            };
+            let stx = SignedTransaction {
+                raw,
+                tx_hash: Hash::from_empty(),
+                pubkey: Bytes::from(
+                    hex::decode(
+                        "031288a6788678c25952eba8693b2f278f66e2187004b64ac09416d07f83f96d5b",
+                    )
+                    .unwrap(),
+                ),
+                signature: BytesMut::from("").freeze(),
+                scheme: SignatureScheme::Secp256k1,
+                kind: PubkeyKind::Single,
+            };
+
+            let executor_resp = executor.exec(&params, &[stx]).unwrap();
+            executor_resp.receipts[0].response.response.clone()
+        })
+        .unwrap();
+
+    handle.join().unwrap()
+}
+
 pub struct MockService<SDK> {
     sdk: SDK,
 }
@@ -119,6 +345,92 @@ impl<SDK: ServiceSDK> MockService<SDK> {
         ctx.emit_event("call create asset succeed".to_owned());
         ServiceResponse::<Asset>::from_succeed(asset)
     }
+
+    #[write]
+    fn write_then_fail(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
+        self.sdk.set_value("inner".to_owned(), "written".to_owned());
+        ctx.emit_event("write then fail".to_owned());
+        ServiceResponse::<()>::from_error(112, "write then fail".to_owned())
+    }
+
+    #[write]
+    fn call_failed_write(
+        &mut self,
+        ctx: ServiceContext,
+    ) -> ServiceResponse<CallFailedWriteResponse> {
+        self.sdk.set_value("outer".to_owned(), "written".to_owned());
+
+        let ret = self.sdk.write(&ctx, None, "mock", "write_then_fail", "");
+
+        ServiceResponse::<CallFailedWriteResponse>::from_succeed(CallFailedWriteResponse {
+            code:  ret.code,
+            outer: self.sdk.get_value(&"outer".to_owned()),
+            inner: self.sdk.get_value(&"inner".to_owned()),
+        })
+    }
+
+    #[write]
+    fn write_inner(&mut self, _ctx: ServiceContext) -> ServiceResponse<()> {
+        self.sdk.set_value("inner".to_owned(), "written".to_owned());
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[write]
+    fn call_reverted_write(
+        &mut self,
+        ctx: ServiceContext,
+    ) -> ServiceResponse<CallFailedWriteResponse> {
+        self.sdk.checkpoint();
+        self.sdk.set_value("outer".to_owned(), "written".to_owned());
+
+        let ret = self.sdk.write(&ctx, None, "mock", "write_inner", "");
+        self.sdk.revert_to_checkpoint();
+
+        ServiceResponse::<CallFailedWriteResponse>::from_succeed(CallFailedWriteResponse {
+            code:  ret.code,
+            outer: self.sdk.get_value(&"outer".to_owned()),
+            inner: self.sdk.get_value(&"inner".to_owned()),
+        })
+    }
+
+    // Calls `pong`, which calls back, until the depth limit is hit.
+    #[write]
+    fn ping(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
+        let ret = self.sdk.write(&ctx, None, "mock", "pong", "");
+        if ret.is_error() {
+            return ServiceResponse::<()>::from_error(ret.code, ret.error_message);
+        }
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[write]
+    fn pong(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
+        let ret = self.sdk.write(&ctx, None, "mock", "ping", "");
+        if ret.is_error() {
+            return ServiceResponse::<()>::from_error(ret.code, ret.error_message);
+        }
+        ServiceResponse::<()>::from_succeed(())
+    }
+
+    #[write]
+    fn recurse(&mut self, ctx: ServiceContext) -> ServiceResponse<u64> {
+        let ret = self.sdk.write(&ctx, None, "mock", "recurse", "");
+
+        if ret.code == CALL_DEPTH_EXCEEDED_CODE {
+            ServiceResponse::<u64>::from_succeed(ctx.get_call_depth())
+        } else if ret.is_error() {
+            ServiceResponse::<u64>::from_error(ret.code, ret.error_message)
+        } else {
+            ServiceResponse::<u64>::from_succeed(serde_json::from_str(&ret.succeed_data).unwrap())
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
+pub struct CallFailedWriteResponse {
+    pub code:  u64,
+    pub outer: Option<String>,
+    pub inner: Option<String>,
 }
 
 pub struct MockServiceMapping;
@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 
-use binding_macro::{cycles, service, tx_hook_after, tx_hook_before};
-use protocol::traits::{ExecutorParams, ServiceResponse, ServiceSDK};
-use protocol::types::ServiceContext;
+use binding_macro::{cycles, migrate, service, tx_hook_after, tx_hook_before};
+use protocol::traits::{ExecutorParams, ServiceRegistration, ServiceResponse, ServiceSDK};
+use protocol::types::{ServiceContext, VerifyPayload};
 
 pub struct TestService<SDK> {
     sdk: SDK,
@@ -70,13 +70,99 @@ impl<SDK: ServiceSDK> TestService<SDK> {
         ServiceResponse::<TestWriteResponse>::from_succeed(TestWriteResponse {})
     }
 
+    #[cycles(210_00)]
+    #[write]
+    fn test_map_insert(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TestWritePayload,
+    ) -> ServiceResponse<TestWriteResponse> {
+        let mut map = self.sdk.alloc_or_recover_map::<String, String>("test_map");
+        map.insert(payload.key, payload.value);
+        ServiceResponse::<TestWriteResponse>::from_succeed(TestWriteResponse {})
+    }
+
+    #[cycles(210_00)]
+    #[write]
+    fn test_map_remove(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TestReadPayload,
+    ) -> ServiceResponse<TestWriteResponse> {
+        let mut map = self.sdk.alloc_or_recover_map::<String, String>("test_map");
+        map.remove(&payload.key);
+        ServiceResponse::<TestWriteResponse>::from_succeed(TestWriteResponse {})
+    }
+
+    #[write]
+    fn test_out_of_cycles(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TestWritePayload,
+    ) -> ServiceResponse<TestWriteResponse> {
+        self.sdk.set_value(payload.key, payload.value);
+        ctx.sub_cycles(std::u64::MAX);
+        ServiceResponse::<TestWriteResponse>::from_succeed(TestWriteResponse {})
+    }
+
+    #[write]
+    fn test_panic(
+        &mut self,
+        _ctx: ServiceContext,
+        payload: TestWritePayload,
+    ) -> ServiceResponse<TestWriteResponse> {
+        self.sdk.set_value(payload.key, payload.value);
+        panic!("test panic");
+    }
+
+    #[write]
+    fn test_register_service(
+        &mut self,
+        ctx: ServiceContext,
+        payload: ServiceRegistration,
+    ) -> ServiceResponse<String> {
+        self.sdk.register_service(&ctx, payload)
+    }
+
+    // Schedule the write of the payload at the next height
+    #[cycles(210_00)]
+    #[write]
+    fn test_schedule(
+        &mut self,
+        ctx: ServiceContext,
+        payload: TestWritePayload,
+    ) -> ServiceResponse<String> {
+        let payload = serde_json::to_string(&payload).unwrap();
+        let height = ctx.get_current_height() + 1;
+        self.sdk
+            .schedule(&ctx, height, "test", "test_write", &payload)
+    }
+
+    // A witness is valid if it's the pubkey itself
+    #[cycles(100_00)]
+    #[read]
+    fn verify(&self, ctx: ServiceContext, payload: VerifyPayload) -> ServiceResponse<String> {
+        if payload.witness.as_string() != payload.pubkey.as_string() {
+            return ServiceResponse::<String>::from_error(113, "wrong witness".to_owned());
+        }
+        ServiceResponse::<String>::from_succeed(String::new())
+    }
+
     #[tx_hook_before]
-    fn test_tx_hook_before(&mut self, ctx: ServiceContext) {
+    fn test_tx_hook_before(&mut self, ctx: ServiceContext) -> ServiceResponse<()> {
         if ctx.get_service_name() == "test"
             && ctx.get_payload().to_owned().contains("test_hook_before")
         {
             ctx.emit_event("test_tx_hook_before invoked".to_owned());
         }
+
+        if ctx.get_service_name() == "test"
+            && ctx.get_payload().to_owned().contains("test_hook_reject")
+        {
+            return ServiceResponse::<()>::from_error(111, "rejected by tx hook".to_owned());
+        }
+
+        ServiceResponse::<()>::from_succeed(())
     }
 
     #[tx_hook_after]
@@ -86,5 +172,30 @@ impl<SDK: ServiceSDK> TestService<SDK> {
         {
             ctx.emit_event("test_tx_hook_after invoked".to_owned());
         }
+
+        if ctx.get_service_name() == "test"
+            && ctx
+                .get_payload()
+                .to_owned()
+                .contains("test_hook_after_write")
+        {
+            self.sdk
+                .set_value("hook_after".to_owned(), "invoked".to_owned());
+        }
+    }
+
+    #[migrate(from = 0)]
+    fn test_migrate_from_v0(&mut self, _params: &ExecutorParams) {
+        let migrated: String = self
+            .sdk
+            .get_value(&"migrated".to_owned())
+            .unwrap_or_default();
+        self.sdk.set_value("migrated".to_owned(), migrated + "v1");
+    }
+
+    #[migrate(from = 1)]
+    fn test_migrate_from_v1(&mut self, _params: &ExecutorParams) {
+        self.sdk.set_value("migrated".to_owned(), "v2".to_owned());
+        panic!("migrate from v1");
     }
 }
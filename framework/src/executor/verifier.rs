@@ -0,0 +1,146 @@
+use std::cmp;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use cita_trie::DB as TrieDB;
+
+use protocol::traits::{
+    Context, ExecutorParams, ServiceMapping, ServiceResponse, SignatureVerifier, Storage,
+};
+use protocol::types::{
+    Hex, ServiceContext, SignedTransaction, TransactionRequest, VerifierPubkey, VerifyPayload,
+    VERIFY_METHOD,
+};
+use protocol::ProtocolResult;
+
+use crate::executor::state_reader::StateReader;
+use crate::executor::{ExecType, ExecutorError, ServiceExecutor, INVALID_SIGNATURE_CODE};
+
+// The verification in the mempool is bounded but not charged, a tx is verified
+// again when it's executed, within its own cycles. So a verifier service has
+// to fit in it.
+const VERIFY_CYCLES_LIMIT: u64 = 100_000;
+
+impl<S: 'static + Storage, DB: 'static + TrieDB, Mapping: 'static + ServiceMapping>
+    ServiceExecutor<S, DB, Mapping>
+{
+    // Call the `verify` method of the verifier service with the tx's witness,
+    // whose caller is the tx's sender. The witness is valid if the call
+    // succeeds. Returns the cycles used by the call as well.
+    pub fn verify_signature(
+        &self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+        pubkey: &VerifierPubkey,
+        cycles_limit: u64,
+    ) -> ProtocolResult<(bool, u64)> {
+        let caller = stx.sender()?;
+        let payload = VerifyPayload {
+            tx_hash: stx.tx_hash.clone(),
+            pubkey:  Hex::from_string("0x".to_owned() + &hex::encode(&pubkey.pubkey))?,
+            witness: Hex::from_string("0x".to_owned() + &hex::encode(&stx.signature))?,
+        };
+        let request = TransactionRequest {
+            service_name: pubkey.service.clone(),
+            method:       VERIFY_METHOD.to_owned(),
+            payload:      serde_json::to_string(&payload).map_err(ExecutorError::from)?,
+        };
+
+        let context = self.get_context(
+            Some(stx.tx_hash.clone()),
+            None,
+            &caller,
+            0,
+            cycles_limit,
+            params,
+            &request,
+        )?;
+        // A missing service panics as well
+        let verified = match panic::catch_unwind(AssertUnwindSafe(|| {
+            self.call(context.clone(), ExecType::Read)
+        })) {
+            Ok(resp) => !resp.is_error(),
+            Err(_) => false,
+        };
+        Ok((verified, context.get_cycles_used()))
+    }
+
+    // The witness of a tx verified by a service is checked against the state
+    // it's executed on, its cycles are taken from the tx's ones.
+    pub(crate) fn verify_tx_signature(
+        &self,
+        params: &ExecutorParams,
+        stx: &SignedTransaction,
+        context: &ServiceContext,
+    ) -> ProtocolResult<Option<ServiceResponse<String>>> {
+        let invalid =
+            || ServiceResponse::from_error(INVALID_SIGNATURE_CODE, "invalid signature".to_owned());
+        let pubkey = match stx.verifier_pubkey() {
+            Ok(Some(pubkey)) => pubkey,
+            Ok(None) => return Ok(None),
+            Err(_) => return Ok(Some(invalid())),
+        };
+
+        let cycles_limit = cmp::min(context.get_cycles_limit(), VERIFY_CYCLES_LIMIT);
+        let (verified, cycles_used) = self.verify_signature(params, stx, &pubkey, cycles_limit)?;
+        context.sub_cycles(cycles_used);
+
+        if verified {
+            Ok(None)
+        } else {
+            Ok(Some(invalid()))
+        }
+    }
+}
+
+pub struct DefaultSignatureVerifier<S, DB, Mapping> {
+    storage: Arc<S>,
+    reader:  StateReader<S, DB, Mapping>,
+}
+
+impl<S, DB, Mapping> DefaultSignatureVerifier<S, DB, Mapping>
+where
+    S: 'static + Storage,
+    DB: 'static + TrieDB,
+    Mapping: 'static + ServiceMapping,
+{
+    pub fn new(storage: Arc<S>, trie_db: Arc<DB>, service_mapping: Arc<Mapping>) -> Self {
+        Self {
+            reader: StateReader::new(Arc::clone(&storage), trie_db, service_mapping),
+            storage,
+        }
+    }
+}
+
+#[async_trait]
+impl<S, DB, Mapping> SignatureVerifier for DefaultSignatureVerifier<S, DB, Mapping>
+where
+    S: 'static + Storage,
+    DB: 'static + TrieDB,
+    Mapping: 'static + ServiceMapping,
+{
+    async fn verify_signature(
+        &self,
+        ctx: Context,
+        stx: SignedTransaction,
+        pubkey: VerifierPubkey,
+    ) -> ProtocolResult<bool> {
+        let block = self.storage.get_latest_block(ctx).await?;
+        let params = ExecutorParams {
+            state_root:   block.header.state_root.clone(),
+            height:       block.header.height,
+            timestamp:    block.header.timestamp,
+            cycles_limit: VERIFY_CYCLES_LIMIT,
+            random_seed:  block.header.random_seed(),
+        };
+
+        self.reader
+            .read(block.header.state_root, move |executor| {
+                let (verified, _) =
+                    executor.verify_signature(&params, &stx, &pubkey, VERIFY_CYCLES_LIMIT)?;
+                Ok(verified)
+            })
+            .await
+    }
+}
@@ -73,6 +73,9 @@ pub struct BlockHeader {
 
     #[prost(uint64, tag = "15")]
     pub exec_height: u64,
+
+    #[prost(bytes, tag = "16")]
+    pub seed_signature: Vec<u8>,
 }
 
 #[derive(Clone, Message)]
@@ -201,6 +204,7 @@ impl From<block::BlockHeader> for BlockHeader {
             receipt_root,
             cycles_used: block_header.cycles_used,
             proposer,
+            seed_signature: block_header.seed_signature.to_vec(),
             proof,
             validator_version: block_header.validator_version,
             validators,
@@ -252,6 +256,7 @@ impl TryFrom<BlockHeader> for block::BlockHeader {
             receipt_root,
             cycles_used: block_header.cycles_used,
             proposer: protocol_primitive::Address::try_from(proposer)?,
+            seed_signature: Bytes::from(block_header.seed_signature),
             proof: block::Proof::try_from(proof)?,
             validator_version: block_header.validator_version,
             validators,
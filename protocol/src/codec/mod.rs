@@ -78,6 +78,13 @@ pub enum CodecError {
     #[display(fmt = "invalid contract type {}", _0)]
     InvalidContractType(i32),
 
+    #[display(fmt = "invalid signature scheme {}", _0)]
+    InvalidSignatureScheme(u32),
+
+    #[display(fmt = "invalid pubkey kind {}", _0)]
+    #[from(ignore)]
+    InvalidPubkeyKind(u32),
+
     #[display(fmt = "wrong bytes length: {{ expect: {}, got: {} }}", expect, real)]
     WrongBytesLength { expect: usize, real: usize },
 
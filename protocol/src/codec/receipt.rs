@@ -35,6 +35,12 @@ pub struct Receipt {
 
     #[prost(message, tag = "6")]
     pub response: Option<ReceiptResponse>,
+
+    #[prost(uint64, tag = "7")]
+    pub cycles_refunded: u64,
+
+    #[prost(message, repeated, tag = "8")]
+    pub cycles_breakdown: Vec<CyclesRecord>,
 }
 
 #[derive(Clone, Message)]
@@ -62,6 +68,27 @@ pub struct Event {
 
     #[prost(bytes, tag = "2")]
     pub data: Vec<u8>,
+
+    #[prost(bytes, tag = "3")]
+    pub name: Vec<u8>,
+
+    #[prost(bytes, repeated, tag = "4")]
+    pub topics: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, Message)]
+pub struct CyclesRecord {
+    #[prost(bytes, tag = "1")]
+    pub service: Vec<u8>,
+
+    #[prost(bytes, tag = "2")]
+    pub method: Vec<u8>,
+
+    #[prost(uint64, tag = "3")]
+    pub call_depth: u64,
+
+    #[prost(uint64, tag = "4")]
+    pub cycles: u64,
 }
 
 // #################
@@ -108,6 +135,11 @@ impl From<receipt::Receipt> for Receipt {
         let state_root = Some(Hash::from(receipt.state_root));
         let tx_hash = Some(Hash::from(receipt.tx_hash));
         let events = receipt.events.into_iter().map(Event::from).collect();
+        let cycles_breakdown = receipt
+            .cycles_breakdown
+            .into_iter()
+            .map(CyclesRecord::from)
+            .collect();
         let response = Some(ReceiptResponse::from(receipt.response));
 
         Receipt {
@@ -117,6 +149,8 @@ impl From<receipt::Receipt> for Receipt {
             cycles_used: receipt.cycles_used,
             events,
             response,
+            cycles_refunded: receipt.cycles_refunded,
+            cycles_breakdown,
         }
     }
 }
@@ -133,12 +167,19 @@ impl TryFrom<Receipt> for receipt::Receipt {
             .into_iter()
             .map(protocol_receipt::Event::try_from)
             .collect::<Result<Vec<protocol_receipt::Event>, ProtocolError>>()?;
+        let cycles_breakdown = receipt
+            .cycles_breakdown
+            .into_iter()
+            .map(protocol_receipt::CyclesRecord::try_from)
+            .collect::<Result<Vec<protocol_receipt::CyclesRecord>, ProtocolError>>()?;
 
         let receipt = receipt::Receipt {
             state_root: protocol_primitive::Hash::try_from(state_root)?,
             height: receipt.height,
             tx_hash: protocol_primitive::Hash::try_from(tx_hash)?,
             cycles_used: receipt.cycles_used,
+            cycles_refunded: receipt.cycles_refunded,
+            cycles_breakdown,
             events,
             response: receipt::ReceiptResponse::try_from(response)?,
         };
@@ -153,6 +194,12 @@ impl From<receipt::Event> for Event {
         Event {
             service: event.service.as_bytes().to_vec(),
             data:    event.data.as_bytes().to_vec(),
+            name:    event.name.as_bytes().to_vec(),
+            topics:  event
+                .topics
+                .iter()
+                .map(|topic| topic.as_bytes().to_vec())
+                .collect(),
         }
     }
 }
@@ -161,9 +208,42 @@ impl TryFrom<Event> for receipt::Event {
     type Error = ProtocolError;
 
     fn try_from(event: Event) -> Result<receipt::Event, Self::Error> {
+        let topics = event
+            .topics
+            .into_iter()
+            .map(|topic| String::from_utf8(topic).map_err(CodecError::FromStringUtf8))
+            .collect::<Result<Vec<_>, _>>()?;
+
         Ok(receipt::Event {
             service: String::from_utf8(event.service).map_err(CodecError::FromStringUtf8)?,
-            data:    String::from_utf8(event.data).map_err(CodecError::FromStringUtf8)?,
+            name: String::from_utf8(event.name).map_err(CodecError::FromStringUtf8)?,
+            topics,
+            data: String::from_utf8(event.data).map_err(CodecError::FromStringUtf8)?,
+        })
+    }
+}
+
+// CyclesRecord
+impl From<receipt::CyclesRecord> for CyclesRecord {
+    fn from(record: receipt::CyclesRecord) -> CyclesRecord {
+        CyclesRecord {
+            service:    record.service.as_bytes().to_vec(),
+            method:     record.method.as_bytes().to_vec(),
+            call_depth: record.call_depth,
+            cycles:     record.cycles,
+        }
+    }
+}
+
+impl TryFrom<CyclesRecord> for receipt::CyclesRecord {
+    type Error = ProtocolError;
+
+    fn try_from(record: CyclesRecord) -> Result<receipt::CyclesRecord, Self::Error> {
+        Ok(receipt::CyclesRecord {
+            service:    String::from_utf8(record.service).map_err(CodecError::FromStringUtf8)?,
+            method:     String::from_utf8(record.method).map_err(CodecError::FromStringUtf8)?,
+            call_depth: record.call_depth,
+            cycles:     record.cycles,
         })
     }
 }
@@ -56,6 +56,12 @@ pub struct SignedTransaction {
 
     #[prost(bytes, tag = "4")]
     pub signature: Vec<u8>,
+
+    #[prost(uint32, tag = "5")]
+    pub scheme: u32,
+
+    #[prost(uint32, tag = "6")]
+    pub kind: u32,
 }
 
 // #################
@@ -141,6 +147,8 @@ impl From<transaction::SignedTransaction> for SignedTransaction {
             tx_hash:   Some(tx_hash),
             pubkey:    stx.pubkey.to_vec(),
             signature: stx.signature.to_vec(),
+            scheme:    u32::from(stx.scheme.tag()),
+            kind:      u32::from(stx.kind.tag()),
         }
     }
 }
@@ -151,12 +159,17 @@ impl TryFrom<SignedTransaction> for transaction::SignedTransaction {
     fn try_from(stx: SignedTransaction) -> Result<transaction::SignedTransaction, Self::Error> {
         let raw = field!(stx.raw, "SignedTransaction", "raw")?;
         let tx_hash = field!(stx.tx_hash, "SignedTransaction", "tx_hash")?;
+        let scheme =
+            u8::try_from(stx.scheme).map_err(|_| CodecError::InvalidSignatureScheme(stx.scheme))?;
+        let kind = u8::try_from(stx.kind).map_err(|_| CodecError::InvalidPubkeyKind(stx.kind))?;
 
         let stx = transaction::SignedTransaction {
             raw:       transaction::RawTransaction::try_from(raw)?,
             tx_hash:   protocol_primitive::Hash::try_from(tx_hash)?,
             pubkey:    Bytes::from(stx.pubkey),
             signature: Bytes::from(stx.signature),
+            scheme:    transaction::SignatureScheme::from_tag(scheme)?,
+            kind:      transaction::PubkeyKind::from_tag(kind)?,
         };
 
         Ok(stx)
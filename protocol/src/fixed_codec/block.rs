@@ -0,0 +1,75 @@
+use crate::fixed_codec::{FixedCodec, FixedCodecError};
+use crate::types::block::BlockHeader;
+use crate::ProtocolResult;
+
+// The seed signature comes last, a header stored before it was added has none.
+// A header without one is encoded as before, so that the hash of a block
+// stored by an older node doesn't change.
+impl rlp::Encodable for BlockHeader {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let len = if self.seed_signature.is_empty() {
+            15
+        } else {
+            16
+        };
+
+        s.begin_list(len)
+            .append(&self.chain_id)
+            .append(&self.height)
+            .append(&self.exec_height)
+            .append(&self.pre_hash)
+            .append(&self.timestamp)
+            .append_list(&self.logs_bloom)
+            .append(&self.order_root)
+            .append_list(&self.confirm_root)
+            .append(&self.state_root)
+            .append_list(&self.receipt_root)
+            .append_list(&self.cycles_used)
+            .append(&self.proposer)
+            .append(&self.proof)
+            .append(&self.validator_version)
+            .append_list(&self.validators);
+        if !self.seed_signature.is_empty() {
+            s.append(&self.seed_signature.to_vec());
+        }
+    }
+}
+
+impl rlp::Decodable for BlockHeader {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let seed_signature = match r.item_count()? {
+            15 => bytes::Bytes::new(),
+            16 => bytes::Bytes::from(r.val_at::<Vec<u8>>(15)?),
+            _ => return Err(rlp::DecoderError::RlpIncorrectListLen),
+        };
+
+        Ok(BlockHeader {
+            chain_id: r.val_at(0)?,
+            height: r.val_at(1)?,
+            exec_height: r.val_at(2)?,
+            pre_hash: r.val_at(3)?,
+            timestamp: r.val_at(4)?,
+            logs_bloom: r.list_at(5)?,
+            order_root: r.val_at(6)?,
+            confirm_root: r.list_at(7)?,
+            state_root: r.val_at(8)?,
+            receipt_root: r.list_at(9)?,
+            cycles_used: r.list_at(10)?,
+            proposer: r.val_at(11)?,
+            proof: r.val_at(12)?,
+            validator_version: r.val_at(13)?,
+            validators: r.list_at(14)?,
+            seed_signature,
+        })
+    }
+}
+
+impl FixedCodec for BlockHeader {
+    fn encode_fixed(&self) -> ProtocolResult<bytes::Bytes> {
+        Ok(bytes::Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: bytes::Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
@@ -1,3 +1,4 @@
+pub mod block;
 pub mod primitive;
 pub mod receipt;
 #[cfg(test)]
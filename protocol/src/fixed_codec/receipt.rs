@@ -1,6 +1,6 @@
 use crate::fixed_codec::{FixedCodec, FixedCodecError};
 use crate::traits::ServiceResponse;
-use crate::types::receipt::ReceiptResponse;
+use crate::types::receipt::{Event, Receipt, ReceiptResponse};
 use crate::ProtocolResult;
 
 impl rlp::Encodable for ReceiptResponse {
@@ -47,3 +47,89 @@ impl FixedCodec for ReceiptResponse {
         Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
     }
 }
+
+// The name and the topics come after the data, an event stored before they
+// were added has neither.
+impl rlp::Encodable for Event {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(4)
+            .append(&self.service)
+            .append(&self.data)
+            .append(&self.name)
+            .append_list::<String, _>(&self.topics);
+    }
+}
+
+impl rlp::Decodable for Event {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let (name, topics) = match r.item_count()? {
+            2 => (String::new(), vec![]),
+            4 => (r.at(2)?.as_val()?, r.at(3)?.as_list()?),
+            _ => return Err(rlp::DecoderError::RlpIncorrectListLen),
+        };
+
+        Ok(Event {
+            service: r.at(0)?.as_val()?,
+            name,
+            topics,
+            data: r.at(1)?.as_val()?,
+        })
+    }
+}
+
+impl FixedCodec for Event {
+    fn encode_fixed(&self) -> ProtocolResult<bytes::Bytes> {
+        Ok(bytes::Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: bytes::Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+// The refund and the breakdown come last, a receipt stored before they were
+// added has neither.
+impl rlp::Encodable for Receipt {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(8)
+            .append(&self.state_root)
+            .append(&self.height)
+            .append(&self.tx_hash)
+            .append(&self.cycles_used)
+            .append_list(&self.events)
+            .append(&self.response)
+            .append(&self.cycles_refunded)
+            .append_list(&self.cycles_breakdown);
+    }
+}
+
+impl rlp::Decodable for Receipt {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let (cycles_refunded, cycles_breakdown) = match r.item_count()? {
+            6 => (0, vec![]),
+            8 => (r.val_at(6)?, r.list_at(7)?),
+            _ => return Err(rlp::DecoderError::RlpIncorrectListLen),
+        };
+
+        Ok(Receipt {
+            state_root: r.val_at(0)?,
+            height: r.val_at(1)?,
+            tx_hash: r.val_at(2)?,
+            cycles_used: r.val_at(3)?,
+            events: r.list_at(4)?,
+            response: r.val_at(5)?,
+            cycles_refunded,
+            cycles_breakdown,
+        })
+    }
+}
+
+impl FixedCodec for Receipt {
+    fn encode_fixed(&self) -> ProtocolResult<bytes::Bytes> {
+        Ok(bytes::Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: bytes::Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
@@ -73,6 +73,106 @@ fn test_fixed_codec() {
     test_eq!(receipt, Receipt, mock_receipt);
 }
 
+#[test]
+fn test_legacy_event() {
+    let mut s = rlp::RlpStream::new_list(2);
+    s.append(&"mock-event".to_owned())
+        .append(&"mock-data".to_owned());
+
+    let event = types::Event::decode_fixed(Bytes::from(s.out())).unwrap();
+    assert_eq!(event.service, "mock-event");
+    assert_eq!(event.data, "mock-data");
+    assert!(event.name.is_empty());
+    assert!(event.topics.is_empty());
+
+    test_eq!(receipt, Event, mock_event);
+}
+
+#[test]
+fn test_legacy_receipt() {
+    let receipt = mock_receipt();
+    let mut s = rlp::RlpStream::new_list(6);
+    s.append(&receipt.state_root)
+        .append(&receipt.height)
+        .append(&receipt.tx_hash)
+        .append(&receipt.cycles_used)
+        .append_list(&receipt.events)
+        .append(&receipt.response);
+
+    let decoded = types::Receipt::decode_fixed(Bytes::from(s.out())).unwrap();
+    assert_eq!(decoded.cycles_refunded, 0);
+    assert!(decoded.cycles_breakdown.is_empty());
+    assert_eq!(decoded.events, receipt.events);
+    assert_eq!(decoded.cycles_used, receipt.cycles_used);
+}
+
+#[test]
+fn test_legacy_block_header() {
+    let header = mock_block_header();
+    let mut s = rlp::RlpStream::new_list(15);
+    s.append(&header.chain_id)
+        .append(&header.height)
+        .append(&header.exec_height)
+        .append(&header.pre_hash)
+        .append(&header.timestamp)
+        .append_list(&header.logs_bloom)
+        .append(&header.order_root)
+        .append_list(&header.confirm_root)
+        .append(&header.state_root)
+        .append_list(&header.receipt_root)
+        .append_list(&header.cycles_used)
+        .append(&header.proposer)
+        .append(&header.proof)
+        .append(&header.validator_version)
+        .append_list(&header.validators);
+    let legacy_bytes = Bytes::from(s.out());
+
+    let decoded = types::BlockHeader::decode_fixed(legacy_bytes.clone()).unwrap();
+    assert!(decoded.seed_signature.is_empty());
+    assert_eq!(decoded, header);
+    // The hash of a legacy block doesn't change
+    assert_eq!(decoded.encode_fixed().unwrap(), legacy_bytes);
+
+    let mut header = mock_block_header();
+    header.seed_signature = get_random_bytes(96);
+    let decoded = types::BlockHeader::decode_fixed(header.encode_fixed().unwrap()).unwrap();
+    assert_eq!(decoded, header);
+}
+
+#[test]
+fn test_legacy_signed_tx() {
+    let stx = mock_sign_tx();
+    let mut s = rlp::RlpStream::new_list(4);
+    s.append(&stx.raw)
+        .append(&stx.tx_hash)
+        .append(&stx.pubkey.to_vec())
+        .append(&stx.signature.to_vec());
+
+    let decoded = types::SignedTransaction::decode_fixed(Bytes::from(s.out())).unwrap();
+    assert_eq!(decoded.scheme, types::SignatureScheme::Secp256k1);
+    assert_eq!(decoded.kind, types::PubkeyKind::Single);
+    assert_eq!(decoded.raw, stx.raw);
+    assert_eq!(decoded.pubkey, stx.pubkey);
+    assert_eq!(decoded.signature, stx.signature);
+
+    // A tx stored before the kind was added is a single key one, whatever its
+    // pubkey looks like
+    let multisig = types::MultiSigPubkey {
+        threshold: 1,
+        pubkeys:   vec![stx.pubkey.clone()],
+    };
+    let mut s = rlp::RlpStream::new_list(5);
+    s.append(&stx.raw)
+        .append(&stx.tx_hash)
+        .append(&multisig.encode_fixed().unwrap().to_vec())
+        .append(&stx.signature.to_vec())
+        .append(&types::SignatureScheme::Sm2);
+
+    let decoded = types::SignedTransaction::decode_fixed(Bytes::from(s.out())).unwrap();
+    assert_eq!(decoded.scheme, types::SignatureScheme::Sm2);
+    assert_eq!(decoded.kind, types::PubkeyKind::Single);
+}
+
 #[test]
 fn test_signed_tx_serialize_size() {
     let txs: Vec<Bytes> = (0..50_000)
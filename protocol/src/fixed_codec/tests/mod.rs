@@ -6,7 +6,7 @@ use rand::random;
 use crate::traits::ServiceResponse;
 use crate::types::block::{Block, BlockHeader, Pill, Proof, Validator};
 use crate::types::primitive::{Address, Hash, MerkleRoot};
-use crate::types::receipt::{Event, Receipt, ReceiptResponse};
+use crate::types::receipt::{CyclesRecord, Event, Receipt, ReceiptResponse};
 use crate::types::transaction::{RawTransaction, SignedTransaction, TransactionRequest};
 
 // #####################
@@ -44,18 +44,31 @@ pub fn mock_receipt_response() -> ReceiptResponse {
 
 pub fn mock_receipt() -> Receipt {
     Receipt {
-        state_root:  mock_merkle_root(),
-        height:      13,
-        tx_hash:     mock_hash(),
-        cycles_used: 100,
-        events:      vec![mock_event()],
-        response:    mock_receipt_response(),
+        state_root:       mock_merkle_root(),
+        height:           13,
+        tx_hash:          mock_hash(),
+        cycles_used:      100,
+        cycles_refunded:  10,
+        cycles_breakdown: vec![mock_cycles_record()],
+        events:           vec![mock_event()],
+        response:         mock_receipt_response(),
+    }
+}
+
+pub fn mock_cycles_record() -> CyclesRecord {
+    CyclesRecord {
+        service:    "mock-service".to_owned(),
+        method:     "mock-method".to_owned(),
+        call_depth: 0,
+        cycles:     110,
     }
 }
 
 pub fn mock_event() -> Event {
     Event {
         service: "mock-event".to_owned(),
+        name:    "mock-name".to_owned(),
+        topics:  vec!["mock-topic".to_owned()],
         data:    "mock-data".to_owned(),
     }
 }
@@ -89,6 +102,8 @@ pub fn mock_sign_tx() -> SignedTransaction {
         tx_hash:   mock_hash(),
         pubkey:    Default::default(),
         signature: Default::default(),
+        scheme:    Default::default(),
+        kind:      Default::default(),
     }
 }
 
@@ -128,6 +143,7 @@ pub fn mock_block_header() -> BlockHeader {
         receipt_root:      vec![mock_hash(), mock_hash()],
         cycles_used:       vec![999_999],
         proposer:          mock_address(),
+        seed_signature:    Default::default(),
         proof:             mock_proof(),
         validator_version: 1,
         validators:        vec![
@@ -1,7 +1,10 @@
 use bytes::BytesMut;
 
 use crate::fixed_codec::{FixedCodec, FixedCodecError};
-use crate::types::{Hash, RawTransaction, TransactionRequest};
+use crate::types::{
+    Hash, MultiSigPubkey, MultiSigWitness, PubkeyKind, RawTransaction, SignatureScheme,
+    SignedTransaction, TransactionRequest, VerifierPubkey,
+};
 use crate::ProtocolResult;
 
 impl rlp::Encodable for RawTransaction {
@@ -56,3 +59,197 @@ impl FixedCodec for RawTransaction {
         Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
     }
 }
+
+// The scheme and the pubkey kind come last, a tx stored before the scheme was
+// added is a secp256k1 one, and one stored before the kind was added is signed
+// by a single key.
+impl rlp::Encodable for SignedTransaction {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(6);
+        s.append(&self.raw);
+        s.append(&self.tx_hash);
+        s.append(&self.pubkey.to_vec());
+        s.append(&self.signature.to_vec());
+        s.append(&self.scheme);
+        s.append(&self.kind);
+    }
+}
+
+impl rlp::Decodable for SignedTransaction {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let (scheme, kind) = match r.item_count()? {
+            4 => (SignatureScheme::Secp256k1, PubkeyKind::Single),
+            5 => (r.val_at(4)?, PubkeyKind::Single),
+            6 => (r.val_at(4)?, r.val_at(5)?),
+            _ => return Err(rlp::DecoderError::RlpIncorrectListLen),
+        };
+
+        let pubkey: Vec<u8> = r.val_at(2)?;
+        let signature: Vec<u8> = r.val_at(3)?;
+
+        Ok(Self {
+            raw: r.val_at(0)?,
+            tx_hash: r.val_at(1)?,
+            pubkey: bytes::Bytes::from(pubkey),
+            signature: bytes::Bytes::from(signature),
+            scheme,
+            kind,
+        })
+    }
+}
+
+impl FixedCodec for SignedTransaction {
+    fn encode_fixed(&self) -> ProtocolResult<bytes::Bytes> {
+        Ok(bytes::Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: bytes::Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Encodable for MultiSigPubkey {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let pubkeys = self
+            .pubkeys
+            .iter()
+            .map(|pubkey| pubkey.to_vec())
+            .collect::<Vec<_>>();
+
+        s.begin_list(2);
+        s.append(&self.threshold);
+        s.append_list::<Vec<u8>, _>(&pubkeys);
+    }
+}
+
+impl rlp::Decodable for MultiSigPubkey {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() || r.item_count()? != 2 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let threshold = r.at(0)?.as_val()?;
+        let pubkeys = r
+            .at(1)?
+            .as_list::<Vec<u8>>()?
+            .into_iter()
+            .map(bytes::Bytes::from)
+            .collect();
+
+        Ok(Self { threshold, pubkeys })
+    }
+}
+
+impl FixedCodec for MultiSigPubkey {
+    fn encode_fixed(&self) -> ProtocolResult<bytes::Bytes> {
+        Ok(bytes::Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: bytes::Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Encodable for MultiSigWitness {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        let signatures = self
+            .signatures
+            .iter()
+            .map(|signature| signature.to_vec())
+            .collect::<Vec<_>>();
+
+        s.append_list::<Vec<u8>, _>(&signatures);
+    }
+}
+
+impl rlp::Decodable for MultiSigWitness {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        let signatures = r
+            .as_list::<Vec<u8>>()?
+            .into_iter()
+            .map(bytes::Bytes::from)
+            .collect();
+
+        Ok(Self { signatures })
+    }
+}
+
+impl FixedCodec for MultiSigWitness {
+    fn encode_fixed(&self) -> ProtocolResult<bytes::Bytes> {
+        Ok(bytes::Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: bytes::Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Encodable for VerifierPubkey {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.begin_list(2);
+        s.append(&self.service);
+        s.append(&self.pubkey.to_vec());
+    }
+}
+
+impl rlp::Decodable for VerifierPubkey {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        if !r.is_list() || r.item_count()? != 2 {
+            return Err(rlp::DecoderError::RlpIncorrectListLen);
+        }
+
+        let service = r.at(0)?.as_val()?;
+        let pubkey: Vec<u8> = r.at(1)?.as_val()?;
+
+        Ok(Self {
+            service,
+            pubkey: bytes::Bytes::from(pubkey),
+        })
+    }
+}
+
+impl FixedCodec for VerifierPubkey {
+    fn encode_fixed(&self) -> ProtocolResult<bytes::Bytes> {
+        Ok(bytes::Bytes::from(rlp::encode(self)))
+    }
+
+    fn decode_fixed(bytes: bytes::Bytes) -> ProtocolResult<Self> {
+        Ok(rlp::decode(bytes.as_ref()).map_err(FixedCodecError::from)?)
+    }
+}
+
+impl rlp::Encodable for SignatureScheme {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append(&self.tag());
+    }
+}
+
+impl rlp::Decodable for SignatureScheme {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        SignatureScheme::from_tag(r.as_val()?)
+            .map_err(|_| rlp::DecoderError::Custom("unknown signature scheme"))
+    }
+}
+
+impl FixedCodec for SignatureScheme {
+    fn encode_fixed(&self) -> ProtocolResult<bytes::Bytes> {
+        self.tag().encode_fixed()
+    }
+
+    fn decode_fixed(bytes: bytes::Bytes) -> ProtocolResult<Self> {
+        SignatureScheme::from_tag(u8::decode_fixed(bytes)?)
+    }
+}
+
+impl rlp::Encodable for PubkeyKind {
+    fn rlp_append(&self, s: &mut rlp::RlpStream) {
+        s.append(&self.tag());
+    }
+}
+
+impl rlp::Decodable for PubkeyKind {
+    fn decode(r: &rlp::Rlp) -> Result<Self, rlp::DecoderError> {
+        PubkeyKind::from_tag(r.as_val()?)
+            .map_err(|_| rlp::DecoderError::Custom("unknown pubkey kind"))
+    }
+}
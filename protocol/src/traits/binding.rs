@@ -1,10 +1,13 @@
 use std::iter::Iterator;
 
 use bytes::Bytes;
+use serde::{Deserialize, Serialize};
 
 use crate::fixed_codec::FixedCodec;
 use crate::traits::{ExecutorParams, ServiceResponse};
-use crate::types::{Address, Block, Hash, MerkleRoot, Receipt, ServiceContext, SignedTransaction};
+use crate::types::{
+    Address, Block, Hash, MerkleRoot, NonceMode, Receipt, ServiceContext, SignedTransaction,
+};
 use crate::ProtocolResult;
 
 pub trait ServiceMapping: Send + Sync {
@@ -15,6 +18,78 @@ pub trait ServiceMapping: Send + Sync {
     ) -> ProtocolResult<Box<dyn Service>>;
 
     fn list_service_name(&self) -> Vec<String>;
+
+    // The version of the service at `height`. When it's raised, the
+    // `#[migrate]` methods of the service run once before the block.
+    fn service_version(&self, _name: &str, _height: u64) -> u64 {
+        0
+    }
+
+    // The implementation of the service at `height`, services are versioned
+    // by overriding it with `service_version`.
+    fn get_service_at<SDK: 'static + ServiceSDK>(
+        &self,
+        name: &str,
+        _height: u64,
+        sdk: SDK,
+    ) -> ProtocolResult<Box<dyn Service>> {
+        self.get_service(name, sdk)
+    }
+
+    // The service method that charges transaction fees, no fee is charged if
+    // it is `None`
+    fn fee_hook(&self) -> Option<FeeHook> {
+        None
+    }
+
+    // How transactions are protected from replay, see `NonceMode`
+    fn nonce_mode(&self) -> NonceMode {
+        NonceMode::Random
+    }
+
+    // The service allowed to register service instances at runtime through
+    // `ServiceSDK::register_service`, no service can be registered if it is
+    // `None`
+    fn governance_service(&self) -> Option<String> {
+        None
+    }
+}
+
+// `FeeHook` names a writable method which is called after every transaction
+// with the payload `{"fee": cycles_used * cycles_price}`, the caller of the
+// transaction is the caller of the hook. If the hook returns an error, the
+// transaction is reverted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeeHook {
+    pub service: String,
+    pub method:  String,
+}
+
+// The extra of the context the executor calls the hook with.
+pub const FEE_HOOK_EXTRA: &[u8] = b"fee_hook";
+
+impl FeeHook {
+    // Whether the call is the executor's call of the hook, which is at the top
+    // of the call stack. The calls from services are nested, so they can't
+    // pass for it whatever extra they set.
+    pub fn is_hook_call(ctx: &ServiceContext) -> bool {
+        ctx.get_call_depth() == 0
+            && ctx
+                .get_extra()
+                .map_or(false, |extra| extra.as_ref() == FEE_HOOK_EXTRA)
+    }
+}
+
+// `ServiceRegistration` adds the service `name` at `height`. It runs the code
+// of the service `code`, as returned by `ServiceMapping::get_service_at`, on a
+// state of its own initialized with the genesis `payload`. The code doesn't
+// have to be listed in `ServiceMapping::list_service_name`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceRegistration {
+    pub name:    String,
+    pub code:    String,
+    pub payload: String,
+    pub height:  u64,
 }
 
 // `ServiceState` provides access to` world state` and `account` for` service`.
@@ -57,6 +132,11 @@ pub trait ServiceState {
 
     // Persist data from stash into MPT
     fn commit(&mut self) -> ProtocolResult<MerkleRoot>;
+
+    // A read-only state rejects every write
+    fn is_readonly(&self) -> bool {
+        false
+    }
 }
 
 pub trait ChainQuerier {
@@ -91,11 +171,18 @@ pub trait Service {
     // Called after block execution
     fn hook_after_(&mut self, _params: &ExecutorParams) {}
 
-    // Called before tx execution
-    fn tx_hook_before_(&mut self, _ctx: ServiceContext) {}
+    // Called before tx execution, the tx is rejected if any service returns an
+    // error
+    fn tx_hook_before_(&mut self, _ctx: ServiceContext) -> ServiceResponse<String> {
+        ServiceResponse::from_succeed("".to_owned())
+    }
+
     // Called after tx execution
     fn tx_hook_after_(&mut self, _ctx: ServiceContext) {}
 
+    // Transform the stored state from version `from` to the next one
+    fn migrate_(&mut self, _params: &ExecutorParams, _from: u64) {}
+
     fn write_(&mut self, ctx: ServiceContext) -> ServiceResponse<String>;
 
     fn read_(&self, ctx: ServiceContext) -> ServiceResponse<String>;
@@ -186,6 +273,7 @@ pub trait ServiceSDK {
     ) -> ServiceResponse<String>;
 
     // Call other writable methods of `service` and return the results synchronously
+    // If the call returns an error, its writes and events are reverted
     // NOTE: You can use recursive calls, but the maximum call stack is 1024
     fn write(
         &mut self,
@@ -195,6 +283,49 @@ pub trait ServiceSDK {
         method: &str,
         payload: &str,
     ) -> ServiceResponse<String>;
+
+    // Mark the writes from now on, of this service and the ones it calls, so
+    // that they can be dropped as a whole by `revert_to_checkpoint`, or kept
+    // by `release_checkpoint`. Checkpoints nest, each one is ended once.
+    fn checkpoint(&mut self);
+
+    fn revert_to_checkpoint(&mut self);
+
+    fn release_checkpoint(&mut self);
+
+    // Register a service instance, which is active from the height of the
+    // registration on. Only the governance service of the mapping can do it.
+    fn register_service(
+        &mut self,
+        ctx: &ServiceContext,
+        registration: ServiceRegistration,
+    ) -> ServiceResponse<String>;
+
+    // Schedule a call of the writable `method` of `service`, which runs at the
+    // start of the block at `height` with its own receipt. Returns the hash of
+    // the receipt. The cycles the call can use are charged to `ctx` up front.
+    // NOTE: The caller of the call is the executor's scheduler, not the one of
+    // `ctx`, and there are at most `MAX_SCHEDULED_CALLS` calls at a height
+    fn schedule(
+        &mut self,
+        ctx: &ServiceContext,
+        height: u64,
+        service: &str,
+        method: &str,
+        payload: &str,
+    ) -> ServiceResponse<String>;
+
+    // Emit an event of type `name` with the data in json, each of the `topics`
+    // is indexed in the logs bloom with the type and its position, so the
+    // events can be filtered by it.
+    // NOTE: There are at most `MAX_EVENT_TOPICS` topics
+    fn emit_event<Data: Serialize>(
+        &self,
+        ctx: &ServiceContext,
+        name: &str,
+        topics: Vec<String>,
+        data: &Data,
+    );
 }
 
 pub trait StoreMap<K: FixedCodec + PartialEq, V: FixedCodec> {
@@ -99,6 +99,17 @@ pub trait CommonConsensusAdapter: Send + Sync {
 
     async fn save_receipts(&self, ctx: Context, receipts: Vec<Receipt>) -> ProtocolResult<()>;
 
+    /// Save a block together with its proof, signed transactions and receipts
+    /// in one atomic write.
+    async fn save_block_bundle(
+        &self,
+        ctx: Context,
+        block: Block,
+        proof: Proof,
+        signed_txs: Vec<SignedTransaction>,
+        receipts: Vec<Receipt>,
+    ) -> ProtocolResult<()>;
+
     /// Flush the given transactions in the mempool.
     async fn flush_mempool(&self, ctx: Context, ordered_tx_hashes: &[Hash]) -> ProtocolResult<()>;
 
@@ -123,6 +134,7 @@ pub trait CommonConsensusAdapter: Send + Sync {
         state_root: MerkleRoot,
         height: u64,
         timestamp: u64,
+        random_seed: Hash,
     ) -> ProtocolResult<Metadata>;
 
     fn report_bad(&self, ctx: Context, feedback: TrustFeedback);
@@ -197,6 +209,7 @@ pub trait ConsensusAdapter: CommonConsensusAdapter + Send + Sync {
         signed_txs: Vec<SignedTransaction>,
         cycles_limit: u64,
         timestamp: u64,
+        random_seed: Hash,
     ) -> ProtocolResult<()>;
 
     /// Get the validator list of the given last block.
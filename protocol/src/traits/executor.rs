@@ -1,17 +1,29 @@
 use std::sync::Arc;
 
-use crate::traits::{ServiceMapping, Storage};
+use crate::traits::{ServiceMapping, ServiceRegistration, Storage};
 use crate::types::{
-    Address, Bloom, MerkleRoot, Receipt, ServiceContext, SignedTransaction, TransactionRequest,
+    Address, Bloom, Hash, MerkleRoot, Receipt, ServiceContext, SignedTransaction,
+    TransactionRequest,
 };
 use crate::ProtocolResult;
 
 #[derive(Debug, Clone)]
 pub struct ExecutorResp {
-    pub receipts:        Vec<Receipt>,
-    pub all_cycles_used: u64,
-    pub logs_bloom:      Bloom,
-    pub state_root:      MerkleRoot,
+    pub receipts:           Vec<Receipt>,
+    // The receipts of the calls scheduled at the height, which run before the
+    // txs. They follow the receipts of the txs, see `all_receipts`.
+    pub scheduled_receipts: Vec<Receipt>,
+    pub all_cycles_used:    u64,
+    pub logs_bloom:         Bloom,
+    pub state_root:         MerkleRoot,
+}
+
+impl ExecutorResp {
+    /// The receipts of the txs followed by the ones of the scheduled calls,
+    /// which is the order they are stored in and hashed into the receipt root.
+    pub fn all_receipts(&self) -> impl Iterator<Item = &Receipt> {
+        self.receipts.iter().chain(self.scheduled_receipts.iter())
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -20,6 +32,15 @@ pub struct ExecutorParams {
     pub height:       u64,
     pub timestamp:    u64,
     pub cycles_limit: u64,
+    // See `BlockHeader::random_seed`
+    pub random_seed:  Hash,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecutorOptions {
+    // Execute the txs of a block optimistically in parallel, the result is the
+    // same as the serial one
+    pub parallel: bool,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -69,6 +90,7 @@ pub trait ExecutorFactory<DB: cita_trie::DB, S: Storage, Mapping: ServiceMapping
         db: Arc<DB>,
         storage: Arc<S>,
         mapping: Arc<Mapping>,
+        options: ExecutorOptions,
     ) -> ProtocolResult<Box<dyn Executor>>;
 }
 
@@ -93,6 +115,23 @@ pub trait Dispatcher {
     fn read(&self, context: ServiceContext) -> ServiceResponse<String>;
 
     fn write(&self, context: ServiceContext) -> ServiceResponse<String>;
+
+    fn register(
+        &self,
+        context: ServiceContext,
+        registration: ServiceRegistration,
+    ) -> ServiceResponse<String>;
+
+    fn schedule(&self, context: ServiceContext, height: u64) -> ServiceResponse<String>;
+
+    // The writes after a checkpoint are dropped by `revert_to_checkpoint` or
+    // kept by `release_checkpoint`, like the ones of a nested write.
+    // Checkpoints nest, each one is ended once.
+    fn checkpoint(&self);
+
+    fn revert_to_checkpoint(&self);
+
+    fn release_checkpoint(&self);
 }
 
 pub struct NoopDispatcher;
@@ -105,4 +144,23 @@ impl Dispatcher for NoopDispatcher {
     fn write(&self, _context: ServiceContext) -> ServiceResponse<String> {
         unimplemented!()
     }
+
+    fn register(
+        &self,
+        _context: ServiceContext,
+        _registration: ServiceRegistration,
+    ) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+
+    fn schedule(&self, _context: ServiceContext, _height: u64) -> ServiceResponse<String> {
+        unimplemented!()
+    }
+
+    // No nested call is made through it, so there is nothing to revert.
+    fn checkpoint(&self) {}
+
+    fn revert_to_checkpoint(&self) {}
+
+    fn release_checkpoint(&self) {}
 }
@@ -1,7 +1,7 @@
 use async_trait::async_trait;
 use creep::Context;
 
-use crate::types::{Hash, SignedTransaction};
+use crate::types::{Address, Hash, SignedTransaction, VerifierPubkey};
 use crate::ProtocolResult;
 
 #[allow(dead_code)]
@@ -78,3 +78,23 @@ pub trait MemPoolAdapter: Send + Sync {
 
     fn set_args(&self, timeout_gap: u64, cycles_limit: u64, max_tx_size: u64);
 }
+
+// Reads the next nonce of senders at the latest state, in the account nonce
+// mode. Like `SignatureVerifier`, it's only a best effort check of new txs.
+#[async_trait]
+pub trait NonceQuerier: Send + Sync {
+    async fn get_account_nonce(&self, ctx: Context, address: Address) -> ProtocolResult<u64>;
+}
+
+// Verifies the signatures of new txs by their verifier services at the latest
+// state, see `VerifierPubkey`. It's only a best effort check, the executor
+// verifies them again against the state they're executed on.
+#[async_trait]
+pub trait SignatureVerifier: Send + Sync {
+    async fn verify_signature(
+        &self,
+        ctx: Context,
+        stx: SignedTransaction,
+        pubkey: VerifierPubkey,
+    ) -> ProtocolResult<bool>;
+}
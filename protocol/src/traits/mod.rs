@@ -8,19 +8,23 @@ mod storage;
 
 pub use api::APIAdapter;
 pub use binding::{
-    AdmissionControl, ChainQuerier, Service, ServiceMapping, ServiceSDK, ServiceState, StoreArray,
-    StoreBool, StoreMap, StoreString, StoreUint64,
+    AdmissionControl, ChainQuerier, FeeHook, Service, ServiceMapping, ServiceRegistration,
+    ServiceSDK, ServiceState, StoreArray, StoreBool, StoreMap, StoreString, StoreUint64,
+    FEE_HOOK_EXTRA,
 };
 pub use consensus::{
     CommonConsensusAdapter, Consensus, ConsensusAdapter, MessageTarget, NodeInfo, Synchronization,
     SynchronizationAdapter,
 };
 pub use executor::{
-    Dispatcher, Executor, ExecutorFactory, ExecutorParams, ExecutorResp, NoopDispatcher,
-    ServiceResponse,
+    Dispatcher, Executor, ExecutorFactory, ExecutorOptions, ExecutorParams, ExecutorResp,
+    NoopDispatcher, ServiceResponse,
 };
-pub use mempool::{MemPool, MemPoolAdapter, MixedTxHashes};
+pub use mempool::{MemPool, MemPoolAdapter, MixedTxHashes, NonceQuerier, SignatureVerifier};
 pub use network::{Gossip, MessageCodec, MessageHandler, PeerTrust, Priority, Rpc, TrustFeedback};
-pub use storage::{Storage, StorageAdapter, StorageBatchModify, StorageCategory, StorageSchema};
+pub use storage::{
+    prefix_successor, Storage, StorageAdapter, StorageBatch, StorageBatchModify, StorageCategory,
+    StorageIterDirection, StorageScanMode, StorageSchema,
+};
 
 pub use creep::{Cloneable, Context};
@@ -8,7 +8,7 @@ use crate::types::receipt::Receipt;
 use crate::types::{Hash, SignedTransaction};
 use crate::{Bytes, ProtocolResult};
 
-#[derive(Debug, Copy, Clone, Display)]
+#[derive(Debug, Copy, Clone, Display, PartialEq, Eq, Hash)]
 pub enum StorageCategory {
     Block,
     Receipt,
@@ -37,6 +37,17 @@ pub trait Storage: Send + Sync {
 
     async fn update_latest_proof(&self, ctx: Context, proof: Proof) -> ProtocolResult<()>;
 
+    /// Write the block, its proof, signed transactions and receipts, and move
+    /// the latest block pointer, all in one atomic batch.
+    async fn commit_block_bundle(
+        &self,
+        ctx: Context,
+        block: Block,
+        proof: Proof,
+        signed_txs: Vec<SignedTransaction>,
+        receipts: Vec<Receipt>,
+    ) -> ProtocolResult<()>;
+
     async fn get_transaction_by_hash(
         &self,
         ctx: Context,
@@ -71,6 +82,69 @@ pub enum StorageBatchModify<S: StorageSchema> {
     Insert(<S as StorageSchema>::Value),
 }
 
+/// A set of modifications across several schemas, applied atomically by
+/// `StorageAdapter::write_batch`.
+#[derive(Debug, Default)]
+pub struct StorageBatch {
+    ops: Vec<(StorageCategory, Bytes, Option<Bytes>)>,
+}
+
+impl StorageBatch {
+    pub fn new() -> Self {
+        StorageBatch { ops: Vec::new() }
+    }
+
+    pub async fn insert<S: StorageSchema>(
+        &mut self,
+        mut key: <S as StorageSchema>::Key,
+        mut val: <S as StorageSchema>::Value,
+    ) -> ProtocolResult<()> {
+        let key = key.encode().await?;
+        let val = val.encode().await?;
+
+        self.ops.push((S::category(), key, Some(val)));
+        Ok(())
+    }
+
+    pub async fn remove<S: StorageSchema>(
+        &mut self,
+        mut key: <S as StorageSchema>::Key,
+    ) -> ProtocolResult<()> {
+        let key = key.encode().await?;
+
+        self.ops.push((S::category(), key, None));
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    pub fn into_ops(self) -> Vec<(StorageCategory, Bytes, Option<Bytes>)> {
+        self.ops
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StorageIterDirection {
+    Forward,
+    Reverse,
+}
+
+/// Where a scan over the encoded keys of a category starts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StorageScanMode {
+    /// Every key that starts with the prefix, an empty prefix matches all
+    /// keys.
+    Prefix(Bytes),
+    /// Every key from the given one, inclusive, in the scan direction.
+    From(Bytes),
+}
+
 #[async_trait]
 pub trait StorageAdapter: Send + Sync {
     async fn insert<S: StorageSchema>(
@@ -109,4 +183,85 @@ pub trait StorageAdapter: Send + Sync {
         keys: Vec<<S as StorageSchema>::Key>,
         vals: Vec<StorageBatchModify<S>>,
     ) -> ProtocolResult<()>;
+
+    /// Apply every modification in the batch, or none of them.
+    async fn write_batch(&self, batch: StorageBatch) -> ProtocolResult<()>;
+
+    /// Return at most `limit` raw key value pairs of the category, ordered by
+    /// their encoded keys.
+    async fn scan(
+        &self,
+        category: StorageCategory,
+        mode: StorageScanMode,
+        direction: StorageIterDirection,
+        limit: usize,
+    ) -> ProtocolResult<Vec<(Bytes, Bytes)>>;
+
+    /// Return at most `limit` entries of the schema whose encoded key starts
+    /// with `prefix`. Note that entries are ordered by encoded keys, e.g. u64
+    /// keys are little endian encoded.
+    async fn iter<S: StorageSchema>(
+        &self,
+        prefix: Bytes,
+        direction: StorageIterDirection,
+        limit: usize,
+    ) -> ProtocolResult<Vec<(<S as StorageSchema>::Key, <S as StorageSchema>::Value)>> {
+        let pairs = self
+            .scan(
+                S::category(),
+                StorageScanMode::Prefix(prefix),
+                direction,
+                limit,
+            )
+            .await?;
+
+        decode_pairs::<S>(pairs).await
+    }
+
+    /// Return at most `limit` entries of the schema starting from the given
+    /// key, inclusive, or from the first (last in reverse) one if it is
+    /// `None`.
+    async fn range<S: StorageSchema>(
+        &self,
+        from: Option<<S as StorageSchema>::Key>,
+        direction: StorageIterDirection,
+        limit: usize,
+    ) -> ProtocolResult<Vec<(<S as StorageSchema>::Key, <S as StorageSchema>::Value)>> {
+        let mode = match from {
+            Some(mut key) => StorageScanMode::From(key.encode().await?),
+            None => StorageScanMode::Prefix(Bytes::new()),
+        };
+        let pairs = self.scan(S::category(), mode, direction, limit).await?;
+
+        decode_pairs::<S>(pairs).await
+    }
+}
+
+async fn decode_pairs<S: StorageSchema>(
+    pairs: Vec<(Bytes, Bytes)>,
+) -> ProtocolResult<Vec<(<S as StorageSchema>::Key, <S as StorageSchema>::Value)>> {
+    let mut entries = Vec::with_capacity(pairs.len());
+
+    for (key, val) in pairs.into_iter() {
+        let key = <S as StorageSchema>::Key::decode(key).await?;
+        let val = <S as StorageSchema>::Value::decode(val).await?;
+        entries.push((key, val));
+    }
+
+    Ok(entries)
+}
+
+/// The smallest key that is greater than every key starting with `prefix`,
+/// `None` if there is no such key.
+pub fn prefix_successor(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut successor = prefix.to_vec();
+
+    while let Some(last) = successor.pop() {
+        if last != u8::max_value() {
+            successor.push(last + 1);
+            return Some(successor);
+        }
+    }
+
+    None
 }
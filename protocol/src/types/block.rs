@@ -13,11 +13,12 @@ pub struct Block {
     pub ordered_tx_hashes: Vec<Hash>,
 }
 
-#[derive(RlpFixedCodec, Clone, Debug, Display, PartialEq, Eq)]
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
 #[display(
     fmt = "chain id {:?}, height {}, exec height {}, previous hash {:?}, logs bloom {:?},
     ordered root {:?}, confirm root {:?}, state root {:?},
-    receipt root {:?},cycles_used {:?}, proposer {:?}, proof {:?}, validators {:?}",
+    receipt root {:?},cycles_used {:?}, proposer {:?}, proof {:?}, validators {:?},
+    seed signature {:?}",
     chain_id,
     height,
     exec_height,
@@ -30,7 +31,8 @@ pub struct Block {
     cycles_used,
     proposer,
     proof,
-    validators
+    validators,
+    seed_signature
 )]
 pub struct BlockHeader {
     pub chain_id:          Hash,
@@ -48,6 +50,26 @@ pub struct BlockHeader {
     pub proof:             Proof,
     pub validator_version: u64,
     pub validators:        Vec<Validator>,
+    pub seed_signature:    Bytes,
+}
+
+impl BlockHeader {
+    // The random seed of the block. The proposer signs the seed of the
+    // previous block with its BLS key, and a BLS signature is unique, so the
+    // proposer can't choose the seed among several ones.
+    //
+    // It isn't derived from the aggregated signature of the previous proof:
+    // the leader who aggregates the votes chooses which 2/3+ of them go in,
+    // and each subset gives another signature, so a single leader could pick
+    // the seed among many.
+    //
+    // A block without a seed signature, such as the genesis one or one stored
+    // before the signature was added, has the digest of an empty signature as
+    // its seed. Once a block carries a seed signature, every block after it
+    // must carry one as well.
+    pub fn random_seed(&self) -> Hash {
+        Hash::digest(self.seed_signature.clone())
+    }
 }
 
 #[derive(RlpFixedCodec, Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq)]
@@ -19,9 +19,15 @@ pub use primitive::{
     Address, Hash, Hex, JsonString, MerkleRoot, Metadata, ValidatorExtend, GENESIS_HEIGHT,
     METADATA_KEY,
 };
-pub use receipt::{Event, Receipt, ReceiptResponse};
-pub use service_context::{ServiceContext, ServiceContextError, ServiceContextParams};
-pub use transaction::{RawTransaction, SignedTransaction, TransactionRequest};
+pub use receipt::{CyclesRecord, Event, Receipt, ReceiptResponse, MAX_EVENT_TOPICS};
+pub use service_context::{
+    ServiceContext, ServiceContextCounters, ServiceContextError, ServiceContextParams,
+};
+pub use transaction::{
+    account_nonce_to_hash, MultiSigPubkey, MultiSigWitness, NonceMode, PubkeyKind, RawTransaction,
+    SignatureScheme, SignedTransaction, TransactionRequest, VerifierPubkey, VerifyPayload,
+    MAX_MULTISIG_PUBKEYS, VERIFY_METHOD,
+};
 
 #[derive(Debug, Display, From)]
 pub enum TypesError {
@@ -36,6 +42,12 @@ pub enum TypesError {
 
     #[display(fmt = "Hex should start with 0x")]
     HexPrefix,
+
+    #[display(fmt = "Unknown signature scheme {}", scheme)]
+    UnknownSignatureScheme { scheme: String },
+
+    #[display(fmt = "Unknown pubkey kind {}", kind)]
+    UnknownPubkeyKind { kind: String },
 }
 
 impl Error for TypesError {}
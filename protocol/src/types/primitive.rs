@@ -1,6 +1,6 @@
 use std::fmt;
 
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use hasher::{Hasher, HasherKeccak};
 use lazy_static::lazy_static;
 use muta_codec_derive::RlpFixedCodec;
@@ -8,7 +8,7 @@ use serde::de;
 use serde::{Deserialize, Serialize};
 
 use crate::fixed_codec::{FixedCodec, FixedCodecError};
-use crate::types::TypesError;
+use crate::types::{SignatureScheme, TypesError};
 use crate::ProtocolResult;
 
 pub const METADATA_KEY: &str = "metadata";
@@ -21,7 +21,7 @@ lazy_static! {
 pub const GENESIS_HEIGHT: u64 = 0;
 
 /// Hash length
-const HASH_LEN: usize = 32;
+pub(crate) const HASH_LEN: usize = 32;
 
 // Should started with 0x
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
@@ -243,6 +243,19 @@ impl Address {
         Self::from_hash(hash)
     }
 
+    /// A secp256k1 pubkey is hashed alone as before, the ones of other schemes
+    /// are hashed after the scheme's tag, so the same bytes of different
+    /// schemes never share an address.
+    pub fn from_scheme_pubkey(scheme: SignatureScheme, bytes: Bytes) -> ProtocolResult<Self> {
+        if scheme == SignatureScheme::Secp256k1 {
+            return Self::from_pubkey_bytes(bytes);
+        }
+
+        let mut tagged = BytesMut::from([scheme.tag()].as_ref());
+        tagged.extend_from_slice(bytes.as_ref());
+        Self::from_pubkey_bytes(tagged.freeze())
+    }
+
     pub fn from_hash(hash: Hash) -> ProtocolResult<Self> {
         let mut hash_val = hash.as_bytes();
         hash_val.truncate(20);
@@ -5,20 +5,50 @@ use crate::fixed_codec::{FixedCodec, FixedCodecError};
 use crate::types::{Hash, MerkleRoot};
 use crate::{traits::ServiceResponse, ProtocolResult};
 
-#[derive(RlpFixedCodec, Debug, Clone, PartialEq, Eq)]
+// The maximum number of the indexed topics of an event.
+pub const MAX_EVENT_TOPICS: usize = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Event {
     pub service: String,
+    // The type of the event, empty if it's emitted as a plain message
+    pub name:    String,
+    // Each topic is added to the logs bloom with the name and its position
+    pub topics:  Vec<String>,
     pub data:    String,
 }
 
-#[derive(RlpFixedCodec, Clone, Debug, PartialEq, Eq)]
+impl Event {
+    // The item of the `index`th topic in the logs bloom, so that e.g. the
+    // sender and the receiver of a transfer are told apart.
+    pub fn topic_bloom_item(&self, index: usize) -> Option<String> {
+        self.topics
+            .get(index)
+            .map(|topic| format!("{}:{}:{}", self.name, index, topic))
+    }
+}
+
+// The cycles used by a service call, including the ones of its nested calls.
+#[derive(RlpFixedCodec, Debug, Clone, PartialEq, Eq)]
+pub struct CyclesRecord {
+    pub service:    String,
+    pub method:     String,
+    pub call_depth: u64,
+    pub cycles:     u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Receipt {
-    pub state_root:  MerkleRoot,
-    pub height:      u64,
-    pub tx_hash:     Hash,
-    pub cycles_used: u64,
-    pub events:      Vec<Event>,
-    pub response:    ReceiptResponse,
+    pub state_root:       MerkleRoot,
+    pub height:           u64,
+    pub tx_hash:          Hash,
+    // The cycles charged, that is, the ones used minus the refunded ones
+    pub cycles_used:      u64,
+    pub events:           Vec<Event>,
+    pub response:         ReceiptResponse,
+    pub cycles_refunded:  u64,
+    // The calls in the order they were made
+    pub cycles_breakdown: Vec<CyclesRecord>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -4,7 +4,7 @@ use std::rc::Rc;
 use bytes::Bytes;
 use derive_more::{Display, From};
 
-use crate::types::{Address, Event, Hash};
+use crate::types::{Address, CyclesRecord, Event, Hash};
 use crate::{ProtocolError, ProtocolErrorKind};
 
 #[derive(Debug, Clone)]
@@ -21,9 +21,21 @@ pub struct ServiceContextParams {
     pub service_payload: String,
     pub extra:           Option<Bytes>,
     pub timestamp:       u64,
+    pub random_seed:     Hash,
     pub events:          Rc<RefCell<Vec<Event>>>,
 }
 
+// What a context has counted so far. Unlike the context, it can be sent to
+// another thread, and resumed there by a context of the same transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServiceContextCounters {
+    pub cycles_used:    u64,
+    pub out_of_cycles:  bool,
+    pub cycles_refund:  u64,
+    pub events:         Vec<Event>,
+    pub cycles_records: Vec<(CyclesRecord, Option<u64>)>,
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ServiceContext {
     tx_hash:         Option<Hash>,
@@ -38,7 +50,15 @@ pub struct ServiceContext {
     service_payload: String,
     extra:           Option<Bytes>,
     timestamp:       u64,
+    random_seed:     Hash,
     events:          Rc<RefCell<Vec<Event>>>,
+    out_of_cycles:   Rc<RefCell<bool>>,
+    cycles_refund:   Rc<RefCell<u64>>,
+    // Each call's record with the cycles used when it started, which is
+    // `None` once it returned
+    cycles_records:  Rc<RefCell<Vec<(CyclesRecord, Option<u64>)>>>,
+    // The number of cross-service calls from the transaction to this one
+    call_depth:      u64,
 }
 
 impl ServiceContext {
@@ -56,7 +76,12 @@ impl ServiceContext {
             service_payload: params.service_payload,
             extra:           params.extra,
             timestamp:       params.timestamp,
+            random_seed:     params.random_seed,
             events:          params.events,
+            out_of_cycles:   Rc::new(RefCell::new(false)),
+            cycles_refund:   Rc::new(RefCell::new(0)),
+            cycles_records:  Rc::new(RefCell::new(vec![])),
+            call_depth:      0,
         }
     }
 
@@ -80,10 +105,38 @@ impl ServiceContext {
             service_payload,
             extra,
             timestamp: context.get_timestamp(),
+            random_seed: context.random_seed.clone(),
             events: Rc::clone(&context.events),
+            out_of_cycles: Rc::clone(&context.out_of_cycles),
+            cycles_refund: Rc::clone(&context.cycles_refund),
+            cycles_records: Rc::clone(&context.cycles_records),
+            call_depth: context.call_depth + 1,
+        }
+    }
+
+    pub fn get_counters(&self) -> ServiceContextCounters {
+        ServiceContextCounters {
+            cycles_used:    self.get_cycles_used(),
+            out_of_cycles:  self.is_out_of_cycles(),
+            cycles_refund:  self.get_cycles_refund(),
+            events:         self.get_events(),
+            cycles_records: self.cycles_records.borrow().clone(),
         }
     }
 
+    // Carry on from the counters of the same transaction's context.
+    pub fn resume(&self, counters: ServiceContextCounters) {
+        *self.cycles_used.borrow_mut() = counters.cycles_used;
+        *self.out_of_cycles.borrow_mut() = counters.out_of_cycles;
+        *self.cycles_refund.borrow_mut() = counters.cycles_refund;
+        *self.events.borrow_mut() = counters.events;
+        *self.cycles_records.borrow_mut() = counters.cycles_records;
+    }
+
+    pub fn get_call_depth(&self) -> u64 {
+        self.call_depth
+    }
+
     pub fn get_tx_hash(&self) -> Option<Hash> {
         self.tx_hash.clone()
     }
@@ -96,15 +149,90 @@ impl ServiceContext {
         self.events.borrow().clone()
     }
 
+    pub fn get_events_len(&self) -> usize {
+        self.events.borrow().len()
+    }
+
+    // Drop the events emitted after the first `len` ones
+    pub fn revert_events(&self, len: usize) {
+        self.events.borrow_mut().truncate(len);
+    }
+
+    // Running out of cycles uses up the whole cycles limit, the executor will
+    // revert the state changes of the transaction.
     pub fn sub_cycles(&self, cycles: u64) -> bool {
-        if self.get_cycles_used() + cycles <= self.cycles_limit {
-            *self.cycles_used.borrow_mut() = self.get_cycles_used() + cycles;
-            true
-        } else {
-            false
+        match self.get_cycles_used().checked_add(cycles) {
+            Some(used) if used <= self.cycles_limit => {
+                *self.cycles_used.borrow_mut() = used;
+                true
+            }
+            _ => {
+                *self.cycles_used.borrow_mut() = self.cycles_limit;
+                *self.out_of_cycles.borrow_mut() = true;
+                false
+            }
+        }
+    }
+
+    pub fn is_out_of_cycles(&self) -> bool {
+        *self.out_of_cycles.borrow()
+    }
+
+    // The refund is returned when the tx is charged, it doesn't give back the
+    // cycles to the current call.
+    pub fn refund_cycles(&self, cycles: u64) {
+        let refund = self.get_cycles_refund().saturating_add(cycles);
+        *self.cycles_refund.borrow_mut() = refund;
+    }
+
+    pub fn get_cycles_refund(&self) -> u64 {
+        *self.cycles_refund.borrow()
+    }
+
+    // Drop the refund added since it was `refund`
+    pub fn revert_cycles_refund(&self, refund: u64) {
+        *self.cycles_refund.borrow_mut() = refund;
+    }
+
+    // Start recording the cycles of the call, returns the index of its record.
+    pub fn start_cycles_record(&self) -> usize {
+        let record = CyclesRecord {
+            service:    self.service_name.clone(),
+            method:     self.service_method.clone(),
+            call_depth: self.call_depth,
+            cycles:     0,
+        };
+
+        let mut records = self.cycles_records.borrow_mut();
+        records.push((record, Some(self.get_cycles_used())));
+        records.len() - 1
+    }
+
+    pub fn finish_cycles_record(&self, index: usize) {
+        let used = self.get_cycles_used();
+        if let Some((record, start)) = self.cycles_records.borrow_mut().get_mut(index) {
+            if let Some(start) = start.take() {
+                record.cycles = used.saturating_sub(start);
+            }
         }
     }
 
+    // The calls which didn't return, e.g. panicked, are counted up to now.
+    pub fn get_cycles_records(&self) -> Vec<CyclesRecord> {
+        let used = self.get_cycles_used();
+        self.cycles_records
+            .borrow()
+            .iter()
+            .map(|(record, start)| match start {
+                Some(start) => CyclesRecord {
+                    cycles: used.saturating_sub(*start),
+                    ..record.clone()
+                },
+                None => record.clone(),
+            })
+            .collect()
+    }
+
     pub fn get_cycles_price(&self) -> u64 {
         self.cycles_price
     }
@@ -145,10 +273,24 @@ impl ServiceContext {
         self.timestamp
     }
 
+    // The seed is the same for all the transactions of the block, mix it with
+    // the tx hash to tell them apart.
+    pub fn get_random_seed(&self) -> Hash {
+        self.random_seed.clone()
+    }
+
     pub fn emit_event(&self, message: String) {
+        self.emit_indexed_event(String::new(), vec![], message)
+    }
+
+    // The topics are expected to be at most `MAX_EVENT_TOPICS`, which is
+    // checked by the sdk.
+    pub fn emit_indexed_event(&self, name: String, topics: Vec<String>, data: String) {
         self.events.borrow_mut().push(Event {
             service: self.service_name.clone(),
-            data:    message,
+            name,
+            topics,
+            data,
         })
     }
 }
@@ -172,6 +314,8 @@ mod tests {
     use std::cell::RefCell;
     use std::rc::Rc;
 
+    use bytes::Bytes;
+
     use super::{ServiceContext, ServiceContextParams};
     use crate::types::{Address, Hash};
 
@@ -186,6 +330,7 @@ mod tests {
             caller:          Address::from_hash(Hash::from_empty()).unwrap(),
             height:          1,
             timestamp:       0,
+            random_seed:     Hash::digest(Bytes::from_static(b"seed")),
             service_name:    "service_name".to_owned(),
             service_method:  "service_method".to_owned(),
             service_payload: "service_payload".to_owned(),
@@ -196,6 +341,7 @@ mod tests {
 
         ctx.sub_cycles(8);
         assert_eq!(ctx.get_cycles_used(), 18);
+        assert!(!ctx.is_out_of_cycles());
 
         assert_eq!(ctx.get_cycles_limit(), 100);
         assert_eq!(ctx.get_cycles_price(), 8);
@@ -205,8 +351,45 @@ mod tests {
         );
         assert_eq!(ctx.get_current_height(), 1);
         assert_eq!(ctx.get_timestamp(), 0);
+        assert_eq!(
+            ctx.get_random_seed(),
+            Hash::digest(Bytes::from_static(b"seed"))
+        );
         assert_eq!(ctx.get_service_name(), "service_name");
         assert_eq!(ctx.get_service_method(), "service_method");
         assert_eq!(ctx.get_payload(), "service_payload");
+        assert_eq!(ctx.get_call_depth(), 0);
+
+        let nested_ctx = ServiceContext::with_context(
+            &ctx,
+            None,
+            "nested_name".to_owned(),
+            "nested_method".to_owned(),
+            "nested_payload".to_owned(),
+        );
+        assert_eq!(nested_ctx.get_call_depth(), 1);
+        assert_eq!(nested_ctx.get_random_seed(), ctx.get_random_seed());
+
+        let index = ctx.start_cycles_record();
+        let nested_index = nested_ctx.start_cycles_record();
+        nested_ctx.sub_cycles(20);
+        nested_ctx.refund_cycles(5);
+        nested_ctx.finish_cycles_record(nested_index);
+        ctx.sub_cycles(30);
+        let records = ctx.get_cycles_records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].service, "service_name");
+        assert_eq!(records[0].cycles, 50);
+        assert_eq!(records[1].method, "nested_method");
+        assert_eq!(records[1].call_depth, 1);
+        assert_eq!(records[1].cycles, 20);
+        ctx.finish_cycles_record(index);
+        assert_eq!(ctx.get_cycles_refund(), 5);
+        ctx.revert_cycles_refund(0);
+        assert_eq!(nested_ctx.get_cycles_refund(), 0);
+
+        assert!(!ctx.sub_cycles(33));
+        assert!(ctx.is_out_of_cycles());
+        assert_eq!(ctx.get_cycles_used(), 100);
     }
 }
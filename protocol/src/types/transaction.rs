@@ -1,8 +1,10 @@
 use bytes::Bytes;
 use muta_codec_derive::RlpFixedCodec;
+use serde::{Deserialize, Serialize};
 
 use crate::fixed_codec::{FixedCodec, FixedCodecError};
-use crate::types::primitive::{Hash, JsonString};
+use crate::types::primitive::{Address, Hash, Hex, JsonString, HASH_LEN};
+use crate::types::TypesError;
 use crate::ProtocolResult;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -15,6 +17,39 @@ pub struct RawTransaction {
     pub timeout:      u64,
 }
 
+impl RawTransaction {
+    /// The nonce as a per sender counter, which is a big endian u64 left padded
+    /// with zeros. `None` if the nonce isn't in this form.
+    pub fn account_nonce(&self) -> Option<u64> {
+        let bytes = self.nonce.as_bytes();
+        if bytes[..HASH_LEN - 8].iter().any(|b| *b != 0) {
+            return None;
+        }
+
+        let mut nonce = [0u8; 8];
+        nonce.copy_from_slice(&bytes[HASH_LEN - 8..]);
+        Some(u64::from_be_bytes(nonce))
+    }
+}
+
+/// Encode a per sender counter as the nonce of a transaction.
+pub fn account_nonce_to_hash(nonce: u64) -> Hash {
+    let mut bytes = vec![0u8; HASH_LEN - 8];
+    bytes.extend_from_slice(&nonce.to_be_bytes());
+
+    Hash::from_bytes(Bytes::from(bytes)).expect("account nonce hash")
+}
+
+/// How a transaction's nonce protects it from replay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceMode {
+    /// The nonce is random, replay is prevented by the tx hash and timeout.
+    Random,
+    /// The nonce is a per sender counter, see `RawTransaction::account_nonce`.
+    /// A tx is executed only if its nonce equals the sender's next one.
+    Account,
+}
+
 #[derive(RlpFixedCodec, Clone, Debug, PartialEq, Eq)]
 pub struct TransactionRequest {
     pub method:       String,
@@ -22,10 +57,337 @@ pub struct TransactionRequest {
     pub payload:      JsonString,
 }
 
-#[derive(RlpFixedCodec, Clone, Debug, PartialEq, Eq)]
+/// The signature scheme of a tx's keys, which is tagged in the tx.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SignatureScheme {
+    Secp256k1,
+    Ed25519,
+    Sm2,
+}
+
+impl SignatureScheme {
+    pub fn tag(self) -> u8 {
+        match self {
+            SignatureScheme::Secp256k1 => 0,
+            SignatureScheme::Ed25519 => 1,
+            SignatureScheme::Sm2 => 2,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> ProtocolResult<Self> {
+        match tag {
+            0 => Ok(SignatureScheme::Secp256k1),
+            1 => Ok(SignatureScheme::Ed25519),
+            2 => Ok(SignatureScheme::Sm2),
+            _ => Err(TypesError::UnknownSignatureScheme {
+                scheme: tag.to_string(),
+            }
+            .into()),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            SignatureScheme::Secp256k1 => "secp256k1",
+            SignatureScheme::Ed25519 => "ed25519",
+            SignatureScheme::Sm2 => "sm2",
+        }
+    }
+
+    pub fn from_name(name: &str) -> ProtocolResult<Self> {
+        match name {
+            "secp256k1" => Ok(SignatureScheme::Secp256k1),
+            "ed25519" => Ok(SignatureScheme::Ed25519),
+            "sm2" => Ok(SignatureScheme::Sm2),
+            _ => Err(TypesError::UnknownSignatureScheme {
+                scheme: name.to_owned(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl Default for SignatureScheme {
+    fn default() -> Self {
+        SignatureScheme::Secp256k1
+    }
+}
+
+/// How the `pubkey` of a tx is encoded, which is tagged in the tx.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PubkeyKind {
+    /// A single key of the tx's scheme.
+    Single,
+    /// The fixed encoding of a `MultiSigPubkey`.
+    MultiSig,
+    /// The fixed encoding of a `VerifierPubkey`.
+    Verifier,
+}
+
+impl PubkeyKind {
+    pub fn tag(self) -> u8 {
+        match self {
+            PubkeyKind::Single => 0,
+            PubkeyKind::MultiSig => 1,
+            PubkeyKind::Verifier => 2,
+        }
+    }
+
+    pub fn from_tag(tag: u8) -> ProtocolResult<Self> {
+        match tag {
+            0 => Ok(PubkeyKind::Single),
+            1 => Ok(PubkeyKind::MultiSig),
+            2 => Ok(PubkeyKind::Verifier),
+            _ => Err(TypesError::UnknownPubkeyKind {
+                kind: tag.to_string(),
+            }
+            .into()),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PubkeyKind::Single => "single",
+            PubkeyKind::MultiSig => "multisig",
+            PubkeyKind::Verifier => "verifier",
+        }
+    }
+
+    pub fn from_name(name: &str) -> ProtocolResult<Self> {
+        match name {
+            "single" => Ok(PubkeyKind::Single),
+            "multisig" => Ok(PubkeyKind::MultiSig),
+            "verifier" => Ok(PubkeyKind::Verifier),
+            _ => Err(TypesError::UnknownPubkeyKind {
+                kind: name.to_owned(),
+            }
+            .into()),
+        }
+    }
+}
+
+impl Default for PubkeyKind {
+    fn default() -> Self {
+        PubkeyKind::Single
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SignedTransaction {
     pub raw:       RawTransaction,
     pub tx_hash:   Hash,
     pub pubkey:    Bytes,
     pub signature: Bytes,
+    pub scheme:    SignatureScheme,
+    pub kind:      PubkeyKind,
+}
+
+impl SignedTransaction {
+    /// The caller of the tx, see `Address::from_scheme_pubkey`.
+    pub fn sender(&self) -> ProtocolResult<Address> {
+        Address::from_scheme_pubkey(self.scheme, self.pubkey.clone())
+    }
+
+    /// The multisig pubkey of the tx, `None` if it's not of a multisig account.
+    pub fn multisig_pubkey(&self) -> ProtocolResult<Option<MultiSigPubkey>> {
+        if self.kind != PubkeyKind::MultiSig {
+            return Ok(None);
+        }
+
+        MultiSigPubkey::decode_fixed(self.pubkey.clone()).map(Some)
+    }
+
+    /// The pubkey of the tx if its signature is verified by a service.
+    pub fn verifier_pubkey(&self) -> ProtocolResult<Option<VerifierPubkey>> {
+        if self.kind != PubkeyKind::Verifier {
+            return Ok(None);
+        }
+
+        VerifierPubkey::decode_fixed(self.pubkey.clone()).map(Some)
+    }
+}
+
+/// The max number of keys of a multisig account.
+pub const MAX_MULTISIG_PUBKEYS: usize = 16;
+
+/// The keys of a multisig account, a tx of it is of the `MultiSig` kind and
+/// carries the fixed encoding as its `pubkey` and a `MultiSigWitness` as its
+/// `signature`. So the address of the account is derived from the encoding
+/// like from a single pubkey, and the keys are of the tx's scheme.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiSigPubkey {
+    pub threshold: u8,
+    pub pubkeys:   Vec<Bytes>,
+}
+
+impl MultiSigPubkey {
+    pub fn address(&self, scheme: SignatureScheme) -> ProtocolResult<Address> {
+        Address::from_scheme_pubkey(scheme, self.encode_fixed()?)
+    }
+
+    /// The threshold must be reachable, and the keys must be distinct.
+    pub fn is_valid(&self) -> bool {
+        let mut pubkeys = self.pubkeys.clone();
+        pubkeys.sort();
+        pubkeys.dedup();
+
+        self.threshold > 0
+            && usize::from(self.threshold) <= self.pubkeys.len()
+            && self.pubkeys.len() <= MAX_MULTISIG_PUBKEYS
+            && pubkeys.len() == self.pubkeys.len()
+    }
+}
+
+/// The signatures of a multisig tx in the order of the keys, the one of a key
+/// which doesn't sign is empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiSigWitness {
+    pub signatures: Vec<Bytes>,
+}
+
+/// The `#[read]` method of a verifier service, see `VerifierPubkey`.
+pub const VERIFY_METHOD: &str = "verify";
+
+/// The key of an account whose signatures are verified by a service, a tx of
+/// it is of the `Verifier` kind and carries the fixed encoding as its `pubkey`
+/// and any witness the service takes as its `signature`. So the address of the
+/// account is bound to the service. The `verify` method of the service is
+/// called with `VerifyPayload` and returns an error if the witness isn't valid.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifierPubkey {
+    pub service: String,
+    pub pubkey:  Bytes,
+}
+
+impl VerifierPubkey {
+    pub fn address(&self, scheme: SignatureScheme) -> ProtocolResult<Address> {
+        Address::from_scheme_pubkey(scheme, self.encode_fixed()?)
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct VerifyPayload {
+    pub tx_hash: Hash,
+    pub pubkey:  Hex,
+    pub witness: Hex,
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::{
+        account_nonce_to_hash, MultiSigPubkey, PubkeyKind, RawTransaction, SignatureScheme,
+        SignedTransaction, TransactionRequest, VerifierPubkey,
+    };
+    use crate::fixed_codec::FixedCodec;
+    use crate::types::primitive::{Address, Hash};
+
+    #[test]
+    fn test_account_nonce() {
+        let mut raw = mock_raw_tx();
+        assert_eq!(raw.account_nonce(), Some(258));
+
+        raw.nonce = Hash::digest(Bytes::from("xxxxxx"));
+        assert_eq!(raw.account_nonce(), None);
+    }
+
+    #[test]
+    fn test_signature_scheme() {
+        let pubkey = Bytes::from(vec![2u8; 33]);
+        let secp256k1 =
+            Address::from_scheme_pubkey(SignatureScheme::Secp256k1, pubkey.clone()).unwrap();
+        let sm2 = Address::from_scheme_pubkey(SignatureScheme::Sm2, pubkey.clone()).unwrap();
+        assert_eq!(secp256k1, Address::from_pubkey_bytes(pubkey).unwrap());
+        assert_ne!(secp256k1, sm2);
+
+        for scheme in [
+            SignatureScheme::Secp256k1,
+            SignatureScheme::Ed25519,
+            SignatureScheme::Sm2,
+        ]
+        .iter()
+        {
+            assert_eq!(SignatureScheme::from_tag(scheme.tag()).unwrap(), *scheme);
+            assert_eq!(SignatureScheme::from_name(scheme.name()).unwrap(), *scheme);
+        }
+        assert!(SignatureScheme::from_tag(3).is_err());
+    }
+
+    #[test]
+    fn test_multisig_pubkey() {
+        let multisig = MultiSigPubkey {
+            threshold: 2,
+            pubkeys:   vec![Bytes::from(vec![2u8; 33]), Bytes::from(vec![3u8; 33])],
+        };
+        assert!(multisig.is_valid());
+
+        let mut stx = SignedTransaction {
+            raw:       mock_raw_tx(),
+            tx_hash:   Hash::from_empty(),
+            pubkey:    Bytes::from(vec![2u8; 33]),
+            signature: Bytes::new(),
+            scheme:    SignatureScheme::Secp256k1,
+            kind:      PubkeyKind::Single,
+        };
+        assert_eq!(stx.multisig_pubkey().unwrap(), None);
+
+        // The kind is tagged, a single key is never taken as a multisig one
+        stx.pubkey = multisig.encode_fixed().unwrap();
+        assert_eq!(stx.multisig_pubkey().unwrap(), None);
+
+        stx.kind = PubkeyKind::MultiSig;
+        assert_eq!(stx.multisig_pubkey().unwrap(), Some(multisig.clone()));
+        assert_eq!(stx.verifier_pubkey().unwrap(), None);
+        assert_eq!(
+            stx.sender().unwrap(),
+            multisig.address(SignatureScheme::Secp256k1).unwrap()
+        );
+
+        let verifier = VerifierPubkey {
+            service: "account".to_owned(),
+            pubkey:  Bytes::from(vec![2u8; 33]),
+        };
+        stx.pubkey = verifier.encode_fixed().unwrap();
+        stx.kind = PubkeyKind::Verifier;
+        assert_eq!(stx.multisig_pubkey().unwrap(), None);
+        assert_eq!(stx.verifier_pubkey().unwrap(), Some(verifier));
+
+        let mut invalid = multisig.clone();
+        invalid.threshold = 3;
+        assert!(!invalid.is_valid());
+
+        let mut invalid = multisig;
+        invalid.pubkeys[1] = invalid.pubkeys[0].clone();
+        assert!(!invalid.is_valid());
+
+        for kind in [
+            PubkeyKind::Single,
+            PubkeyKind::MultiSig,
+            PubkeyKind::Verifier,
+        ]
+        .iter()
+        {
+            assert_eq!(PubkeyKind::from_tag(kind.tag()).unwrap(), *kind);
+            assert_eq!(PubkeyKind::from_name(kind.name()).unwrap(), *kind);
+        }
+        assert!(PubkeyKind::from_tag(3).is_err());
+    }
+
+    fn mock_raw_tx() -> RawTransaction {
+        RawTransaction {
+            chain_id:     Hash::from_empty(),
+            cycles_price: 1,
+            cycles_limit: 1,
+            nonce:        account_nonce_to_hash(258),
+            request:      TransactionRequest {
+                method:       "method".to_owned(),
+                service_name: "service".to_owned(),
+                payload:      "".to_owned(),
+            },
+            timeout:      0,
+        }
+    }
 }
@@ -5,6 +5,8 @@ use std::path::PathBuf;
 use serde_derive::Deserialize;
 
 use core_mempool::{DEFAULT_BROADCAST_TXS_INTERVAL, DEFAULT_BROADCAST_TXS_SIZE};
+use core_storage::adapter::rocks::{RocksColumnOptions, RocksCompression};
+use protocol::traits::StorageCategory;
 use protocol::types::Hex;
 
 #[derive(Debug, Deserialize)]
@@ -80,17 +82,94 @@ pub struct ConfigMempool {
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigExecutor {
-    pub light: bool,
+    pub light:    bool,
+    // Execute the txs of a block optimistically in parallel
+    #[serde(default)]
+    pub parallel: bool,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigRocksDBCompression {
+    None,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+impl From<ConfigRocksDBCompression> for RocksCompression {
+    fn from(compression: ConfigRocksDBCompression) -> Self {
+        match compression {
+            ConfigRocksDBCompression::None => RocksCompression::None,
+            ConfigRocksDBCompression::Snappy => RocksCompression::Snappy,
+            ConfigRocksDBCompression::Lz4 => RocksCompression::Lz4,
+            ConfigRocksDBCompression::Zstd => RocksCompression::Zstd,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigRocksDBColumn {
+    pub block_cache_size:  Option<usize>,
+    pub compression:       Option<ConfigRocksDBCompression>,
+    pub bloom_filter_bits: Option<i32>,
+    pub write_buffer_size: Option<usize>,
+}
+
+impl From<&ConfigRocksDBColumn> for RocksColumnOptions {
+    fn from(column: &ConfigRocksDBColumn) -> Self {
+        Self {
+            block_cache_size:  column.block_cache_size,
+            compression:       column.compression.map(RocksCompression::from),
+            bloom_filter_bits: column.bloom_filter_bits,
+            write_buffer_size: column.write_buffer_size,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct ConfigRocksDB {
-    pub max_open_files: i32,
+    pub max_open_files:     i32,
+    #[serde(default)]
+    pub block:              ConfigRocksDBColumn,
+    #[serde(default)]
+    pub receipt:            ConfigRocksDBColumn,
+    #[serde(default)]
+    pub signed_transaction: ConfigRocksDBColumn,
+    #[serde(default)]
+    pub wal:                ConfigRocksDBColumn,
+    #[serde(default)]
+    pub state:              ConfigRocksDBColumn,
 }
 
 impl Default for ConfigRocksDB {
     fn default() -> Self {
-        Self { max_open_files: 64 }
+        Self {
+            max_open_files:     64,
+            block:              ConfigRocksDBColumn::default(),
+            receipt:            ConfigRocksDBColumn::default(),
+            signed_transaction: ConfigRocksDBColumn::default(),
+            wal:                ConfigRocksDBColumn::default(),
+            state:              ConfigRocksDBColumn::default(),
+        }
+    }
+}
+
+impl ConfigRocksDB {
+    pub fn storage_options(&self) -> HashMap<StorageCategory, RocksColumnOptions> {
+        let mut options = HashMap::new();
+        options.insert(StorageCategory::Block, (&self.block).into());
+        options.insert(StorageCategory::Receipt, (&self.receipt).into());
+        options.insert(
+            StorageCategory::SignedTransaction,
+            (&self.signed_transaction).into(),
+        );
+        options.insert(StorageCategory::Wal, (&self.wal).into());
+        options
+    }
+
+    pub fn state_options(&self) -> RocksColumnOptions {
+        (&self.state).into()
     }
 }
 
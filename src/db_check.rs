@@ -0,0 +1,674 @@
+use std::fmt;
+use std::sync::Arc;
+
+use derive_more::Display;
+
+use common_merkle::Merkle;
+use core_storage::{
+    BlockSchema, HashBlockSchema, LatestBlockSchema, LatestProofSchema, OverlordWalSchema,
+    ReceiptSchema, TransactionSchema, LATEST_BLOCK_KEY, LATEST_PROOF_KEY, OVERLORD_WAL_KEY,
+};
+use framework::executor::{scheduled_call_id, MAX_SCHEDULED_CALLS};
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::{StorageAdapter, StorageBatch};
+use protocol::types::{Block, Hash, MerkleRoot, Receipt};
+use protocol::ProtocolResult;
+
+use crate::MainError;
+
+#[derive(Debug, Display)]
+pub enum BlockIssue {
+    #[display(fmt = "block not found")]
+    MissingBlock,
+
+    #[display(fmt = "pre_hash {:?} does not match the previous block {:?}", _0, _1)]
+    BrokenLink(Hash, Hash),
+
+    #[display(fmt = "order_root {:?} does not match transactions root {:?}", _0, _1)]
+    OrderRootMismatch(Hash, Hash),
+
+    #[display(fmt = "transaction {:?} not found", _0)]
+    MissingTransaction(Hash),
+
+    #[display(
+        fmt = "exec height goes from {} to {} but {} confirm roots are recorded",
+        from,
+        to,
+        len
+    )]
+    ExecHeightMismatch { from: u64, to: u64, len: usize },
+
+    #[display(fmt = "confirm_root of height {} does not match its order_root", _0)]
+    ConfirmRootMismatch(u64),
+
+    #[display(fmt = "receipt {:?} of height {} not found", _1, _0)]
+    MissingReceipt(u64, Hash),
+
+    #[display(fmt = "receipt_root of height {} does not match stored receipts", _0)]
+    ReceiptRootMismatch(u64),
+
+    #[display(fmt = "state_root {:?} not found in the trie db", _0)]
+    MissingStateRoot(MerkleRoot),
+}
+
+#[derive(Debug, Default)]
+pub struct DbCheckReport {
+    pub latest_height:    u64,
+    /// `None` if even the genesis block is broken.
+    pub last_good_height: Option<u64>,
+    pub issues:           Vec<(u64, BlockIssue)>,
+    pub truncated:        bool,
+}
+
+impl DbCheckReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for DbCheckReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "latest height: {}", self.latest_height)?;
+        for (height, issue) in self.issues.iter() {
+            writeln!(f, "height {}: {}", height, issue)?;
+        }
+
+        match self.last_good_height {
+            Some(height) if self.is_ok() => write!(f, "all blocks up to {} are good", height),
+            Some(height) if self.truncated => write!(f, "truncated to height {}", height),
+            Some(height) => write!(f, "last good height: {}", height),
+            None => write!(f, "no good block found"),
+        }
+    }
+}
+
+/// Walk the chain from genesis to the latest block and check every block
+/// against the stored transactions, receipts and state. With `truncate`, the
+/// chain is cut back to the last good height, so that the node can sync the
+/// rest again.
+pub async fn check_db<Adapter: StorageAdapter, DB: cita_trie::DB>(
+    adapter: Arc<Adapter>,
+    trie_db: Arc<DB>,
+    truncate: bool,
+) -> ProtocolResult<DbCheckReport> {
+    let latest_block = adapter
+        .get::<LatestBlockSchema>(LATEST_BLOCK_KEY.clone())
+        .await?
+        .ok_or_else(|| MainError::Other("latest block not found".to_owned()))?;
+
+    let mut report = DbCheckReport {
+        latest_height: latest_block.header.height,
+        ..Default::default()
+    };
+    let mut opt_prev: Option<Block> = None;
+
+    for height in 0..=report.latest_height {
+        let block = match adapter.get::<BlockSchema>(height).await? {
+            Some(block) => block,
+            None => {
+                report.issues.push((height, BlockIssue::MissingBlock));
+                opt_prev = None;
+                continue;
+            }
+        };
+
+        let issues = check_block(&*adapter, &*trie_db, opt_prev.as_ref(), &block).await?;
+        if issues.is_empty() && report.issues.is_empty() {
+            report.last_good_height = Some(height);
+        }
+        report
+            .issues
+            .extend(issues.into_iter().map(|issue| (height, issue)));
+
+        opt_prev = Some(block);
+    }
+
+    if truncate && !report.is_ok() {
+        if let Some(last_good_height) = report.last_good_height {
+            truncate_to(&*adapter, last_good_height, report.latest_height).await?;
+            report.truncated = true;
+        }
+    }
+
+    Ok(report)
+}
+
+async fn check_block<Adapter: StorageAdapter, DB: cita_trie::DB>(
+    adapter: &Adapter,
+    trie_db: &DB,
+    opt_prev: Option<&Block>,
+    block: &Block,
+) -> ProtocolResult<Vec<BlockIssue>> {
+    let mut issues = Vec::new();
+    let header = &block.header;
+
+    if header.height > 0 {
+        if let Some(prev) = opt_prev {
+            let prev_hash = Hash::digest(prev.encode_fixed()?);
+            if header.pre_hash != prev_hash {
+                issues.push(BlockIssue::BrokenLink(header.pre_hash.clone(), prev_hash));
+            }
+        }
+
+        let order_root = merkle_root(block.ordered_tx_hashes.clone());
+        if header.order_root != order_root {
+            issues.push(BlockIssue::OrderRootMismatch(
+                header.order_root.clone(),
+                order_root,
+            ));
+        }
+    }
+
+    let txs = adapter
+        .get_batch::<TransactionSchema>(block.ordered_tx_hashes.clone())
+        .await?;
+    for (tx_hash, tx) in block.ordered_tx_hashes.iter().zip(txs.iter()) {
+        if tx.is_none() {
+            issues.push(BlockIssue::MissingTransaction(tx_hash.clone()));
+        }
+    }
+
+    // The confirm and receipt roots of a block cover the blocks executed
+    // since the previous one was proposed.
+    let from = opt_prev.map_or(0, |prev| prev.header.exec_height) + 1;
+    let to = header.exec_height;
+    let len = header.confirm_root.len();
+    if header.height > 0 && (to + 1 < from || (to + 1 - from) as usize != len) {
+        issues.push(BlockIssue::ExecHeightMismatch { from, to, len });
+    } else if header.height > 0 {
+        for (exec_height, (confirm_root, receipt_root)) in
+            (from..=to).zip(header.confirm_root.iter().zip(header.receipt_root.iter()))
+        {
+            issues.extend(
+                check_executed_block(adapter, exec_height, confirm_root, receipt_root).await?,
+            );
+        }
+    }
+
+    if header.state_root != Hash::from_empty()
+        && !trie_db
+            .contains(&header.state_root.as_bytes())
+            .map_err(|e| MainError::Other(e.to_string()))?
+    {
+        issues.push(BlockIssue::MissingStateRoot(header.state_root.clone()));
+    }
+
+    Ok(issues)
+}
+
+async fn check_executed_block<Adapter: StorageAdapter>(
+    adapter: &Adapter,
+    height: u64,
+    confirm_root: &MerkleRoot,
+    receipt_root: &MerkleRoot,
+) -> ProtocolResult<Vec<BlockIssue>> {
+    let mut issues = Vec::new();
+
+    let block = match adapter.get::<BlockSchema>(height).await? {
+        Some(block) => block,
+        // Reported when the walk reaches this height.
+        None => return Ok(issues),
+    };
+
+    if &block.header.order_root != confirm_root {
+        issues.push(BlockIssue::ConfirmRootMismatch(height));
+    }
+
+    let receipts = adapter
+        .get_batch::<ReceiptSchema>(block.ordered_tx_hashes.clone())
+        .await?;
+    let mut receipt_hashes = Vec::with_capacity(receipts.len());
+    for (tx_hash, receipt) in block.ordered_tx_hashes.iter().zip(receipts.into_iter()) {
+        match receipt {
+            Some(receipt) => receipt_hashes.push(Hash::digest(receipt.encode_fixed()?)),
+            None => issues.push(BlockIssue::MissingReceipt(height, tx_hash.clone())),
+        }
+    }
+    for receipt in get_scheduled_receipts(adapter, height).await?.into_iter() {
+        receipt_hashes.push(Hash::digest(receipt.encode_fixed()?));
+    }
+
+    if issues
+        .iter()
+        .all(|issue| !matches!(issue, BlockIssue::MissingReceipt(..)))
+        && &merkle_root(receipt_hashes) != receipt_root
+    {
+        issues.push(BlockIssue::ReceiptRootMismatch(height));
+    }
+
+    Ok(issues)
+}
+
+// The receipts of the calls scheduled at a height follow the ones of its txs,
+// their ids are taken in order until one is missing.
+async fn get_scheduled_receipts<Adapter: StorageAdapter>(
+    adapter: &Adapter,
+    height: u64,
+) -> ProtocolResult<Vec<Receipt>> {
+    let mut receipts = Vec::new();
+    for index in 0..MAX_SCHEDULED_CALLS {
+        match adapter
+            .get::<ReceiptSchema>(scheduled_call_id(height, index))
+            .await?
+        {
+            Some(receipt) => receipts.push(receipt),
+            None => break,
+        }
+    }
+
+    Ok(receipts)
+}
+
+// Everything stored for the truncated heights goes in one batch: the blocks,
+// their txs and receipts, which are written again when the blocks are synced
+// and executed. The overlord wal is dropped too, it's the consensus state of a
+// truncated height.
+async fn truncate_to<Adapter: StorageAdapter>(
+    adapter: &Adapter,
+    last_good_height: u64,
+    latest_height: u64,
+) -> ProtocolResult<()> {
+    let last_good_block = adapter
+        .get::<BlockSchema>(last_good_height)
+        .await?
+        .ok_or_else(|| MainError::Other(format!("block {} not found", last_good_height)))?;
+    let last_good_hash = Hash::digest(last_good_block.encode_fixed()?);
+
+    let mut batch = StorageBatch::new();
+    for height in last_good_height + 1..=latest_height {
+        if let Some(block) = adapter.get::<BlockSchema>(height).await? {
+            // The proof of the last good block is carried by its child.
+            if height == last_good_height + 1 && block.header.proof.block_hash == last_good_hash {
+                batch
+                    .insert::<LatestProofSchema>(
+                        LATEST_PROOF_KEY.clone(),
+                        block.header.proof.clone(),
+                    )
+                    .await?;
+            }
+
+            for tx_hash in block.ordered_tx_hashes.iter() {
+                batch.remove::<TransactionSchema>(tx_hash.clone()).await?;
+                batch.remove::<ReceiptSchema>(tx_hash.clone()).await?;
+            }
+            for receipt in get_scheduled_receipts(adapter, height).await?.into_iter() {
+                batch.remove::<ReceiptSchema>(receipt.tx_hash).await?;
+            }
+
+            let block_hash = Hash::digest(block.encode_fixed()?);
+            batch.remove::<HashBlockSchema>(block_hash).await?;
+        }
+        batch.remove::<BlockSchema>(height).await?;
+    }
+    batch
+        .insert::<LatestBlockSchema>(LATEST_BLOCK_KEY.clone(), last_good_block)
+        .await?;
+    batch
+        .remove::<OverlordWalSchema>(OVERLORD_WAL_KEY.clone())
+        .await?;
+
+    adapter.write_batch(batch).await
+}
+
+fn merkle_root(hashes: Vec<Hash>) -> MerkleRoot {
+    Merkle::from_hashes(hashes)
+        .get_root_hash()
+        .unwrap_or_else(Hash::from_empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use cita_trie::{MemoryDB, DB};
+
+    use core_storage::adapter::memory::MemoryAdapter;
+    use core_storage::{
+        BlockSchema, HashBlockSchema, LatestBlockSchema, LatestProofSchema, OverlordWalSchema,
+        ReceiptSchema, TransactionSchema, LATEST_BLOCK_KEY, LATEST_PROOF_KEY, OVERLORD_WAL_KEY,
+    };
+    use framework::executor::scheduled_call_id;
+    use protocol::fixed_codec::FixedCodec;
+    use protocol::traits::{ServiceResponse, StorageAdapter};
+    use protocol::types::{
+        Address, Block, BlockHeader, Hash, Proof, RawTransaction, Receipt, ReceiptResponse,
+        SignedTransaction, TransactionRequest,
+    };
+    use protocol::Bytes;
+
+    use super::{check_db, merkle_root, BlockIssue, DbCheckReport};
+
+    const LATEST_HEIGHT: u64 = 3;
+
+    struct MockBlock {
+        block:    Block,
+        txs:      Vec<SignedTransaction>,
+        receipts: Vec<Receipt>,
+    }
+
+    fn block_hash(block: &Block) -> Hash {
+        Hash::digest(block.encode_fixed().unwrap())
+    }
+
+    fn receipt_root(receipts: &[Receipt]) -> Hash {
+        merkle_root(
+            receipts
+                .iter()
+                .map(|receipt| Hash::digest(receipt.encode_fixed().unwrap()))
+                .collect(),
+        )
+    }
+
+    // Every block is proposed after its parent is executed, so the roots of a
+    // block cover its parent. A call is scheduled at height 2.
+    fn mock_chain() -> Vec<MockBlock> {
+        let mut chain: Vec<MockBlock> = Vec::new();
+
+        for height in 0..=LATEST_HEIGHT {
+            let txs = if height == 0 {
+                vec![]
+            } else {
+                vec![mock_signed_tx(height, 0), mock_signed_tx(height, 1)]
+            };
+            let mut receipts = txs
+                .iter()
+                .map(|tx| mock_receipt(height, tx.tx_hash.clone()))
+                .collect::<Vec<_>>();
+            if height == 2 {
+                receipts.push(mock_receipt(height, scheduled_call_id(height, 0)));
+            }
+            let ordered_tx_hashes = txs.iter().map(|tx| tx.tx_hash.clone()).collect::<Vec<_>>();
+
+            let (pre_hash, confirm_root, receipt_root) = match chain.last() {
+                Some(prev) if height > 1 => (
+                    block_hash(&prev.block),
+                    vec![prev.block.header.order_root.clone()],
+                    vec![receipt_root(&prev.receipts)],
+                ),
+                Some(prev) => (block_hash(&prev.block), vec![], vec![]),
+                None => (Hash::from_empty(), vec![], vec![]),
+            };
+
+            let header = BlockHeader {
+                chain_id: Hash::digest(Bytes::from("chain")),
+                height,
+                exec_height: height.saturating_sub(1),
+                pre_hash: pre_hash.clone(),
+                timestamp: 1000 + height,
+                logs_bloom: Default::default(),
+                order_root: merkle_root(ordered_tx_hashes.clone()),
+                confirm_root,
+                state_root: Hash::digest(Bytes::from(format!("state {}", height))),
+                receipt_root,
+                cycles_used: vec![],
+                proposer: Address::from_hex("0xCAB8EEA4799C21379C20EF5BAA2CC8AF1BEC475B").unwrap(),
+                proof: Proof {
+                    height:     height.saturating_sub(1),
+                    round:      0,
+                    block_hash: pre_hash,
+                    signature:  Default::default(),
+                    bitmap:     Default::default(),
+                },
+                validator_version: 1,
+                validators: vec![],
+                seed_signature: Bytes::new(),
+            };
+
+            chain.push(MockBlock {
+                block: Block {
+                    header,
+                    ordered_tx_hashes,
+                },
+                txs,
+                receipts,
+            });
+        }
+
+        chain
+    }
+
+    async fn save_chain(chain: &[MockBlock]) -> (Arc<MemoryAdapter>, Arc<MemoryDB>) {
+        let adapter = Arc::new(MemoryAdapter::new());
+        let trie_db = Arc::new(MemoryDB::new(false));
+
+        for mock in chain.iter() {
+            let header = &mock.block.header;
+            adapter
+                .insert::<BlockSchema>(header.height, mock.block.clone())
+                .await
+                .unwrap();
+            adapter
+                .insert::<HashBlockSchema>(block_hash(&mock.block), header.height)
+                .await
+                .unwrap();
+            for tx in mock.txs.iter() {
+                adapter
+                    .insert::<TransactionSchema>(tx.tx_hash.clone(), tx.clone())
+                    .await
+                    .unwrap();
+            }
+            for receipt in mock.receipts.iter() {
+                adapter
+                    .insert::<ReceiptSchema>(receipt.tx_hash.clone(), receipt.clone())
+                    .await
+                    .unwrap();
+            }
+            trie_db
+                .insert(header.state_root.as_bytes().to_vec(), b"state".to_vec())
+                .unwrap();
+        }
+
+        let latest = &chain.last().unwrap().block;
+        adapter
+            .insert::<LatestBlockSchema>(LATEST_BLOCK_KEY.clone(), latest.clone())
+            .await
+            .unwrap();
+        adapter
+            .insert::<LatestProofSchema>(LATEST_PROOF_KEY.clone(), latest.header.proof.clone())
+            .await
+            .unwrap();
+        adapter
+            .insert::<OverlordWalSchema>(OVERLORD_WAL_KEY.clone(), Bytes::from("wal"))
+            .await
+            .unwrap();
+
+        (adapter, trie_db)
+    }
+
+    async fn check_chain(chain: &[MockBlock]) -> DbCheckReport {
+        let (adapter, trie_db) = save_chain(chain).await;
+        check_db(adapter, trie_db, false).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_check_good_chain() {
+        let report = check_chain(&mock_chain()).await;
+
+        assert!(report.is_ok());
+        assert_eq!(report.latest_height, LATEST_HEIGHT);
+        assert_eq!(report.last_good_height, Some(LATEST_HEIGHT));
+    }
+
+    #[tokio::test]
+    async fn test_check_broken_pre_hash() {
+        let mut chain = mock_chain();
+        chain[2].block.header.pre_hash = Hash::digest(Bytes::from("broken"));
+
+        let report = check_chain(&chain).await;
+        assert_eq!(report.last_good_height, Some(1));
+        assert!(matches!(report.issues[0], (2, BlockIssue::BrokenLink(..))));
+    }
+
+    #[tokio::test]
+    async fn test_check_wrong_order_root() {
+        let mut chain = mock_chain();
+        chain[2].block.header.order_root = Hash::digest(Bytes::from("wrong"));
+
+        let report = check_chain(&chain).await;
+        assert_eq!(report.last_good_height, Some(1));
+        assert!(matches!(
+            report.issues[0],
+            (2, BlockIssue::OrderRootMismatch(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_wrong_receipt_root() {
+        let mut chain = mock_chain();
+        chain[3].block.header.receipt_root = vec![Hash::digest(Bytes::from("wrong"))];
+
+        let report = check_chain(&chain).await;
+        assert_eq!(report.last_good_height, Some(2));
+        assert_eq!(report.issues.len(), 1);
+        assert!(matches!(
+            report.issues[0],
+            (3, BlockIssue::ReceiptRootMismatch(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_check_missing_tx() {
+        let mut chain = mock_chain();
+        let missing = chain[2].txs.remove(0);
+
+        let report = check_chain(&chain).await;
+        assert_eq!(report.last_good_height, Some(1));
+        match &report.issues[0] {
+            (2, BlockIssue::MissingTransaction(tx_hash)) => assert_eq!(tx_hash, &missing.tx_hash),
+            issue => panic!("unexpected issue {:?}", issue),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_missing_state_root() {
+        let chain = mock_chain();
+        let (adapter, trie_db) = save_chain(&chain).await;
+        let state_root = &chain[3].block.header.state_root;
+        trie_db.remove(&state_root.as_bytes()).unwrap();
+
+        let report = check_db(adapter, trie_db, false).await.unwrap();
+        assert_eq!(report.last_good_height, Some(2));
+        assert_eq!(report.issues.len(), 1);
+        assert!(matches!(
+            report.issues[0],
+            (3, BlockIssue::MissingStateRoot(..))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_truncate() {
+        let mut chain = mock_chain();
+        chain[2].txs.remove(0);
+        let (adapter, trie_db) = save_chain(&chain).await;
+
+        let report = check_db(Arc::clone(&adapter), Arc::clone(&trie_db), true)
+            .await
+            .unwrap();
+        assert!(report.truncated);
+        assert_eq!(report.last_good_height, Some(1));
+
+        for mock in chain[2..].iter() {
+            let block = &mock.block;
+            assert!(adapter
+                .get::<BlockSchema>(block.header.height)
+                .await
+                .unwrap()
+                .is_none());
+            assert!(adapter
+                .get::<HashBlockSchema>(block_hash(block))
+                .await
+                .unwrap()
+                .is_none());
+            for tx_hash in block.ordered_tx_hashes.iter() {
+                assert!(adapter
+                    .get::<TransactionSchema>(tx_hash.clone())
+                    .await
+                    .unwrap()
+                    .is_none());
+            }
+            for receipt in mock.receipts.iter() {
+                assert!(adapter
+                    .get::<ReceiptSchema>(receipt.tx_hash.clone())
+                    .await
+                    .unwrap()
+                    .is_none());
+            }
+        }
+        // The receipts of the last good block are kept.
+        for receipt in chain[1].receipts.iter() {
+            assert!(adapter
+                .get::<ReceiptSchema>(receipt.tx_hash.clone())
+                .await
+                .unwrap()
+                .is_some());
+        }
+
+        let latest = adapter
+            .get::<LatestBlockSchema>(LATEST_BLOCK_KEY.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.header.height, 1);
+        let proof = adapter
+            .get::<LatestProofSchema>(LATEST_PROOF_KEY.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(proof.block_hash, block_hash(&chain[1].block));
+        assert!(adapter
+            .get::<OverlordWalSchema>(OVERLORD_WAL_KEY.clone())
+            .await
+            .unwrap()
+            .is_none());
+
+        let report = check_db(adapter, trie_db, false).await.unwrap();
+        assert!(report.is_ok());
+        assert_eq!(report.latest_height, 1);
+    }
+
+    fn mock_signed_tx(height: u64, index: u64) -> SignedTransaction {
+        let nonce = Hash::digest(Bytes::from(format!("tx {} {}", height, index)));
+        let raw = RawTransaction {
+            chain_id:     Hash::digest(Bytes::from("chain")),
+            nonce:        nonce.clone(),
+            timeout:      height + 10,
+            cycles_limit: 10,
+            cycles_price: 1,
+            request:      TransactionRequest {
+                service_name: "test".to_owned(),
+                method:       "test".to_owned(),
+                payload:      "test".to_owned(),
+            },
+        };
+
+        SignedTransaction {
+            raw,
+            tx_hash: nonce,
+            pubkey: Default::default(),
+            signature: Default::default(),
+            scheme: Default::default(),
+            kind: Default::default(),
+        }
+    }
+
+    fn mock_receipt(height: u64, tx_hash: Hash) -> Receipt {
+        Receipt {
+            state_root: Hash::digest(Bytes::from(format!("state {}", height))),
+            height,
+            tx_hash,
+            cycles_used: 10,
+            events: vec![],
+            response: ReceiptResponse {
+                service_name: "test".to_owned(),
+                method:       "test".to_owned(),
+                response:     ServiceResponse::<String> {
+                    code:          0,
+                    succeed_data:  "ok".to_owned(),
+                    error_message: "".to_owned(),
+                },
+            },
+            cycles_refunded: 0,
+            cycles_breakdown: vec![],
+        }
+    }
+}
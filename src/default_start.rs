@@ -35,9 +35,15 @@ use core_mempool::{
 use core_network::{NetworkConfig, NetworkService};
 use core_storage::{adapter::rocks::RocksAdapter, ImplStorage};
 use framework::binding::state::RocksTrieDB;
-use framework::executor::{ServiceExecutor, ServiceExecutorFactory};
-use protocol::traits::{APIAdapter, Context, MemPool, NodeInfo, ServiceMapping, Storage};
-use protocol::types::{Address, Block, BlockHeader, Genesis, Hash, Metadata, Proof, Validator};
+use framework::executor::{
+    DefaultNonceQuerier, DefaultSignatureVerifier, ServiceExecutor, ServiceExecutorFactory,
+};
+use protocol::traits::{
+    APIAdapter, Context, ExecutorOptions, MemPool, NodeInfo, ServiceMapping, Storage,
+};
+use protocol::types::{
+    Address, Block, BlockHeader, Genesis, Hash, Metadata, NonceMode, Proof, Validator,
+};
 use protocol::{fixed_codec::FixedCodec, ProtocolResult};
 
 use crate::config::Config;
@@ -66,9 +72,10 @@ pub async fn create_genesis<Mapping: 'static + ServiceMapping>(
 
     // Init Block db
     let path_block = config.data_path_for_block();
-    let rocks_adapter = Arc::new(RocksAdapter::new(
+    let rocks_adapter = Arc::new(RocksAdapter::with_options(
         path_block,
         config.rocksdb.max_open_files,
+        config.rocksdb.storage_options(),
     )?);
     let storage = Arc::new(ImplStorage::new(Arc::clone(&rocks_adapter)));
 
@@ -84,14 +91,6 @@ pub async fn create_genesis<Mapping: 'static + ServiceMapping>(
         }
     };
 
-    // Init trie db
-    let path_state = config.data_path_for_state();
-    let trie_db = Arc::new(RocksTrieDB::new(
-        path_state,
-        config.executor.light,
-        config.rocksdb.max_open_files,
-    )?);
-
     // Init genesis
     let genesis_state_root = ServiceExecutor::create_genesis(
         genesis.services.clone(),
@@ -114,6 +113,7 @@ pub async fn create_genesis<Mapping: 'static + ServiceMapping>(
         receipt_root: vec![],
         cycles_used: vec![],
         proposer: Address::from_hex("0x0000000000000000000000000000000000000000")?,
+        seed_signature: Bytes::new(),
         proof: Proof {
             height:     0,
             round:      0,
@@ -130,10 +130,13 @@ pub async fn create_genesis<Mapping: 'static + ServiceMapping>(
         ordered_tx_hashes: vec![],
     };
     storage
-        .insert_block(Context::new(), genesis_block.clone())
-        .await?;
-    storage
-        .update_latest_proof(Context::new(), latest_proof)
+        .commit_block_bundle(
+            Context::new(),
+            genesis_block.clone(),
+            latest_proof,
+            vec![],
+            vec![],
+        )
         .await?;
 
     log::info!("The genesis block is created {:?}", genesis_block);
@@ -145,16 +148,27 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
     service_mapping: Arc<Mapping>,
 ) -> ProtocolResult<()> {
     log::info!("node starts");
+
     // Init Block db
     let path_block = config.data_path_for_block();
     log::info!("Data path for block: {:?}", path_block);
 
-    let rocks_adapter = Arc::new(RocksAdapter::new(
+    let rocks_adapter = Arc::new(RocksAdapter::with_options(
         path_block.clone(),
         config.rocksdb.max_open_files,
+        config.rocksdb.storage_options(),
     )?);
     let storage = Arc::new(ImplStorage::new(Arc::clone(&rocks_adapter)));
 
+    // Check the block db for partial writes left by a crash
+    let report = storage.check_consistency().await?;
+    for repaired in report.repaired.iter() {
+        log::warn!("[storage] repaired: {}", repaired);
+    }
+    for unrepaired in report.unrepaired.iter() {
+        log::error!("[storage] inconsistent: {}", unrepaired);
+    }
+
     // Init network
     let network_config = NetworkConfig::new()
         .max_connections(config.network.max_connected_peers.clone())
@@ -197,14 +211,36 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
         .listen(config.network.listening_address)
         .await?;
 
+    // Init trie db
+    let path_state = config.data_path_for_state();
+    let trie_db = Arc::new(RocksTrieDB::with_options(
+        path_state,
+        config.executor.light,
+        config.rocksdb.max_open_files,
+        config.rocksdb.state_options(),
+    )?);
+
     // Init mempool
     let current_block = storage.get_latest_block(Context::new()).await?;
-    let mempool_adapter = DefaultMemPoolAdapter::<Secp256k1, _, _>::new(
+    let mut mempool_adapter = DefaultMemPoolAdapter::<Secp256k1, _, _>::new(
         network_service.handle(),
         Arc::clone(&storage),
         config.mempool.broadcast_txs_size,
         config.mempool.broadcast_txs_interval,
-    );
+    )
+    .with_signature_verifier(Arc::new(DefaultSignatureVerifier::new(
+        Arc::clone(&storage),
+        Arc::clone(&trie_db),
+        Arc::clone(&service_mapping),
+    )));
+    if service_mapping.nonce_mode() == NonceMode::Account {
+        let nonce_querier = DefaultNonceQuerier::new(
+            Arc::clone(&storage),
+            Arc::clone(&trie_db),
+            Arc::clone(&service_mapping),
+        );
+        mempool_adapter = mempool_adapter.with_nonce_querier(Arc::new(nonce_querier));
+    }
     let mempool = Arc::new(HashMemPool::new(
         config.mempool.pool_size as usize,
         mempool_adapter,
@@ -212,10 +248,11 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
 
     // Init trie db
     let path_state = config.data_path_for_state();
-    let trie_db = Arc::new(RocksTrieDB::new(
+    let trie_db = Arc::new(RocksTrieDB::with_options(
         path_state,
         config.executor.light,
         config.rocksdb.max_open_files,
+        config.rocksdb.state_options(),
     )?);
 
     // self private key
@@ -363,6 +400,9 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&service_mapping),
             status_agent.clone(),
             Arc::clone(&crypto),
+            ExecutorOptions {
+                parallel: config.executor.parallel,
+            },
         )?;
 
     let exec_demon = consensus_adapter.take_exec_demon();
@@ -390,7 +430,9 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
     ));
 
     // Re-execute block from exec_height + 1 to current_height, so that init the
-    // lost current status.
+    // lost current status. The blocks are committed before the executor saves
+    // their receipts, so save them again in case the node stopped in between,
+    // this repairs the unexecuted blocks found by the consistency check.
     log::info!("Re-execute from {} to {}", exec_height + 1, current_height);
     for height in exec_height + 1..=current_height {
         let block = storage.get_block_by_height(Context::new(), height).await?;
@@ -398,9 +440,14 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             .get_transactions(Context::new(), block.ordered_tx_hashes.clone())
             .await?;
         let rich_block = RichBlock { block, txs };
-        let _ = synchronization
+        let resp = synchronization
             .exec_block(Context::new(), rich_block, status_agent.clone())
             .await?;
+        let receipts = resp.all_receipts().cloned().collect();
+        storage.insert_receipts(Context::new(), receipts).await?;
+    }
+    for height in report.unexecuted.iter() {
+        log::warn!("[storage] repaired: receipts of block {} saved", height);
     }
 
     // register consensus
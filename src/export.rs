@@ -0,0 +1,332 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+use serde_derive::{Deserialize, Serialize};
+
+use protocol::fixed_codec::FixedCodec;
+use protocol::traits::{Context, Storage};
+use protocol::types::{Block, Hash, Receipt, SignedTransaction};
+use protocol::ProtocolResult;
+
+use crate::MainError;
+
+const CHECKPOINT_FILE: &str = "checkpoint.json";
+const CHECKPOINT_INTERVAL: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    NdJson,
+}
+
+impl FromStr for ExportFormat {
+    type Err = MainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ndjson" => Ok(ExportFormat::NdJson),
+            _ => Err(MainError::Other(format!("unsupported export format {}", s))),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub output: PathBuf,
+    pub format: ExportFormat,
+    pub from:   u64,
+    /// Defaults to the latest executed height.
+    pub to:     Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportBlock {
+    pub height:            u64,
+    pub exec_height:       u64,
+    pub block_hash:        String,
+    pub pre_hash:          String,
+    pub chain_id:          String,
+    pub timestamp:         u64,
+    pub order_root:        String,
+    pub state_root:        String,
+    pub confirm_root:      Vec<String>,
+    pub receipt_root:      Vec<String>,
+    pub cycles_used:       Vec<u64>,
+    pub proposer:          String,
+    pub validator_version: u64,
+    pub tx_count:          u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportTransaction {
+    pub height:       u64,
+    pub index:        u64,
+    pub tx_hash:      String,
+    pub chain_id:     String,
+    pub nonce:        String,
+    pub timeout:      u64,
+    pub cycles_limit: u64,
+    pub cycles_price: u64,
+    pub service_name: String,
+    pub method:       String,
+    pub payload:      String,
+    pub pubkey:       String,
+    pub signature:    String,
+    pub scheme:       String,
+    pub kind:         String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportReceipt {
+    pub height:          u64,
+    pub index:           u64,
+    pub tx_hash:         String,
+    pub state_root:      String,
+    pub cycles_used:     u64,
+    pub cycles_refunded: u64,
+    pub service_name:    String,
+    pub method:          String,
+    pub code:            u64,
+    pub succeed_data:    String,
+    pub error_message:   String,
+    pub event_count:     u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportEvent {
+    pub height:  u64,
+    pub tx_hash: String,
+    pub index:   u64,
+    pub service: String,
+    pub name:    String,
+    pub topics:  Vec<String>,
+    pub data:    String,
+}
+
+/// Where the last run stopped. The offsets are the lengths of the output
+/// files at that height, anything written after them is discarded on resume.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Checkpoint {
+    height:       u64,
+    blocks:       u64,
+    transactions: u64,
+    receipts:     u64,
+    events:       u64,
+}
+
+struct Output {
+    dir:          PathBuf,
+    blocks:       BufWriter<File>,
+    transactions: BufWriter<File>,
+    receipts:     BufWriter<File>,
+    events:       BufWriter<File>,
+}
+
+impl Output {
+    fn open(
+        dir: &Path,
+        format: ExportFormat,
+        opt_checkpoint: Option<&Checkpoint>,
+    ) -> ProtocolResult<Self> {
+        let ext = match format {
+            ExportFormat::NdJson => "ndjson",
+        };
+        let open = |name: &str, offset: u64| -> ProtocolResult<BufWriter<File>> {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .write(true)
+                .open(dir.join(format!("{}.{}", name, ext)))
+                .map_err(MainError::Io)?;
+            file.set_len(offset).map_err(MainError::Io)?;
+            file.seek(SeekFrom::End(0)).map_err(MainError::Io)?;
+
+            Ok(BufWriter::new(file))
+        };
+
+        let cp = opt_checkpoint.cloned().unwrap_or_default();
+        Ok(Output {
+            dir:          dir.to_path_buf(),
+            blocks:       open("blocks", cp.blocks)?,
+            transactions: open("transactions", cp.transactions)?,
+            receipts:     open("receipts", cp.receipts)?,
+            events:       open("events", cp.events)?,
+        })
+    }
+
+    fn checkpoint(&mut self, height: u64) -> ProtocolResult<()> {
+        let mut offset = |writer: &mut BufWriter<File>| -> ProtocolResult<u64> {
+            writer.flush().map_err(MainError::Io)?;
+            let file = writer.get_ref();
+            file.sync_data().map_err(MainError::Io)?;
+            Ok(file.metadata().map_err(MainError::Io)?.len())
+        };
+
+        let checkpoint = Checkpoint {
+            height,
+            blocks: offset(&mut self.blocks)?,
+            transactions: offset(&mut self.transactions)?,
+            receipts: offset(&mut self.receipts)?,
+            events: offset(&mut self.events)?,
+        };
+
+        let tmp = self.dir.join(format!("{}.tmp", CHECKPOINT_FILE));
+        fs::write(
+            &tmp,
+            serde_json::to_vec(&checkpoint).map_err(MainError::Json)?,
+        )
+        .map_err(MainError::Io)?;
+        fs::rename(&tmp, self.dir.join(CHECKPOINT_FILE)).map_err(MainError::Io)?;
+        Ok(())
+    }
+}
+
+/// Stream blocks, transactions, receipts and events to the output directory,
+/// one file per kind. If a checkpoint is found in the output directory, the
+/// export resumes after the last exported height. Returns the last exported
+/// height.
+pub async fn export<S: Storage>(
+    storage: Arc<S>,
+    options: ExportOptions,
+) -> ProtocolResult<Option<u64>> {
+    fs::create_dir_all(&options.output).map_err(MainError::Io)?;
+
+    let opt_checkpoint = match fs::read(options.output.join(CHECKPOINT_FILE)) {
+        Ok(bytes) => Some(serde_json::from_slice::<Checkpoint>(&bytes).map_err(MainError::Json)?),
+        Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(MainError::Io(e).into()),
+    };
+
+    let from = match opt_checkpoint {
+        Some(ref cp) => cp.height + 1,
+        None => options.from,
+    };
+
+    // Receipts only exist for executed blocks.
+    let latest_block = storage.get_latest_block(Context::new()).await?;
+    let to = options
+        .to
+        .unwrap_or(latest_block.header.exec_height)
+        .min(latest_block.header.exec_height);
+
+    if from > to {
+        return Ok(opt_checkpoint.map(|cp| cp.height));
+    }
+
+    let mut output = Output::open(&options.output, options.format, opt_checkpoint.as_ref())?;
+
+    for height in from..=to {
+        let block = storage.get_block_by_height(Context::new(), height).await?;
+        let txs = storage
+            .get_transactions(Context::new(), block.ordered_tx_hashes.clone())
+            .await?;
+        let receipts = storage
+            .get_receipts(Context::new(), block.ordered_tx_hashes.clone())
+            .await?;
+
+        if txs.len() != block.ordered_tx_hashes.len()
+            || receipts.len() != block.ordered_tx_hashes.len()
+        {
+            return Err(MainError::Other(format!(
+                "transactions or receipts of height {} are missing",
+                height
+            ))
+            .into());
+        }
+
+        write_line(&mut output.blocks, &export_block(&block)?)?;
+        for (index, stx) in txs.into_iter().enumerate() {
+            write_line(
+                &mut output.transactions,
+                &export_transaction(height, index as u64, stx),
+            )?;
+        }
+        for (index, receipt) in receipts.into_iter().enumerate() {
+            let tx_hash = receipt.tx_hash.as_hex();
+            for (event_index, event) in receipt.events.iter().enumerate() {
+                write_line(&mut output.events, &ExportEvent {
+                    height,
+                    tx_hash: tx_hash.clone(),
+                    index: event_index as u64,
+                    service: event.service.clone(),
+                    name: event.name.clone(),
+                    topics: event.topics.clone(),
+                    data: event.data.clone(),
+                })?;
+            }
+            write_line(&mut output.receipts, &export_receipt(index as u64, receipt))?;
+        }
+
+        if height == to || (height - from + 1) % CHECKPOINT_INTERVAL == 0 {
+            output.checkpoint(height)?;
+            log::info!("[export]: exported up to height {}", height);
+        }
+    }
+
+    Ok(Some(to))
+}
+
+fn write_line<T: serde::Serialize>(writer: &mut BufWriter<File>, row: &T) -> ProtocolResult<()> {
+    serde_json::to_writer(&mut *writer, row).map_err(MainError::Json)?;
+    writer.write_all(b"\n").map_err(MainError::Io)?;
+    Ok(())
+}
+
+fn export_block(block: &Block) -> ProtocolResult<ExportBlock> {
+    let header = &block.header;
+
+    Ok(ExportBlock {
+        height:            header.height,
+        exec_height:       header.exec_height,
+        block_hash:        Hash::digest(block.encode_fixed()?).as_hex(),
+        pre_hash:          header.pre_hash.as_hex(),
+        chain_id:          header.chain_id.as_hex(),
+        timestamp:         header.timestamp,
+        order_root:        header.order_root.as_hex(),
+        state_root:        header.state_root.as_hex(),
+        confirm_root:      header.confirm_root.iter().map(Hash::as_hex).collect(),
+        receipt_root:      header.receipt_root.iter().map(Hash::as_hex).collect(),
+        cycles_used:       header.cycles_used.clone(),
+        proposer:          header.proposer.as_hex(),
+        validator_version: header.validator_version,
+        tx_count:          block.ordered_tx_hashes.len() as u64,
+    })
+}
+
+fn export_transaction(height: u64, index: u64, stx: SignedTransaction) -> ExportTransaction {
+    ExportTransaction {
+        height,
+        index,
+        tx_hash: stx.tx_hash.as_hex(),
+        chain_id: stx.raw.chain_id.as_hex(),
+        nonce: stx.raw.nonce.as_hex(),
+        timeout: stx.raw.timeout,
+        cycles_limit: stx.raw.cycles_limit,
+        cycles_price: stx.raw.cycles_price,
+        service_name: stx.raw.request.service_name,
+        method: stx.raw.request.method,
+        payload: stx.raw.request.payload,
+        pubkey: "0x".to_owned() + &hex::encode(stx.pubkey),
+        signature: "0x".to_owned() + &hex::encode(stx.signature),
+        scheme: stx.scheme.name().to_owned(),
+        kind: stx.kind.name().to_owned(),
+    }
+}
+
+fn export_receipt(index: u64, receipt: Receipt) -> ExportReceipt {
+    ExportReceipt {
+        height: receipt.height,
+        index,
+        tx_hash: receipt.tx_hash.as_hex(),
+        state_root: receipt.state_root.as_hex(),
+        cycles_used: receipt.cycles_used,
+        cycles_refunded: receipt.cycles_refunded,
+        service_name: receipt.response.service_name,
+        method: receipt.response.method,
+        code: receipt.response.response.code,
+        succeed_data: receipt.response.response.succeed_data,
+        error_message: receipt.response.response.error_message,
+        event_count: receipt.events.len() as u64,
+    }
+}
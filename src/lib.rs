@@ -1,19 +1,28 @@
 #![feature(async_closure)]
 
 mod config;
+mod db_check;
 mod default_start;
+mod export;
 
 use std::fs;
 use std::sync::Arc;
 
 use derive_more::{Display, From};
 
+use core_storage::{adapter::rocks::RocksAdapter, ImplStorage};
+use framework::binding::state::RocksTrieDB;
 use protocol::traits::ServiceMapping;
 use protocol::types::{Block, Genesis};
 use protocol::{ProtocolError, ProtocolErrorKind, ProtocolResult};
 
 use crate::config::Config;
+use crate::db_check::check_db;
 use crate::default_start::{create_genesis, start};
+use crate::export::export;
+
+pub use crate::db_check::{BlockIssue, DbCheckReport};
+pub use crate::export::{ExportFormat, ExportOptions};
 
 #[derive(Default)]
 pub struct MutaBuilder<Mapping: ServiceMapping> {
@@ -112,6 +121,38 @@ impl<Mapping: 'static + ServiceMapping> Muta<Mapping> {
         Ok(())
     }
 
+    /// Check the block db and the state db for corruption, optionally
+    /// truncating the chain to the last good height.
+    pub fn check_db(self, truncate: bool) -> ProtocolResult<DbCheckReport> {
+        let rocks_adapter = Arc::new(RocksAdapter::with_options(
+            self.config.data_path_for_block(),
+            self.config.rocksdb.max_open_files,
+            self.config.rocksdb.storage_options(),
+        )?);
+        let trie_db = Arc::new(RocksTrieDB::with_options(
+            self.config.data_path_for_state(),
+            self.config.executor.light,
+            self.config.rocksdb.max_open_files,
+            self.config.rocksdb.state_options(),
+        )?);
+
+        let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+        rt.block_on(check_db(rocks_adapter, trie_db, truncate))
+    }
+
+    /// Export chain data for analytics, returns the last exported height.
+    pub fn export(self, options: ExportOptions) -> ProtocolResult<Option<u64>> {
+        let rocks_adapter = Arc::new(RocksAdapter::with_options(
+            self.config.data_path_for_block(),
+            self.config.rocksdb.max_open_files,
+            self.config.rocksdb.storage_options(),
+        )?);
+        let storage = Arc::new(ImplStorage::new(rocks_adapter));
+
+        let mut rt = tokio::runtime::Runtime::new().expect("new tokio runtime");
+        rt.block_on(export(storage, options))
+    }
+
     async fn create_genesis(&self) -> ProtocolResult<Block> {
         create_genesis(
             &self.config,
@@ -133,6 +174,9 @@ pub enum MainError {
     #[display(fmt = "Toml fails to parse genesis {:?}", _0)]
     GenesisTomlDe(toml::de::Error),
 
+    #[display(fmt = "json error {:?}", _0)]
+    Json(serde_json::Error),
+
     #[display(fmt = "hex error {:?}", _0)]
     FromHex(hex::FromHexError),
 
@@ -5,7 +5,10 @@ use common_crypto::{
 };
 use protocol::{
     fixed_codec::FixedCodec,
-    types::{Hash, JsonString, RawTransaction, SignedTransaction, TransactionRequest},
+    types::{
+        Hash, JsonString, PubkeyKind, RawTransaction, SignatureScheme, SignedTransaction,
+        TransactionRequest,
+    },
     Bytes, BytesMut,
 };
 use rand::{rngs::OsRng, RngCore};
@@ -106,6 +109,8 @@ impl SignedTransactionBuilder {
             tx_hash,
             pubkey: pk.pub_key().to_bytes(),
             signature: sig.to_bytes(),
+            scheme: SignatureScheme::Secp256k1,
+            kind: PubkeyKind::Single,
         }
     }
 }
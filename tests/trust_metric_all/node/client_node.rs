@@ -307,6 +307,7 @@ fn mock_block(height: u64) -> Block {
         receipt_root: Vec::new(),
         cycles_used: vec![999_999],
         proposer: Address::from_hex(addr_str).unwrap(),
+        seed_signature: Bytes::new(),
         proof,
         validator_version: 1,
         validators: Vec::new(),
@@ -39,7 +39,9 @@ use core_mempool::{
 use core_network::{NetworkConfig, NetworkService};
 use core_storage::ImplStorage;
 use framework::executor::{ServiceExecutor, ServiceExecutorFactory};
-use protocol::traits::{APIAdapter, Context, MemPool, NodeInfo, ServiceMapping, Storage};
+use protocol::traits::{
+    APIAdapter, Context, ExecutorOptions, MemPool, NodeInfo, ServiceMapping, Storage,
+};
 use protocol::types::{Address, Block, BlockHeader, Genesis, Hash, Metadata, Proof, Validator};
 use protocol::{fixed_codec::FixedCodec, ProtocolResult};
 
@@ -101,6 +103,7 @@ pub async fn create_genesis<Mapping: 'static + ServiceMapping>(
         receipt_root: vec![],
         cycles_used: vec![],
         proposer: Address::from_hex("0x0000000000000000000000000000000000000000")?,
+        seed_signature: Bytes::new(),
         proof: Proof {
             height:     0,
             round:      0,
@@ -343,6 +346,7 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             Arc::clone(&service_mapping),
             status_agent.clone(),
             Arc::clone(&crypto),
+            ExecutorOptions::default(),
         )?;
 
     let exec_demon = consensus_adapter.take_exec_demon();
@@ -370,7 +374,8 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
     ));
 
     // Re-execute block from exec_height + 1 to current_height, so that init the
-    // lost current status.
+    // lost current status. The blocks are committed before the executor saves
+    // their receipts, so save them again in case the node stopped in between.
     log::info!("Re-execute from {} to {}", exec_height + 1, current_height);
     for height in exec_height + 1..=current_height {
         let block = storage.get_block_by_height(Context::new(), height).await?;
@@ -378,9 +383,11 @@ pub async fn start<Mapping: 'static + ServiceMapping>(
             .get_transactions(Context::new(), block.ordered_tx_hashes.clone())
             .await?;
         let rich_block = RichBlock { block, txs };
-        let _ = synchronization
+        let resp = synchronization
             .exec_block(Context::new(), rich_block, status_agent.clone())
             .await?;
+        let receipts = resp.all_receipts().cloned().collect();
+        storage.insert_receipts(Context::new(), receipts).await?;
     }
 
     // register consensus
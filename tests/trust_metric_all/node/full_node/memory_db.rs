@@ -2,7 +2,10 @@ use derive_more::Display;
 use protocol::{
     async_trait,
     codec::ProtocolCodecSync,
-    traits::{StorageAdapter, StorageBatchModify, StorageSchema},
+    traits::{
+        StorageAdapter, StorageBatch, StorageBatchModify, StorageCategory, StorageIterDirection,
+        StorageScanMode, StorageSchema,
+    },
     Bytes, ProtocolError, ProtocolErrorKind, ProtocolResult,
 };
 
@@ -165,4 +168,50 @@ impl StorageAdapter for MemoryDB {
 
         Ok(())
     }
+
+    async fn write_batch(&self, batch: StorageBatch) -> ProtocolResult<()> {
+        let mut db = self.write().unwrap();
+
+        for (_, key, value) in batch.into_ops().into_iter() {
+            match value {
+                Some(value) => db.insert(key.to_vec(), value.to_vec()),
+                None => db.remove(&key.to_vec()),
+            };
+        }
+
+        Ok(())
+    }
+
+    // Categories share one map here, so a scan may return keys of every
+    // category.
+    async fn scan(
+        &self,
+        _: StorageCategory,
+        mode: StorageScanMode,
+        direction: StorageIterDirection,
+        limit: usize,
+    ) -> ProtocolResult<Vec<(Bytes, Bytes)>> {
+        let db = self.read().unwrap();
+        let mut pairs = db
+            .iter()
+            .filter(|(key, _)| match (&mode, direction) {
+                (StorageScanMode::Prefix(prefix), _) => key.starts_with(prefix),
+                (StorageScanMode::From(from), StorageIterDirection::Forward) => {
+                    key.as_slice() >= from.as_ref()
+                }
+                (StorageScanMode::From(from), StorageIterDirection::Reverse) => {
+                    key.as_slice() <= from.as_ref()
+                }
+            })
+            .map(|(key, val)| (Bytes::from(key.clone()), Bytes::from(val.clone())))
+            .collect::<Vec<_>>();
+
+        pairs.sort_by(|a, b| a.0.cmp(&b.0));
+        if direction == StorageIterDirection::Reverse {
+            pairs.reverse();
+        }
+        pairs.truncate(limit);
+
+        Ok(pairs)
+    }
 }